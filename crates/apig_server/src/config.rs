@@ -1,6 +1,8 @@
-use anyhow::{Context, Result};
+use anyhow::{anyhow, Context, Result};
 use config::{Config, Environment, File, FileFormat};
 use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fmt};
+use temporal_sdk_helpers::{TemporalInteraction, TemporalTarget, TemporalTls};
 
 #[derive(Serialize, PartialEq, Eq, Deserialize, Debug)]
 #[allow(non_camel_case_types)]
@@ -15,8 +17,131 @@ pub enum Environments {
 pub struct ApigConfig {
     pub temporal_service_host: String,
     pub temporal_service_port: String,
+    /// connect to Temporal with TLS. On when any other `temporal_tls_*` or the api key is set
+    pub temporal_tls_enabled: Option<bool>,
+    /// PEM bundle of the CAs that signed Temporal's certificate, the system's roots when unset
+    pub temporal_tls_ca_cert_path: Option<String>,
+    /// PEM client certificate for mTLS, set with `temporal_tls_client_key_path`
+    pub temporal_tls_client_cert_path: Option<String>,
+    pub temporal_tls_client_key_path: Option<String>,
+    /// name Temporal's certificate is checked against, when it isn't `temporal_service_host`
+    pub temporal_tls_server_name: Option<String>,
+    /// sent as a bearer token with every call to Temporal, e.g. a Temporal Cloud api key
+    pub temporal_api_key: Option<String>,
     pub environment: Environments,
     pub apig_port: String,
+    /// Slack apps allowed to send events to the `/slack` routes, keyed by `api_app_id` or `team_id`.
+    ///
+    /// set with env vars like `SLACK_APPS__<APP_ID>__SIGNING_SECRET`.
+    /// `config` lowercases all keys, so lookups should go through `find_slack_app`
+    #[serde(default)]
+    pub slack_apps: HashMap<String, SlackAppConfig>,
+    /// comma separated `key_id:secret` pairs for signed encoder versions. The first key signs new strings
+    pub encoder_signing_keys: Option<String>,
+    /// refuse to decode strings that aren't signed by one of `encoder_signing_keys`
+    #[serde(default)]
+    pub require_signed_encoding: bool,
+    /// comma separated encoder versions to decode, e.g. `S,X`. Every registered version is accepted when unset
+    pub accepted_encoder_versions: Option<String>,
+    /// send `Terminate` interactions to Temporal. Off in stage/prod unless set, like the raw `/temporal` route
+    pub terminate_enabled: Option<bool>,
+}
+
+impl ApigConfig {
+    /// the Temporal frontend interactions are sent to, and how to connect to it
+    pub fn temporal_target(&self) -> Result<TemporalTarget> {
+        let mut target =
+            TemporalTarget::new(&self.temporal_service_host, &self.temporal_service_port);
+
+        let tls = TemporalTls {
+            ca_cert_path: self.temporal_tls_ca_cert_path.as_ref().map(Into::into),
+            client_cert_path: self.temporal_tls_client_cert_path.as_ref().map(Into::into),
+            client_key_path: self.temporal_tls_client_key_path.as_ref().map(Into::into),
+            server_name: self.temporal_tls_server_name.clone(),
+        };
+        if tls.client_cert_path.is_some() != tls.client_key_path.is_some() {
+            return Err(anyhow!(
+                "set both temporal_tls_client_cert_path and temporal_tls_client_key_path for mTLS"
+            ));
+        }
+
+        // api keys are bearer tokens, which shouldn't be sent in plaintext
+        let tls_configured = tls != TemporalTls::default() || self.temporal_api_key.is_some();
+        match self.temporal_tls_enabled {
+            Some(false) if tls_configured => {
+                return Err(anyhow!(
+                    "temporal_tls_enabled is false, but TLS settings or an api key are set"
+                ))
+            }
+            Some(true) => target = target.with_tls(tls),
+            _ if tls_configured => target = target.with_tls(tls),
+            _ => {}
+        }
+
+        if let Some(api_key) = &self.temporal_api_key {
+            target = target.with_api_key(api_key);
+        }
+
+        Ok(target)
+    }
+
+    /// which interactions the handlers may send to Temporal
+    pub fn interaction_policy(&self) -> InteractionPolicy {
+        InteractionPolicy {
+            terminate_enabled: self.terminate_enabled.unwrap_or(!matches!(
+                self.environment,
+                Environments::stage | Environments::prod
+            )),
+        }
+    }
+}
+
+/// Interactions that are refused before anything is sent to Temporal
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct InteractionPolicy {
+    pub terminate_enabled: bool,
+}
+
+impl InteractionPolicy {
+    pub fn check(&self, interaction: &TemporalInteraction) -> Result<(), DisabledInteractionError> {
+        match interaction {
+            TemporalInteraction::Terminate(_) if !self.terminate_enabled => {
+                Err(DisabledInteractionError(interaction.to_type_string()))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// The interaction type is disabled by `InteractionPolicy`
+#[derive(Debug)]
+pub struct DisabledInteractionError(pub String);
+
+impl fmt::Display for DisabledInteractionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} interactions are disabled on this gateway", self.0)
+    }
+}
+
+impl std::error::Error for DisabledInteractionError {}
+
+#[derive(Serialize, PartialEq, Deserialize, Eq, Debug, Clone)]
+pub struct SlackAppConfig {
+    /// used to verify that requests were sent by this Slack app
+    pub signing_secret: String,
+    /// used to call the Slack API as this app
+    pub bot_token: Option<String>,
+}
+
+/// case-insensitive lookup of a Slack app by its `api_app_id` or `team_id`
+pub fn find_slack_app<'a>(
+    slack_apps: &'a HashMap<String, SlackAppConfig>,
+    app_or_team_id: &str,
+) -> Option<&'a SlackAppConfig> {
+    slack_apps
+        .iter()
+        .find(|(id, _)| id.eq_ignore_ascii_case(app_or_team_id))
+        .map(|(_, app)| app)
 }
 
 pub fn init_config_from_env_and_file() -> Result<ApigConfig> {
@@ -25,7 +150,7 @@ pub fn init_config_from_env_and_file() -> Result<ApigConfig> {
         .unwrap()
         .add_source(File::new(".default.env", FileFormat::Ini).required(true))
         .add_source(File::new(".env", FileFormat::Ini).required(false))
-        .add_source(Environment::default())
+        .add_source(Environment::default().separator("__"))
         .build()?
         .try_deserialize()
         .with_context(|| "missing required config variables")
@@ -2,23 +2,28 @@ mod config;
 mod slack;
 mod versions;
 
-use crate::config::{init_config_from_env_and_file, Environments};
+use crate::config::{
+    init_config_from_env_and_file, ApigConfig, DisabledInteractionError, Environments,
+    InteractionPolicy,
+};
 use axum::{
+    extract::{Extension, Query},
     http::StatusCode,
     response::{IntoResponse, Response},
     routing::{get, post},
     Json, Router,
 };
-use slack::axum_apig_handler_slack_interactions_api;
+use slack::{axum_apig_handler_slack_interactions_api, SlackVerificationLayer};
 use std::net::SocketAddr;
 use temporal_sdk_helpers::{
-    execute_interaction, Encoder, TemporalInteraction, TEMPORAL_HOST_PORT_PAIR,
+    codec, execute_interaction, schema, set_signing_keys, DecodeError, EncodedLengthError, Encoder,
+    Profile, SigningKeys, TemporalClients, TemporalInteraction,
 };
 use tower_http::trace::TraceLayer;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 use versions::ApiVersion;
 
-fn create_router(environment: Environments) -> Router {
+fn create_router(config: &ApigConfig, temporal_clients: TemporalClients) -> Router {
     // keep slack routes separate so we can add Slack Verification layer, shared client, etc
     // /api/:version/slack
     let slack_router = Router::new()
@@ -26,6 +31,7 @@ fn create_router(environment: Environments) -> Router {
             "/interaction",
             post(axum_apig_handler_slack_interactions_api),
         )
+        .layer(SlackVerificationLayer::new(config.slack_apps.clone()))
         .layer(TraceLayer::new_for_http());
 
     // /api/:version
@@ -36,16 +42,26 @@ fn create_router(environment: Environments) -> Router {
     // /api/:version/temporal
     let temporal_router = Router::new()
         .route("/encode", post(temporal_encoder))
-        .route("/decode", post(temporal_decoder));
+        .route("/schema/interaction", get(temporal_interaction_schema))
+        .route(
+            "/schema/response",
+            get(temporal_interaction_response_schema),
+        );
 
-    // disable non-slack event processing routes in prod/stage until api auth is set up
-    let temporal_router = match environment {
+    // disable non-slack event processing routes in prod/stage until api auth is set up.
+    // `/decode` opens `X` strings with the gateway's key, so it would reveal what they hide
+    let temporal_router = match config.environment {
         Environments::stage | Environments::prod => temporal_router,
-        _ => temporal_router.route("/", post(temporal_interaction_handler)),
+        _ => temporal_router
+            .route("/", post(temporal_interaction_handler))
+            .route("/decode", post(temporal_decoder)),
     }
     .layer(TraceLayer::new_for_http());
 
-    let versioned_api_router = versioned_api_router.nest("/temporal", temporal_router);
+    let versioned_api_router = versioned_api_router
+        .nest("/temporal", temporal_router)
+        .layer(Extension(config.interaction_policy()))
+        .layer(Extension(temporal_clients));
 
     Router::new().nest("/api/:version", versioned_api_router)
 }
@@ -54,16 +70,33 @@ fn create_router(environment: Environments) -> Router {
 async fn main() {
     let config = init_config_from_env_and_file().expect("unable to build app config");
 
-    TEMPORAL_HOST_PORT_PAIR
-        .set((config.temporal_service_host, config.temporal_service_port))
-        .expect("shouldn't fail");
+    let signing_keys =
+        SigningKeys::from_key_list(config.encoder_signing_keys.as_deref().unwrap_or_default())
+            .expect("invalid encoder signing keys")
+            .with_require_signature(config.require_signed_encoding);
+    set_signing_keys(signing_keys).expect("shouldn't fail");
+
+    if let Some(versions) = &config.accepted_encoder_versions {
+        let versions: Vec<&str> = versions.split(',').map(str::trim).collect();
+        codec::accept_only_versions(&versions).expect("invalid accepted encoder versions");
+    }
 
     // TODO: add temporal cluster connection check before starting the webserver
+    // connects on the first interaction, then every request shares the connection
+    let temporal_clients = TemporalClients::new(
+        config
+            .temporal_target()
+            .expect("invalid temporal connection config"),
+    );
 
     init_tracing();
+    tracing::info!(
+        "accepting encoder versions {}",
+        codec::accepted_versions().join(", ")
+    );
 
     // build our application with versioned routes
-    let app = create_router(config.environment);
+    let app = create_router(&config, temporal_clients);
     // run it
     let addr = SocketAddr::from((
         [0, 0, 0, 0],
@@ -88,14 +121,31 @@ async fn version_confidence_check(api_version: ApiVersion) -> String {
     message
 }
 
+#[derive(serde::Deserialize)]
+struct TemporalEncoderParams {
+    /// fail if the encoded string is too long for this platform field
+    profile: Option<Profile>,
+}
+
 async fn temporal_encoder(
     api_version: ApiVersion,
+    Query(params): Query<TemporalEncoderParams>,
     Json(payload): Json<TemporalInteraction>,
-) -> Result<impl IntoResponse, AppError> {
+) -> Result<Response, AppError> {
     match api_version {
         ApiVersion::V1 => {
-            let encoded_string = Encoder::default().encode(payload);
-            Ok((StatusCode::CREATED, encoded_string))
+            let encoded = match params.profile {
+                Some(profile) => Encoder::default().encode_checked(payload, profile),
+                None => Encoder::default().encode(payload),
+            };
+
+            match encoded {
+                Ok(encoded_string) => Ok((StatusCode::CREATED, encoded_string).into_response()),
+                Err(err) if err.is::<EncodedLengthError>() => {
+                    Ok((StatusCode::UNPROCESSABLE_ENTITY, err.to_string()).into_response())
+                }
+                Err(err) => Err(err.into()),
+            }
         }
     }
 }
@@ -119,13 +169,30 @@ async fn temporal_decoder(
     }
 }
 
+/// JSON Schema of what the `/` route accepts, and what `/encode` & `/decode` exchange
+async fn temporal_interaction_schema(api_version: ApiVersion) -> impl IntoResponse {
+    match api_version {
+        ApiVersion::V1 => Json(schema::temporal_interaction_schema()),
+    }
+}
+
+/// JSON Schema of what the `/` route returns
+async fn temporal_interaction_response_schema(api_version: ApiVersion) -> impl IntoResponse {
+    match api_version {
+        ApiVersion::V1 => Json(schema::temporal_interaction_response_schema()),
+    }
+}
+
 async fn temporal_interaction_handler(
     api_version: ApiVersion,
+    Extension(interaction_policy): Extension<InteractionPolicy>,
+    Extension(temporal_clients): Extension<TemporalClients>,
     Json(payload): Json<TemporalInteraction>,
 ) -> Result<impl IntoResponse, AppError> {
     match api_version {
         ApiVersion::V1 => {
-            let temporal_response = execute_interaction(payload).await?;
+            interaction_policy.check(&payload)?;
+            let temporal_response = execute_interaction(&temporal_clients, payload).await?;
             Ok((StatusCode::CREATED, Json(temporal_response)))
         }
     }
@@ -149,6 +216,26 @@ pub struct AppError(anyhow::Error);
 // Tell axum how to convert `AppError` into a response.
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
+        // a bad encoded string is the client's mistake, not ours
+        if let Some(decode_error) = self.0.downcast_ref::<DecodeError>() {
+            let status_code = decode_error_status_code(decode_error);
+            if !status_code.is_server_error() {
+                return (
+                    status_code,
+                    format!(
+                        "Invalid encoded string ({}): {}",
+                        decode_error.code(),
+                        decode_error
+                    ),
+                )
+                    .into_response();
+            }
+        }
+
+        if let Some(disabled) = self.0.downcast_ref::<DisabledInteractionError>() {
+            return (StatusCode::FORBIDDEN, disabled.to_string()).into_response();
+        }
+
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             format!("Something went wrong: {}", self.0),
@@ -157,6 +244,24 @@ impl IntoResponse for AppError {
     }
 }
 
+/// Every variant is listed, so a new one has to be sorted into the client's or our mistakes
+fn decode_error_status_code(decode_error: &DecodeError) -> StatusCode {
+    match decode_error {
+        DecodeError::MalformedVersion
+        | DecodeError::UnknownVersion { .. }
+        | DecodeError::MalformedPair(_)
+        | DecodeError::UnknownKey(_)
+        | DecodeError::MissingKey(_)
+        | DecodeError::UnknownEventType(_)
+        | DecodeError::MalformedValue { .. }
+        | DecodeError::MalformedSection(_) => StatusCode::BAD_REQUEST,
+        DecodeError::Unsigned(_) | DecodeError::BadSignature(_) => StatusCode::FORBIDDEN,
+        DecodeError::Expired { .. } => StatusCode::GONE,
+        // e.g. invalid signing keys, which is our misconfiguration & should be alerted on
+        DecodeError::Other(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
 // This enables using `?` on functions that return `Result<_, anyhow::Error>` to turn them into
 // `Result<_, AppError>`. That way you don't need to do that manually.
 impl<E> From<E> for AppError
@@ -173,14 +278,71 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::SlackAppConfig;
     use axum::{
         body::{Body, Bytes},
         http::{self, Request, StatusCode},
     };
     use mime;
     use serde_json::json;
+    use std::collections::HashMap;
+    use temporal_sdk_helpers::{
+        TemporalInteractionResponse, TemporalTls, TemporalUpdateResponse, UpdateWaitStage,
+    };
     use tower::ServiceExt; // for `oneshot` and `ready`
 
+    const TEST_SLACK_APP_ID: &str = "A0TESTAPP";
+    const TEST_SLACK_SIGNING_SECRET: &str = "8f742231b10e8888abcd99yyyzzz85a5";
+    const TEST_SLACK_TEAM_ID: &str = "T0TESTTEAM";
+    const TEST_SLACK_TEAM_SIGNING_SECRET: &str = "c2f1a9d0e3b4a5c6d7e8f9a0b1c2d3e4";
+
+    fn build_test_config() -> ApigConfig {
+        ApigConfig {
+            temporal_service_host: "127.0.0.1".into(),
+            // nothing listens on port 1, so tests never reach a developer's local Temporal
+            temporal_service_port: "1".into(),
+            temporal_tls_enabled: None,
+            temporal_tls_ca_cert_path: None,
+            temporal_tls_client_cert_path: None,
+            temporal_tls_client_key_path: None,
+            temporal_tls_server_name: None,
+            temporal_api_key: None,
+            environment: Environments::local,
+            apig_port: "3000".into(),
+            // keys are lowercase like they are when loaded by `config`
+            slack_apps: HashMap::from([
+                (
+                    TEST_SLACK_APP_ID.to_lowercase(),
+                    SlackAppConfig {
+                        signing_secret: TEST_SLACK_SIGNING_SECRET.into(),
+                        bot_token: None,
+                    },
+                ),
+                (
+                    TEST_SLACK_TEAM_ID.to_lowercase(),
+                    SlackAppConfig {
+                        signing_secret: TEST_SLACK_TEAM_SIGNING_SECRET.into(),
+                        bot_token: None,
+                    },
+                ),
+            ]),
+            encoder_signing_keys: None,
+            require_signed_encoding: false,
+            accepted_encoder_versions: None,
+            terminate_enabled: None,
+        }
+    }
+
+    /// the interaction got past the gateway's checks, and failed connecting to the unreachable test Temporal
+    fn assert_sent_to_temporal(body: &[u8]) {
+        let body = String::from_utf8_lossy(body);
+        assert!(
+            body.contains("Failed to create Temporal Client at url http://127.0.0.1:1/"),
+            "{}",
+            body
+        );
+    }
+
     async fn oneshot(
         method: &str,
         uri: &str,
@@ -188,19 +350,31 @@ mod tests {
         assert_statuscode: StatusCode,
         mime_type: mime::Mime,
     ) -> Bytes {
-        let app = create_router(Environments::local).into_service();
-
         let request = Request::builder()
             .uri(uri)
             .method(method)
-            .header(http::header::CONTENT_TYPE, mime_type.as_ref());
+            .header(http::header::CONTENT_TYPE, mime_type.as_ref())
+            .body(body)
+            .expect("request body is invalid");
+
+        oneshot_request(request, assert_statuscode).await
+    }
+
+    async fn oneshot_request(request: Request<Body>, assert_statuscode: StatusCode) -> Bytes {
+        oneshot_request_with_config(request, assert_statuscode, &build_test_config()).await
+    }
+
+    async fn oneshot_request_with_config(
+        request: Request<Body>,
+        assert_statuscode: StatusCode,
+        config: &ApigConfig,
+    ) -> Bytes {
+        let temporal_clients = TemporalClients::new(config.temporal_target().unwrap());
+        let app = create_router(config, temporal_clients).into_service();
 
         // `Router` implements `tower::Service<Request<Body>>` so we can
         // call it like any tower service, no need to run an HTTP server.
-        let response = app
-            .oneshot(request.body(body).expect("request body is invalid"))
-            .await
-            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
 
         assert_eq!(
             response.status(),
@@ -295,4 +469,607 @@ mod tests {
 
         assert_eq!("A~E:Signal,W:some-workflow-id,N:my-namespace,T:my-taskqueue,R:some-run-id,S:my_signal_name", body);
     }
+
+    #[tokio::test]
+    async fn test_encode_endpoint_with_profile() {
+        let signal_temporal_json = json!({
+            "type" : "Signal",
+            "namespace" : "my-namespace",
+            "task_queue": "my-taskqueue",
+            "run_id": "some-run-id",
+            "workflow_id":"some-workflow-id",
+            "signal_name": "my_signal_name"
+        });
+
+        let body = oneshot(
+            "POST",
+            "/api/v1/temporal/encode?profile=slack_action_id",
+            Body::from(serde_json::to_vec(&signal_temporal_json).unwrap()),
+            StatusCode::CREATED,
+            mime::APPLICATION_JSON,
+        )
+        .await;
+        assert_eq!("A~E:Signal,W:some-workflow-id,N:my-namespace,T:my-taskqueue,R:some-run-id,S:my_signal_name", body);
+
+        let body = oneshot(
+            "POST",
+            "/api/v1/temporal/encode?profile=telegram_callback_data",
+            Body::from(serde_json::to_vec(&signal_temporal_json).unwrap()),
+            StatusCode::UNPROCESSABLE_ENTITY,
+            mime::APPLICATION_JSON,
+        )
+        .await;
+        assert!(String::from_utf8_lossy(&body)
+            .contains("26 over the telegram_callback_data limit of 64"));
+
+        oneshot(
+            "POST",
+            "/api/v1/temporal/encode?profile=not_a_platform",
+            Body::from(serde_json::to_vec(&signal_temporal_json).unwrap()),
+            StatusCode::BAD_REQUEST,
+            mime::APPLICATION_JSON,
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_schema_endpoints() {
+        let body = oneshot(
+            "GET",
+            "/api/v1/temporal/schema/interaction",
+            Body::empty(),
+            StatusCode::OK,
+            mime::APPLICATION_JSON,
+        )
+        .await;
+        let schema: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(schema["title"], "TemporalInteraction");
+        assert_eq!(schema["oneOf"].as_array().unwrap().len(), 8);
+
+        let body = oneshot(
+            "GET",
+            "/api/v1/temporal/schema/response",
+            Body::empty(),
+            StatusCode::OK,
+            mime::APPLICATION_JSON,
+        )
+        .await;
+        let schema: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(schema["title"], "TemporalInteractionResponse");
+    }
+
+    #[tokio::test]
+    async fn test_decode_endpoint_bad_encoded_string() {
+        let body = oneshot(
+            "POST",
+            "/api/v1/temporal/decode",
+            Body::from(
+                serde_json::to_vec(&json!({"encoded": "A~E:Signal,N:my-namespace,T:my-taskqueue"}))
+                    .unwrap(),
+            ),
+            StatusCode::BAD_REQUEST,
+            mime::APPLICATION_JSON,
+        )
+        .await;
+        assert!(String::from_utf8_lossy(&body).contains("(MISSING_KEY)"));
+
+        oneshot(
+            "POST",
+            "/api/v1/temporal/decode",
+            Body::from(serde_json::to_vec(&json!({"encoded": "Z9~E:Signal"})).unwrap()),
+            StatusCode::BAD_REQUEST,
+            mime::APPLICATION_JSON,
+        )
+        .await;
+
+        let expired = format!(
+            "A~E:Signal,D:{},W:my-workflow,N:my-namespace,T:my-taskqueue,S:my_signal_name",
+            now_unix_secs() - 1
+        );
+        oneshot(
+            "POST",
+            "/api/v1/temporal/decode",
+            Body::from(serde_json::to_vec(&json!({ "encoded": expired })).unwrap()),
+            StatusCode::GONE,
+            mime::APPLICATION_JSON,
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_decode_endpoint_disabled_in_prod() {
+        let signing_keys = SigningKeys::default()
+            .with_key("k1", "test-secret")
+            .unwrap();
+        let sealed = Encoder::X
+            .encode_with_keys(
+                serde_json::from_value(json!({
+                    "type": "Signal",
+                    "namespace": "my-namespace",
+                    "task_queue": "my-taskqueue",
+                    "workflow_id": "my-workflow",
+                    "signal_name": "my_signal_name"
+                }))
+                .unwrap(),
+                &signing_keys,
+            )
+            .unwrap();
+        assert!(sealed.starts_with("X~"));
+
+        let config = ApigConfig {
+            environment: Environments::prod,
+            ..build_test_config()
+        };
+        let request = Request::builder()
+            .uri("/api/v1/temporal/decode")
+            .method("POST")
+            .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+            .body(Body::from(json!({ "encoded": sealed }).to_string()))
+            .unwrap();
+
+        let body = oneshot_request_with_config(request, StatusCode::NOT_FOUND, &config).await;
+        assert!(!String::from_utf8_lossy(&body).contains("my-workflow"));
+    }
+
+    #[tokio::test]
+    async fn test_bad_signing_keys_are_a_server_error() {
+        let err = SigningKeys::from_key_list("k1-without-a-secret").unwrap_err();
+        let response = AppError::from(DecodeError::Other(err)).into_response();
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        assert!(String::from_utf8_lossy(&body).contains("`key_id:secret`"));
+    }
+
+    fn build_slack_form_body(payload: serde_json::Value) -> String {
+        serde_urlencoded::to_string([("payload", payload.to_string())]).unwrap()
+    }
+
+    fn build_slack_request(
+        signing_secret: &str,
+        timestamp: u64,
+        signed_body: &str,
+        sent_body: &str,
+    ) -> Request<Body> {
+        let timestamp = timestamp.to_string();
+        let signature =
+            slack::sign_slack_request(signing_secret, &timestamp, signed_body.as_bytes());
+
+        Request::builder()
+            .uri("/api/v1/slack/interaction")
+            .method("POST")
+            .header(
+                http::header::CONTENT_TYPE,
+                mime::APPLICATION_WWW_FORM_URLENCODED.as_ref(),
+            )
+            .header(slack::SLACK_TIMESTAMP_HEADER, timestamp)
+            .header(slack::SLACK_SIGNATURE_HEADER, signature)
+            .body(Body::from(sent_body.to_string()))
+            .expect("request body is invalid")
+    }
+
+    fn now_unix_secs() -> u64 {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+    }
+
+    #[tokio::test]
+    async fn test_slack_verification_valid_signature() {
+        let form_body =
+            build_slack_form_body(json!({"api_app_id": TEST_SLACK_APP_ID, "type": "unknown"}));
+        let request = build_slack_request(
+            TEST_SLACK_SIGNING_SECRET,
+            now_unix_secs(),
+            &form_body,
+            &form_body,
+        );
+
+        // verification passes, so the handler runs and fails to parse the payload
+        let body = oneshot_request(request, StatusCode::INTERNAL_SERVER_ERROR).await;
+
+        assert!(String::from_utf8_lossy(&body).contains("failed to read slack interaction event"))
+    }
+
+    #[tokio::test]
+    async fn test_slack_verification_secret_chosen_by_team_id() {
+        let form_body =
+            build_slack_form_body(json!({"team": {"id": TEST_SLACK_TEAM_ID}, "type": "unknown"}));
+        let request = build_slack_request(
+            TEST_SLACK_TEAM_SIGNING_SECRET,
+            now_unix_secs(),
+            &form_body,
+            &form_body,
+        );
+
+        oneshot_request(request, StatusCode::INTERNAL_SERVER_ERROR).await;
+    }
+
+    #[tokio::test]
+    async fn test_slack_verification_signed_by_other_app() {
+        let form_body =
+            build_slack_form_body(json!({"api_app_id": TEST_SLACK_APP_ID, "type": "unknown"}));
+        let request = build_slack_request(
+            TEST_SLACK_TEAM_SIGNING_SECRET,
+            now_unix_secs(),
+            &form_body,
+            &form_body,
+        );
+
+        let body = oneshot_request(request, StatusCode::UNAUTHORIZED).await;
+
+        assert!(String::from_utf8_lossy(&body).contains("signature does not match"))
+    }
+
+    #[tokio::test]
+    async fn test_slack_verification_unknown_app() {
+        let form_body =
+            build_slack_form_body(json!({"api_app_id": "A0UNKNOWN", "type": "unknown"}));
+        let request = build_slack_request(
+            TEST_SLACK_SIGNING_SECRET,
+            now_unix_secs(),
+            &form_body,
+            &form_body,
+        );
+
+        let body = oneshot_request(request, StatusCode::UNAUTHORIZED).await;
+
+        assert!(String::from_utf8_lossy(&body).contains("no slack app configured"))
+    }
+
+    #[tokio::test]
+    async fn test_slack_verification_stale_timestamp() {
+        let form_body =
+            build_slack_form_body(json!({"api_app_id": TEST_SLACK_APP_ID, "type": "unknown"}));
+        let stale_timestamp = now_unix_secs() - slack::SLACK_REQUEST_MAX_AGE_SECS - 60;
+        let request = build_slack_request(
+            TEST_SLACK_SIGNING_SECRET,
+            stale_timestamp,
+            &form_body,
+            &form_body,
+        );
+
+        let body = oneshot_request(request, StatusCode::UNAUTHORIZED).await;
+
+        assert!(String::from_utf8_lossy(&body).contains("replay window"))
+    }
+
+    #[tokio::test]
+    async fn test_slack_verification_tampered_body() {
+        let request = build_slack_request(
+            TEST_SLACK_SIGNING_SECRET,
+            now_unix_secs(),
+            &build_slack_form_body(json!({"api_app_id": TEST_SLACK_APP_ID, "type": "original"})),
+            &build_slack_form_body(json!({"api_app_id": TEST_SLACK_APP_ID, "type": "tampered"})),
+        );
+
+        let body = oneshot_request(request, StatusCode::UNAUTHORIZED).await;
+
+        assert!(String::from_utf8_lossy(&body).contains("signature does not match"))
+    }
+
+    #[tokio::test]
+    async fn test_slack_verification_oversized_body() {
+        let form_body = build_slack_form_body(json!({
+            "api_app_id": TEST_SLACK_APP_ID,
+            "type": "unknown",
+            "padding": "x".repeat(slack::SLACK_REQUEST_MAX_BODY_BYTES)
+        }));
+        let request = build_slack_request(
+            TEST_SLACK_SIGNING_SECRET,
+            now_unix_secs(),
+            &form_body,
+            &form_body,
+        );
+
+        // refused before the signature is checked, even though it's signed
+        let body = oneshot_request(request, StatusCode::PAYLOAD_TOO_LARGE).await;
+
+        assert!(String::from_utf8_lossy(&body).contains("slack request body is over"))
+    }
+
+    #[tokio::test]
+    async fn test_slack_verification_missing_headers() {
+        let form_body =
+            build_slack_form_body(json!({"api_app_id": TEST_SLACK_APP_ID, "type": "unknown"}));
+
+        oneshot(
+            "POST",
+            "/api/v1/slack/interaction",
+            Body::from(form_body),
+            StatusCode::UNAUTHORIZED,
+            mime::APPLICATION_WWW_FORM_URLENCODED,
+        )
+        .await;
+    }
+
+    fn build_slack_block_actions_payload(action_id: &str) -> serde_json::Value {
+        json!({
+            "type": "block_actions",
+            "api_app_id": TEST_SLACK_APP_ID,
+            "team": {"id": TEST_SLACK_TEAM_ID},
+            "trigger_id": "1234.5678",
+            "container": {"type": "message", "message_ts": "1234.5678"},
+            "actions": [{"type": "button", "action_id": action_id}]
+        })
+    }
+
+    fn build_signal_callback_id(expires_at: u64) -> String {
+        Encoder::encode_default_from_json_string(
+            &json!({
+                "type": "Signal",
+                "namespace": "test-namespace",
+                "task_queue": "test-task-queue-rs",
+                "workflow_id": "some-super-long-uuid-string",
+                "signal_name": "signal_name_thats_defined_in_workflow",
+                "expires_at": expires_at
+            })
+            .to_string(),
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_slack_interaction_expired_callback_id() {
+        let callback_id = build_signal_callback_id(now_unix_secs() - 1);
+        let form_body = build_slack_form_body(build_slack_block_actions_payload(&callback_id));
+        let request = build_slack_request(
+            TEST_SLACK_SIGNING_SECRET,
+            now_unix_secs(),
+            &form_body,
+            &form_body,
+        );
+
+        // acknowledged without calling temporal
+        let body = oneshot_request(request, StatusCode::OK).await;
+        assert!(body.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_slack_interaction_unexpired_callback_id() {
+        let callback_id = build_signal_callback_id(now_unix_secs() + 60 * 60);
+        let form_body = build_slack_form_body(build_slack_block_actions_payload(&callback_id));
+        let request = build_slack_request(
+            TEST_SLACK_SIGNING_SECRET,
+            now_unix_secs(),
+            &form_body,
+            &form_body,
+        );
+
+        // not acknowledged as expired, but sent on to temporal
+        let body = oneshot_request(request, StatusCode::INTERNAL_SERVER_ERROR).await;
+        assert_sent_to_temporal(&body);
+    }
+
+    fn build_terminate_json() -> serde_json::Value {
+        json!({
+            "type": "Terminate",
+            "namespace": "test-namespace",
+            "task_queue": "test-task-queue-rs",
+            "workflow_id": "some-super-long-uuid-string",
+            "reason": "stuck"
+        })
+    }
+
+    #[test]
+    fn test_terminate_disabled_in_stage_and_prod() {
+        for (environment, terminate_enabled) in [
+            (Environments::local, true),
+            (Environments::dev, true),
+            (Environments::stage, false),
+            (Environments::prod, false),
+        ] {
+            let config = ApigConfig {
+                environment,
+                ..build_test_config()
+            };
+            assert_eq!(
+                config.interaction_policy().terminate_enabled,
+                terminate_enabled
+            );
+        }
+
+        let config = ApigConfig {
+            environment: Environments::prod,
+            terminate_enabled: Some(true),
+            ..build_test_config()
+        };
+        assert!(config.interaction_policy().terminate_enabled);
+    }
+
+    #[test]
+    fn test_temporal_connection_config() {
+        let plaintext = build_test_config().temporal_target().unwrap();
+        assert_eq!(plaintext.tls, None);
+        assert_eq!(plaintext.api_key, None);
+
+        // an api key turns TLS on, trusting the system's roots
+        let cloud = ApigConfig {
+            temporal_api_key: Some("test-api-key".into()),
+            ..build_test_config()
+        }
+        .temporal_target()
+        .unwrap();
+        assert_eq!(cloud.tls, Some(TemporalTls::default()));
+        assert_eq!(cloud.api_key.as_deref(), Some("test-api-key"));
+
+        let mtls = ApigConfig {
+            temporal_tls_ca_cert_path: Some("/certs/ca.pem".into()),
+            temporal_tls_client_cert_path: Some("/certs/apig.pem".into()),
+            temporal_tls_client_key_path: Some("/certs/apig.key".into()),
+            temporal_tls_server_name: Some("temporal.internal".into()),
+            ..build_test_config()
+        }
+        .temporal_target()
+        .unwrap();
+        assert_eq!(
+            mtls.tls,
+            Some(TemporalTls {
+                ca_cert_path: Some("/certs/ca.pem".into()),
+                client_cert_path: Some("/certs/apig.pem".into()),
+                client_key_path: Some("/certs/apig.key".into()),
+                server_name: Some("temporal.internal".into()),
+            })
+        );
+
+        let tls_only = ApigConfig {
+            temporal_tls_enabled: Some(true),
+            ..build_test_config()
+        };
+        assert_eq!(
+            tls_only.temporal_target().unwrap().tls,
+            Some(TemporalTls::default())
+        );
+
+        // half an mTLS pair, or TLS settings with TLS turned off, are mistakes
+        for config in [
+            ApigConfig {
+                temporal_tls_client_cert_path: Some("/certs/apig.pem".into()),
+                ..build_test_config()
+            },
+            ApigConfig {
+                temporal_tls_enabled: Some(false),
+                temporal_api_key: Some("test-api-key".into()),
+                ..build_test_config()
+            },
+        ] {
+            assert!(config.temporal_target().is_err(), "{:?}", config);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_disabled_terminate_is_forbidden() {
+        let build_request = || {
+            Request::builder()
+                .uri("/api/v1/temporal")
+                .method("POST")
+                .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .body(Body::from(build_terminate_json().to_string()))
+                .unwrap()
+        };
+
+        let config = ApigConfig {
+            terminate_enabled: Some(false),
+            ..build_test_config()
+        };
+        let body =
+            oneshot_request_with_config(build_request(), StatusCode::FORBIDDEN, &config).await;
+        assert!(String::from_utf8_lossy(&body).contains("Terminate interactions are disabled"));
+
+        // allowed through to temporal
+        let body = oneshot_request(build_request(), StatusCode::INTERNAL_SERVER_ERROR).await;
+        assert_sent_to_temporal(&body);
+    }
+
+    #[tokio::test]
+    async fn test_slack_interaction_terminate_disabled_in_prod() {
+        let callback_id =
+            Encoder::encode_default_from_json_string(&build_terminate_json().to_string()).unwrap();
+        let form_body = build_slack_form_body(build_slack_block_actions_payload(&callback_id));
+        let request = build_slack_request(
+            TEST_SLACK_SIGNING_SECRET,
+            now_unix_secs(),
+            &form_body,
+            &form_body,
+        );
+        let config = ApigConfig {
+            environment: Environments::prod,
+            ..build_test_config()
+        };
+
+        oneshot_request_with_config(request, StatusCode::FORBIDDEN, &config).await;
+    }
+
+    fn build_slack_view_submission_payload(callback_id: &str) -> serde_json::Value {
+        json!({
+            "type": "view_submission",
+            "team": {"id": TEST_SLACK_TEAM_ID},
+            "user": {"id": "U024BE7LH", "team_id": TEST_SLACK_TEAM_ID},
+            "view": {
+                "id": "V0123456789",
+                "team_id": TEST_SLACK_TEAM_ID,
+                "type": "modal",
+                "title": {"type": "plain_text", "text": "Access request"},
+                "blocks": [],
+                "callback_id": callback_id,
+                "state": {"values": {}},
+                "hash": "1234.abcd",
+                "app_id": TEST_SLACK_APP_ID,
+                "bot_id": "B0123456789"
+            }
+        })
+    }
+
+    #[tokio::test]
+    async fn test_slack_acts_on_update_response() {
+        let build_update_response = |result: Option<serde_json::Value>, failure: Option<&str>| {
+            TemporalInteractionResponse::Update(TemporalUpdateResponse {
+                update_id: "some-update-id".into(),
+                stage: UpdateWaitStage::Completed,
+                result: result.map(|result| vec![result]),
+                failure: failure.map(String::from),
+            })
+        };
+        let view_submission: slack_morphism::prelude::SlackInteractionEvent =
+            serde_json::from_value(build_slack_view_submission_payload("A~E:Update")).unwrap();
+        let response_body = |response: axum::response::Response| async move {
+            assert_eq!(response.status(), StatusCode::OK);
+            hyper::body::to_bytes(response.into_body()).await.unwrap()
+        };
+
+        // the workflow keeps the modal open with its validation errors
+        let errors = json!({"response_action": "errors", "errors": {"ticket": "unknown ticket"}});
+        let response = slack::respond_to_slack_interaction(
+            view_submission.clone(),
+            build_update_response(Some(errors.clone()), None),
+        );
+        let body = response_body(response).await;
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap(),
+            errors
+        );
+
+        // a failed update replaces the modal with the failure
+        let response = slack::respond_to_slack_interaction(
+            view_submission.clone(),
+            build_update_response(None, Some("ticket is already closed")),
+        );
+        let body: serde_json::Value =
+            serde_json::from_slice(&response_body(response).await).unwrap();
+        assert_eq!(body["response_action"], "update");
+        assert_eq!(
+            body["view"]["blocks"][0]["text"]["text"],
+            "ticket is already closed"
+        );
+
+        // any other result closes the modal
+        let response = slack::respond_to_slack_interaction(
+            view_submission,
+            build_update_response(Some(json!("approved")), None),
+        );
+        assert!(response_body(response).await.is_empty());
+
+        let block_actions: slack_morphism::prelude::SlackInteractionEvent =
+            serde_json::from_value(build_slack_block_actions_payload("A~E:Update")).unwrap();
+        let response = slack::respond_to_slack_interaction(
+            block_actions,
+            build_update_response(Some(errors), None),
+        );
+        assert!(response_body(response).await.is_empty());
+    }
+
+    #[test]
+    fn test_slack_data_args_include_user_data() {
+        let slack_event: slack_morphism::prelude::SlackInteractionEvent =
+            serde_json::from_value(build_slack_block_actions_payload("A~E:Signal")).unwrap();
+
+        let data_args = slack::build_slack_data_args(&slack_event, Some("approve".into())).unwrap();
+        assert_eq!(data_args.len(), 2);
+        assert_eq!(data_args[0]["type"], "block_actions");
+        assert_eq!(data_args[1], "approve");
+
+        let data_args = slack::build_slack_data_args(&slack_event, None).unwrap();
+        assert_eq!(data_args.len(), 1);
+    }
 }
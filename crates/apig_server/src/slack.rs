@@ -1,17 +1,46 @@
-use crate::{versions::ApiVersion, AppError};
-use anyhow::{anyhow, Result};
-use axum::{response::IntoResponse, Form, Json};
+use crate::{
+    config::{find_slack_app, InteractionPolicy, SlackAppConfig},
+    versions::ApiVersion,
+    AppError,
+};
+use anyhow::{anyhow, Context, Result};
+use axum::{
+    body::Body,
+    http::{header::CONTENT_TYPE, HeaderMap, Request, StatusCode},
+    response::{IntoResponse, Response},
+    Extension, Form, Json,
+};
+use hmac::{Hmac, Mac};
+use http_body::{LengthLimitError, Limited};
 use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::Sha256;
 use slack_morphism::prelude::*;
-use temporal_sdk_helpers::{execute_interaction, Encoder};
-use tracing::log::error;
+use std::{
+    collections::HashMap,
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context as TaskContext, Poll},
+    time::{SystemTime, UNIX_EPOCH},
+};
+use temporal_sdk_helpers::{
+    execute_interaction, DecodeError, Encoder, TemporalClients, TemporalInteractionResponse,
+    TemporalUpdateResponse,
+};
+use tower::{Layer, Service};
+use tracing::log::{error, warn};
 
 pub async fn axum_apig_handler_slack_interactions_api(
     api_version: ApiVersion,
+    Extension(interaction_policy): Extension<InteractionPolicy>,
+    Extension(temporal_clients): Extension<TemporalClients>,
     Form(body): Form<SlackInteractionWrapper>,
 ) -> Result<impl IntoResponse, AppError> {
     match api_version {
-        ApiVersion::V1 => handle_slack_interaction(body).await,
+        ApiVersion::V1 => {
+            handle_slack_interaction(body, interaction_policy, &temporal_clients).await
+        }
     }
 }
 
@@ -21,20 +50,41 @@ pub struct SlackInteractionWrapper {
     payload: String,
 }
 
+/// shown to the user instead of calling Temporal, when the callback_id's deadline has passed
+pub const SLACK_EXPIRED_REQUEST_MESSAGE: &str =
+    "This request has expired, so nothing was sent to the workflow.";
+
 pub async fn handle_slack_interaction(
     wrapper: SlackInteractionWrapper,
-) -> Result<impl IntoResponse, AppError> {
+    interaction_policy: InteractionPolicy,
+    temporal_clients: &TemporalClients,
+) -> Result<Response, AppError> {
     if let Ok(interaction_event) = serde_json::from_str::<SlackInteractionEvent>(&wrapper.payload) {
         let callback_id = get_callback_id_from_slack_interaction_event(interaction_event.clone())?;
-        let temporal_info_no_inputs = Encoder::decode(&callback_id)?;
-
-        let input_data = serde_json::to_value(&interaction_event)?;
+        let (temporal_info_no_inputs, user_data) =
+            match Encoder::decode_with_user_data(&callback_id) {
+                Ok(decoded) => decoded,
+                Err(err @ DecodeError::Expired { .. }) => {
+                    warn!("{:#}, not calling temporal", err);
+                    return Ok(respond_with_slack_message(
+                        interaction_event,
+                        "Request expired",
+                        SLACK_EXPIRED_REQUEST_MESSAGE,
+                    ));
+                }
+                Err(err) => return Err(err.into()),
+            };
+        interaction_policy.check(&temporal_info_no_inputs)?;
 
-        let temporal_info = temporal_info_no_inputs.add_data_args(Some(vec![input_data]));
+        let temporal_info = temporal_info_no_inputs
+            .add_data_args(Some(build_slack_data_args(&interaction_event, user_data)?));
 
-        let temporal_response = execute_interaction(temporal_info).await?;
+        let temporal_response = execute_interaction(temporal_clients, temporal_info).await?;
 
-        Ok(())
+        Ok(respond_to_slack_interaction(
+            interaction_event,
+            temporal_response,
+        ))
     } else {
         error!("Interaction event `payload` key is not valid json or does not deserialize to existing struct");
         error!("{:?}", &wrapper);
@@ -87,3 +137,351 @@ fn get_callback_id_from_slack_interaction_event(
 
     Ok(callback_id)
 }
+
+/// The args sent to the workflow: the interaction event, then the callback_id's user data if it has any
+/// so the workflow can tell which button or option was used.
+pub fn build_slack_data_args(
+    slack_event: &SlackInteractionEvent,
+    user_data: Option<String>,
+) -> Result<Vec<serde_json::Value>> {
+    let mut data_args = vec![serde_json::to_value(slack_event)?];
+    if let Some(user_data) = user_data {
+        data_args.push(serde_json::Value::String(user_data));
+    }
+
+    Ok(data_args)
+}
+
+/// Act on what the workflow returned. Only updates return anything,
+/// every other interaction is acknowledged with an empty `200`.
+pub fn respond_to_slack_interaction(
+    slack_event: SlackInteractionEvent,
+    temporal_response: TemporalInteractionResponse,
+) -> Response {
+    match temporal_response {
+        // e.g. the workflow's validator rejected a modal submission
+        TemporalInteractionResponse::Update(TemporalUpdateResponse {
+            failure: Some(failure),
+            ..
+        }) => respond_with_slack_message(slack_event, "Request failed", &failure),
+        // the update can return a `response_action` to keep the modal open with errors, or replace it
+        // https://api.slack.com/surfaces/modals/using#modifying
+        TemporalInteractionResponse::Update(TemporalUpdateResponse {
+            result: Some(result),
+            ..
+        }) if matches!(slack_event, SlackInteractionEvent::ViewSubmission(_)) => {
+            match result.into_iter().next() {
+                Some(response_action) if response_action.get("response_action").is_some() => {
+                    Json(response_action).into_response()
+                }
+                _ => StatusCode::OK.into_response(),
+            }
+        }
+        _ => StatusCode::OK.into_response(),
+    }
+}
+
+/// Tell the user something instead of what they asked for. Modals are updated in place,
+/// everything else gets an ephemeral message through the event's `response_url`.
+fn respond_with_slack_message(
+    slack_event: SlackInteractionEvent,
+    title: &str,
+    text: &str,
+) -> Response {
+    if let SlackInteractionEvent::ViewSubmission(_) = slack_event {
+        // https://api.slack.com/surfaces/modals/using#updating_response
+        return Json(json!({
+            "response_action": "update",
+            "view": {
+                "type": "modal",
+                "title": { "type": "plain_text", "text": title },
+                "blocks": [{
+                    "type": "section",
+                    "text": { "type": "plain_text", "text": text }
+                }]
+            }
+        }))
+        .into_response();
+    }
+
+    match get_response_url_from_slack_interaction_event(slack_event) {
+        // slack expects the interaction to be acknowledged within 3 seconds, so reply in the background
+        Some(response_url) => {
+            let message = json!({
+                "response_type": "ephemeral",
+                "replace_original": false,
+                "text": text,
+            });
+            tokio::spawn(async move {
+                if let Err(err) = post_to_slack_response_url(&response_url, &message).await {
+                    warn!("failed to send slack user an ephemeral message: {:#}", err);
+                }
+            });
+        }
+        None => warn!(
+            "slack interaction has no response_url, the user won't see: {}",
+            text
+        ),
+    }
+
+    StatusCode::OK.into_response()
+}
+
+// https://api.slack.com/interactivity/handling#message_responses
+fn get_response_url_from_slack_interaction_event(
+    slack_event: SlackInteractionEvent,
+) -> Option<SlackResponseUrl> {
+    match slack_event {
+        SlackInteractionEvent::BlockActions(block_action_event) => block_action_event.response_url,
+        SlackInteractionEvent::MessageAction(msg_action_event) => {
+            Some(msg_action_event.response_url)
+        }
+        _ => None,
+    }
+}
+
+async fn post_to_slack_response_url(
+    response_url: &SlackResponseUrl,
+    message: &serde_json::Value,
+) -> Result<()> {
+    let https = hyper_rustls::HttpsConnectorBuilder::new()
+        .with_native_roots()
+        .https_only()
+        .enable_http1()
+        .build();
+    let client = hyper::Client::builder().build::<_, Body>(https);
+
+    let request = Request::post(response_url.0.as_str())
+        .header(CONTENT_TYPE, "application/json")
+        .body(Body::from(serde_json::to_vec(message)?))?;
+
+    let response = client.request(request).await?;
+    if !response.status().is_success() {
+        return Err(anyhow!(
+            "slack response_url returned status {}",
+            response.status()
+        ));
+    }
+
+    Ok(())
+}
+
+// Slack Request Verification: ////////////////////////////////////////////////
+// https://api.slack.com/authentication/verifying-requests-from-slack
+
+pub const SLACK_SIGNATURE_HEADER: &str = "x-slack-signature";
+pub const SLACK_TIMESTAMP_HEADER: &str = "x-slack-request-timestamp";
+/// the only signature version Slack currently sends
+pub const SLACK_SIGNATURE_VERSION: &str = "v0";
+/// requests older than this are rejected to prevent replay attacks (Slack recommends 5 minutes)
+pub const SLACK_REQUEST_MAX_AGE_SECS: u64 = 60 * 5;
+/// larger bodies get a `413 Payload Too Large` before they're verified, so an unsigned request
+/// can't make us buffer an unbounded body. Interaction payloads, even modal submissions, are far smaller
+pub const SLACK_REQUEST_MAX_BODY_BYTES: usize = 1024 * 1024;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Tower layer that rejects any request not signed by one of our Slack apps with `401 Unauthorized`.
+///
+/// The raw body is buffered, up to `SLACK_REQUEST_MAX_BODY_BYTES`, to compute the signature,
+/// then handed to the inner service so extractors like `Form<SlackInteractionWrapper>` can still read it.
+#[derive(Clone)]
+pub struct SlackVerificationLayer {
+    slack_apps: Arc<HashMap<String, SlackAppConfig>>,
+}
+
+impl SlackVerificationLayer {
+    pub fn new(slack_apps: HashMap<String, SlackAppConfig>) -> Self {
+        Self {
+            slack_apps: Arc::new(slack_apps),
+        }
+    }
+}
+
+impl<S> Layer<S> for SlackVerificationLayer {
+    type Service = SlackVerification<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        SlackVerification {
+            inner,
+            slack_apps: self.slack_apps.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct SlackVerification<S> {
+    inner: S,
+    slack_apps: Arc<HashMap<String, SlackAppConfig>>,
+}
+
+impl<S> Service<Request<Body>> for SlackVerification<S>
+where
+    S: Service<Request<Body>, Response = Response> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut TaskContext<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<Body>) -> Self::Future {
+        // the inner service was driven to readiness, so take it and leave a clone in its place
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let slack_apps = self.slack_apps.clone();
+
+        Box::pin(async move {
+            let (parts, body) = request.into_parts();
+
+            let body = Limited::new(body, SLACK_REQUEST_MAX_BODY_BYTES);
+            let body_bytes = match hyper::body::to_bytes(body).await {
+                Ok(bytes) => bytes,
+                Err(err) if err.is::<LengthLimitError>() => {
+                    return Ok((
+                        StatusCode::PAYLOAD_TOO_LARGE,
+                        format!(
+                            "slack request body is over {} bytes",
+                            SLACK_REQUEST_MAX_BODY_BYTES
+                        ),
+                    )
+                        .into_response())
+                }
+                Err(err) => {
+                    return Ok((
+                        StatusCode::BAD_REQUEST,
+                        format!("unable to read request body: {}", err),
+                    )
+                        .into_response())
+                }
+            };
+
+            if let Err(err) =
+                verify_slack_request(&slack_apps, &parts.headers, &body_bytes, SystemTime::now())
+            {
+                warn!("rejected slack request: {:#}", err);
+                return Ok((StatusCode::UNAUTHORIZED, format!("{:#}", err)).into_response());
+            }
+
+            inner
+                .call(Request::from_parts(parts, Body::from(body_bytes)))
+                .await
+        })
+    }
+}
+
+/// Find which of our Slack apps sent the request, then verify it with that app's signing secret.
+pub fn verify_slack_request(
+    slack_apps: &HashMap<String, SlackAppConfig>,
+    headers: &HeaderMap,
+    body: &[u8],
+    now: SystemTime,
+) -> Result<()> {
+    let slack_app = find_slack_app_for_request(slack_apps, body)?;
+
+    verify_slack_signature(&slack_app.signing_secret, headers, body, now)
+}
+
+/// The ids Slack includes in every interaction payload, used to pick the signing secret
+#[derive(Deserialize, Debug)]
+struct SlackAppIdentifiers {
+    api_app_id: Option<String>,
+    team: Option<SlackTeamIdentifier>,
+}
+
+#[derive(Deserialize, Debug)]
+struct SlackTeamIdentifier {
+    id: String,
+}
+
+/// Look up the Slack app by the payload's `api_app_id`, falling back to its `team.id`.
+///
+/// The body hasn't been verified yet, so it is only used to choose which secret to verify with.
+fn find_slack_app_for_request<'a>(
+    slack_apps: &'a HashMap<String, SlackAppConfig>,
+    body: &[u8],
+) -> Result<&'a SlackAppConfig> {
+    let wrapper: SlackInteractionWrapper =
+        serde_urlencoded::from_bytes(body).context("slack request is not a `payload` form")?;
+    let identifiers: SlackAppIdentifiers =
+        serde_json::from_str(&wrapper.payload).context("slack `payload` is not a json object")?;
+
+    let app_id = identifiers.api_app_id;
+    let team_id = identifiers.team.map(|team| team.id);
+
+    app_id
+        .iter()
+        .chain(team_id.iter())
+        .find_map(|id| find_slack_app(slack_apps, id))
+        .ok_or_else(|| {
+            anyhow!(
+                "no slack app configured for api_app_id {:?} or team_id {:?}",
+                app_id,
+                team_id
+            )
+        })
+}
+
+/// Check the `X-Slack-Signature` header against our own signature of the raw request body,
+/// and that `X-Slack-Request-Timestamp` is within the replay window.
+pub fn verify_slack_signature(
+    signing_secret: &str,
+    headers: &HeaderMap,
+    body: &[u8],
+    now: SystemTime,
+) -> Result<()> {
+    let timestamp = get_header(headers, SLACK_TIMESTAMP_HEADER)?;
+    let timestamp_secs: u64 = timestamp
+        .parse()
+        .with_context(|| format!("{} is not a unix timestamp", SLACK_TIMESTAMP_HEADER))?;
+
+    let now_secs = now.duration_since(UNIX_EPOCH)?.as_secs();
+    if now_secs.abs_diff(timestamp_secs) > SLACK_REQUEST_MAX_AGE_SECS {
+        return Err(anyhow!(
+            "slack request timestamp is outside of the {} second replay window",
+            SLACK_REQUEST_MAX_AGE_SECS
+        ));
+    }
+
+    let signature_hex = get_header(headers, SLACK_SIGNATURE_HEADER)?
+        .strip_prefix(SLACK_SIGNATURE_VERSION)
+        .and_then(|sig| sig.strip_prefix('='))
+        .ok_or_else(|| anyhow!("unsupported slack signature version"))?;
+    let signature = hex::decode(signature_hex).context("slack signature is not valid hex")?;
+
+    build_slack_mac(signing_secret, timestamp, body)
+        .verify_slice(&signature)
+        .map_err(|_| anyhow!("slack signature does not match request"))
+}
+
+/// Sign a request body the same way Slack does: `v0=hex(hmac_sha256("v0:{timestamp}:{body}"))`
+#[cfg(test)]
+pub fn sign_slack_request(signing_secret: &str, timestamp: &str, body: &[u8]) -> String {
+    let signature = build_slack_mac(signing_secret, timestamp, body)
+        .finalize()
+        .into_bytes();
+
+    format!("{}={}", SLACK_SIGNATURE_VERSION, hex::encode(signature))
+}
+
+fn build_slack_mac(signing_secret: &str, timestamp: &str, body: &[u8]) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(signing_secret.as_bytes())
+        .expect("HMAC can take a key of any size");
+    mac.update(SLACK_SIGNATURE_VERSION.as_bytes());
+    mac.update(b":");
+    mac.update(timestamp.as_bytes());
+    mac.update(b":");
+    mac.update(body);
+    mac
+}
+
+fn get_header<'a>(headers: &'a HeaderMap, name: &str) -> Result<&'a str> {
+    headers
+        .get(name)
+        .ok_or_else(|| anyhow!("missing `{}` header", name))?
+        .to_str()
+        .with_context(|| format!("`{}` header is not valid ascii", name))
+}
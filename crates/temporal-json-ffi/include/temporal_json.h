@@ -0,0 +1,45 @@
+/* generated by `cargo xtask codegen` from crates/temporal-json-ffi/src/lib.rs, do not edit */
+
+#ifndef TEMPORAL_JSON_H
+#define TEMPORAL_JSON_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// Result of a `tj_` call. Unless it's `TJ_STATUS_OK`, `out` holds an error message instead of the result.
+typedef enum TjStatus {
+  TJ_STATUS_OK = 0,
+  // an argument is NULL, or the input isn't UTF-8
+  TJ_STATUS_INVALID_ARGUMENT = 1,
+  // the JSON isn't a TemporalInteraction, or doesn't fit in an encoded string
+  TJ_STATUS_ENCODE_FAILED = 2,
+  // the encoded string is invalid. The message starts with the `DecodeError` code, like `MISSING_KEY: `
+  TJ_STATUS_DECODE_FAILED = 3,
+  // a bug in temporal-json, please report it
+  TJ_STATUS_PANICKED = 4,
+} TjStatus;
+
+// Encode a TemporalInteraction provided as a JSON string, using the default encoding.
+//
+// # Safety
+// `json` must be a NUL terminated string, and `out` must point to a `char *` the result can be written to.
+enum TjStatus tj_encode_json(const char *json,
+                             char **out);
+
+// Decode an encoded string into a JSON string representing a TemporalInteraction.
+//
+// # Safety
+// `encoded` must be a NUL terminated string, and `out` must point to a `char *` the result can be written to.
+enum TjStatus tj_decode_json(const char *encoded,
+                             char **out);
+
+// Free a string written to `out` by a `tj_` function. Does nothing if `string` is NULL.
+//
+// # Safety
+// `string` must come from a `tj_` function, and can't be used after this.
+void tj_free_string(char *string);
+
+#endif  /* TEMPORAL_JSON_H */
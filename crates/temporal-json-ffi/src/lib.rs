@@ -0,0 +1,184 @@
+//! C ABI for the `temporal-json` crate, so Go, Java & other workers can encode & decode without calling the APIG.
+//! The header is `include/temporal_json.h`.
+//!
+//! Every string written to `out` is owned by the caller, and must be freed with `tj_free_string`.
+
+use anyhow::{anyhow, Result};
+use std::{
+    ffi::{CStr, CString},
+    os::raw::c_char,
+    panic::{catch_unwind, AssertUnwindSafe},
+    ptr,
+};
+use temporal_json::{DecodeError, Encoder};
+
+/// Result of a `tj_` call. Unless it's `TJ_STATUS_OK`, `out` holds an error message instead of the result.
+#[repr(C)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum TjStatus {
+    Ok = 0,
+    /// an argument is NULL, or the input isn't UTF-8
+    InvalidArgument = 1,
+    /// the JSON isn't a TemporalInteraction, or doesn't fit in an encoded string
+    EncodeFailed = 2,
+    /// the encoded string is invalid. The message starts with the `DecodeError` code, like `MISSING_KEY: `
+    DecodeFailed = 3,
+    /// a bug in temporal-json, please report it
+    Panicked = 4,
+}
+
+/// Encode a TemporalInteraction provided as a JSON string, using the default encoding.
+///
+/// # Safety
+/// `json` must be a NUL terminated string, and `out` must point to a `char *` the result can be written to.
+#[no_mangle]
+pub unsafe extern "C" fn tj_encode_json(json: *const c_char, out: *mut *mut c_char) -> TjStatus {
+    call_with_str(json, out, TjStatus::EncodeFailed, |json| {
+        Encoder::encode_default_from_json_string(json)
+    })
+}
+
+/// Decode an encoded string into a JSON string representing a TemporalInteraction.
+///
+/// # Safety
+/// `encoded` must be a NUL terminated string, and `out` must point to a `char *` the result can be written to.
+#[no_mangle]
+pub unsafe extern "C" fn tj_decode_json(encoded: *const c_char, out: *mut *mut c_char) -> TjStatus {
+    call_with_str(encoded, out, TjStatus::DecodeFailed, |encoded| {
+        Encoder::decode_to_json_string(encoded).map_err(|err| {
+            match err.downcast_ref::<DecodeError>() {
+                Some(decode_error) => anyhow!("{}: {}", decode_error.code(), decode_error),
+                None => err,
+            }
+        })
+    })
+}
+
+/// Free a string written to `out` by a `tj_` function. Does nothing if `string` is NULL.
+///
+/// # Safety
+/// `string` must come from a `tj_` function, and can't be used after this.
+#[no_mangle]
+pub unsafe extern "C" fn tj_free_string(string: *mut c_char) {
+    if !string.is_null() {
+        drop(CString::from_raw(string));
+    }
+}
+
+/// Runs `function` on the input as a `&str`, and writes its result or error message to `out`.
+/// Panics are caught, they can't unwind into C.
+unsafe fn call_with_str(
+    input: *const c_char,
+    out: *mut *mut c_char,
+    failed: TjStatus,
+    function: impl FnOnce(&str) -> Result<String>,
+) -> TjStatus {
+    if out.is_null() {
+        return TjStatus::InvalidArgument;
+    }
+    *out = ptr::null_mut();
+
+    if input.is_null() {
+        return write_out(out, TjStatus::InvalidArgument, "input is NULL".into());
+    }
+    let input = match CStr::from_ptr(input).to_str() {
+        Ok(input) => input,
+        Err(err) => {
+            return write_out(
+                out,
+                TjStatus::InvalidArgument,
+                format!("input is not UTF-8, {}", err),
+            )
+        }
+    };
+
+    match catch_unwind(AssertUnwindSafe(|| function(input))) {
+        Ok(Ok(result)) => match CString::new(result) {
+            Ok(result) => {
+                *out = result.into_raw();
+                TjStatus::Ok
+            }
+            Err(_) => write_out(out, failed, "result contains a NUL byte".into()),
+        },
+        Ok(Err(err)) => write_out(out, failed, err.to_string()),
+        Err(_) => write_out(out, TjStatus::Panicked, "temporal-json panicked".into()),
+    }
+}
+
+/// `message` as a C string in `out`, NUL bytes can't be represented so they're dropped
+unsafe fn write_out(out: *mut *mut c_char, status: TjStatus, message: String) -> TjStatus {
+    let message = CString::new(message.replace('\0', "")).expect("NUL bytes were removed");
+    *out = message.into_raw();
+    status
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// calls a `tj_` function like C would, and frees the result
+    fn call(
+        function: unsafe extern "C" fn(*const c_char, *mut *mut c_char) -> TjStatus,
+        input: &str,
+    ) -> (TjStatus, String) {
+        let input = CString::new(input).unwrap();
+        let mut out = ptr::null_mut();
+
+        unsafe {
+            let status = function(input.as_ptr(), &mut out);
+            let result = CStr::from_ptr(out).to_str().unwrap().to_string();
+            tj_free_string(out);
+            (status, result)
+        }
+    }
+
+    #[test]
+    fn test_encode_decode_round_trip() {
+        let signal = r#"{
+            "type": "Signal",
+            "namespace": "my-namespace",
+            "task_queue": "my-taskqueue",
+            "workflow_id": "some-workflow-id",
+            "run_id": "some-run-id",
+            "signal_name": "my_signal_name"
+        }"#;
+
+        let (status, encoded) = call(tj_encode_json, signal);
+        assert_eq!(status, TjStatus::Ok);
+        assert_eq!(
+            encoded,
+            "A~E:Signal,W:some-workflow-id,N:my-namespace,T:my-taskqueue,R:some-run-id,S:my_signal_name"
+        );
+
+        let (status, decoded) = call(tj_decode_json, &encoded);
+        assert_eq!(status, TjStatus::Ok);
+        assert_eq!(call(tj_encode_json, &decoded), (TjStatus::Ok, encoded));
+    }
+
+    #[test]
+    fn test_errors() {
+        let (status, message) = call(tj_encode_json, r#"{"type": "Ping"}"#);
+        assert_eq!(status, TjStatus::EncodeFailed);
+        assert!(message.contains("unknown variant `Ping`"), "{message}");
+
+        let (status, message) = call(tj_decode_json, "A~E:Signal,N:ns,T:tq");
+        assert_eq!(status, TjStatus::DecodeFailed);
+        assert!(message.starts_with("MISSING_KEY: "), "{message}");
+
+        let mut out = ptr::null_mut();
+        unsafe {
+            assert_eq!(
+                tj_decode_json(ptr::null(), &mut out),
+                TjStatus::InvalidArgument
+            );
+            tj_free_string(out);
+
+            let signal = CString::new("A~E:Signal").unwrap();
+            assert_eq!(
+                tj_decode_json(signal.as_ptr(), ptr::null_mut()),
+                TjStatus::InvalidArgument
+            );
+            tj_free_string(ptr::null_mut());
+        }
+    }
+}
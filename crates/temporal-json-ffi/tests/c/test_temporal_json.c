@@ -0,0 +1,61 @@
+/*
+ * Links against the temporal-json-ffi library like a Go or Java worker would.
+ * Build with -fsanitize=address so a leaked or double freed string fails the test, see README.md
+ */
+/* the asserts make the calls, keep them in release builds */
+#undef NDEBUG
+#include <assert.h>
+#include <stdio.h>
+#include <string.h>
+
+#include "temporal_json.h"
+
+static const char *SIGNAL_JSON =
+    "{\"type\": \"Signal\", \"namespace\": \"my-namespace\", \"task_queue\": \"my-taskqueue\","
+    " \"workflow_id\": \"some-workflow-id\", \"run_id\": \"some-run-id\", \"signal_name\": \"my_signal_name\"}";
+
+static void test_round_trip(void) {
+    char *encoded = NULL;
+    assert(tj_encode_json(SIGNAL_JSON, &encoded) == TJ_STATUS_OK);
+    assert(strcmp(encoded, "A~E:Signal,W:some-workflow-id,N:my-namespace,T:my-taskqueue,R:some-run-id,"
+                           "S:my_signal_name") == 0);
+
+    char *decoded = NULL;
+    assert(tj_decode_json(encoded, &decoded) == TJ_STATUS_OK);
+    assert(strstr(decoded, "\"signal_name\":\"my_signal_name\"") != NULL);
+
+    char *encoded_again = NULL;
+    assert(tj_encode_json(decoded, &encoded_again) == TJ_STATUS_OK);
+    assert(strcmp(encoded, encoded_again) == 0);
+
+    tj_free_string(encoded);
+    tj_free_string(decoded);
+    tj_free_string(encoded_again);
+}
+
+static void test_errors_are_owned_by_the_caller(void) {
+    char *message = NULL;
+    assert(tj_encode_json("{\"type\": \"Ping\"}", &message) == TJ_STATUS_ENCODE_FAILED);
+    assert(message != NULL);
+    tj_free_string(message);
+
+    message = NULL;
+    assert(tj_decode_json("A~E:Signal,N:my-namespace,T:my-taskqueue", &message) == TJ_STATUS_DECODE_FAILED);
+    assert(strncmp(message, "MISSING_KEY: ", strlen("MISSING_KEY: ")) == 0);
+    tj_free_string(message);
+
+    message = NULL;
+    assert(tj_decode_json(NULL, &message) == TJ_STATUS_INVALID_ARGUMENT);
+    tj_free_string(message);
+
+    assert(tj_decode_json("A~E:Signal", NULL) == TJ_STATUS_INVALID_ARGUMENT);
+    tj_free_string(NULL);
+}
+
+int main(void) {
+    test_round_trip();
+    test_errors_are_owned_by_the_caller();
+
+    printf("all passed\n");
+    return 0;
+}
@@ -1,6 +1,20 @@
 import test from 'ava'
 
-import { encodeDefaultFromJsonString, decodeToJsonString } from '../index.js'
+import {
+  Encoder,
+  Profile,
+  encodeCheckedFromJsonString,
+  encodeDefaultFromJsonString,
+  encodeSignalNoArgsChecked,
+  encodeSignalNoArgsWithVersion,
+  decodeToJsonString,
+  encode,
+  decode,
+  setSigningKeys,
+} from '../index.js'
+
+// signing keys can only be set once, before anything is encoded or decoded
+setSigningKeys('1:test-secret-that-is-not-used-anywhere-else')
 
 function build_temporal_interaction_exec_wf(){
   return {
@@ -28,6 +42,17 @@ function build_temporal_interaction_signal(){
   }
 }
 
+function build_temporal_interaction_query(){
+  return {
+    "type": "Query",
+    "namespace": "test-namespace",
+    "task_queue": "test-task-queue-rs",
+    "workflow_id": "some-super-long-uuid-string",
+    "run_id": "some-equally-long-uuid-string",
+    "query_type": "query_type_thats_defined_in_workflow",
+    "query_args": [{ "arg1": "value1" }, 2],
+  }
+}
 
 
 test('encodeDefaultFromJsonString', (t) => {
@@ -52,7 +77,7 @@ test('decodeFromJsonString', (t) => {
 
 
 test('test all event types', (t) => {
-  for (const temporal_event_json of [build_temporal_interaction_exec_wf(), build_temporal_interaction_signal()]) {
+  for (const temporal_event_json of [build_temporal_interaction_exec_wf(), build_temporal_interaction_signal(), build_temporal_interaction_query()]) {
   
     const as_string = JSON.stringify(temporal_event_json);
   
@@ -64,4 +89,211 @@ test('test all event types', (t) => {
   }
 })
 
+test('query round trips with every arg', (t) => {
+  const query = build_temporal_interaction_query();
+
+  const encoded_string = encodeDefaultFromJsonString(JSON.stringify(query));
+  const decoded = JSON.parse(decodeToJsonString(encoded_string));
+
+  t.deepEqual(decoded, { ...query, expires_at: null });
+})
+
+test('signed encoder version', (t) => {
+  const signal = {
+    namespace: 'test-namespace',
+    taskQueue: 'test-task-queue-rs',
+    workflowId: 'some-super-long-uuid-string',
+    runId: 'some-equally-long-uuid-string',
+    signalName: 'signal_name_thats_defined_in_workflow',
+  }
+
+  const encoded_string = encodeSignalNoArgsWithVersion(Encoder.S, signal)
+  t.true(encoded_string.startsWith('S~'))
+
+  const decoded = JSON.parse(decodeToJsonString(encoded_string))
+  t.is(decoded.workflow_id, signal.workflowId)
+
+  t.throws(() => decodeToJsonString(encoded_string.replace('W:some', 'W:another')))
+})
+
+test('expired encoded string', (t) => {
+  const signal = {
+    namespace: 'test-namespace',
+    taskQueue: 'test-task-queue-rs',
+    workflowId: 'some-super-long-uuid-string',
+    signalName: 'signal_name_thats_defined_in_workflow',
+  }
+  const now = Math.floor(Date.now() / 1000)
+
+  const unexpired = encodeSignalNoArgsWithVersion(Encoder.S, { ...signal, expiresAt: now + 60 * 60 })
+  t.is(JSON.parse(decodeToJsonString(unexpired)).expires_at, now + 60 * 60)
+
+  const expired = encodeSignalNoArgsWithVersion(Encoder.S, { ...signal, expiresAt: now - 1 })
+  t.throws(() => decodeToJsonString(expired), { message: /expired/, code: 'EXPIRED' })
+})
+
+test('encode checked against platform limits', (t) => {
+  const as_string = JSON.stringify(build_temporal_interaction_signal())
+
+  t.is(encodeCheckedFromJsonString(as_string, Profile.SlackActionId), encodeDefaultFromJsonString(as_string))
+  t.throws(() => encodeCheckedFromJsonString(as_string, Profile.DiscordCustomId, Encoder.B), {
+    message: /over the discord_custom_id limit of 100/,
+  })
+
+  const signal = {
+    namespace: 'test-namespace',
+    taskQueue: 'test-task-queue-rs',
+    workflowId: 'some-super-long-uuid-string',
+    signalName: 'signal_name_thats_defined_in_workflow',
+  }
+  t.throws(() => encodeSignalNoArgsChecked(Encoder.A, signal, Profile.TelegramCallbackData), {
+    message: /largest field is `S` \(signal_name\)/,
+  })
+})
+
+test('decode errors have a code', (t) => {
+  t.throws(() => decodeToJsonString('A~E:Signal,N:test-namespace,T:test-task-queue-rs'), { code: 'MISSING_KEY' })
+  t.throws(() => decodeToJsonString('Z9~E:Signal'), { code: 'UNKNOWN_VERSION' })
+  t.throws(() => decodeToJsonString('not encoded'), { code: 'MALFORMED_VERSION' })
+})
+
+test('encode & decode typed objects', (t) => {
+  const interactions = [
+    {
+      type: 'Execute',
+      namespace: 'test-namespace',
+      taskQueue: 'template-taskqueue',
+      workflowId: '1',
+      workflowType: 'GreetingWorkflow',
+      args: [{ name: 'saxon', team: 'noxasaxon' }],
+    },
+    {
+      type: 'Signal',
+      namespace: 'test-namespace',
+      taskQueue: 'test-task-queue-rs',
+      workflowId: 'some-super-long-uuid-string',
+      runId: 'some-equally-long-uuid-string',
+      signalName: 'signal_name_thats_defined_in_workflow',
+    },
+    {
+      type: 'Query',
+      namespace: 'test-namespace',
+      taskQueue: 'test-task-queue-rs',
+      workflowId: 'some-super-long-uuid-string',
+      queryType: 'query_type_thats_defined_in_workflow',
+      queryArgs: [{ arg1: 'value1' }, 2],
+    },
+    {
+      type: 'SignalWithStart',
+      namespace: 'test-namespace',
+      taskQueue: 'test-task-queue-rs',
+      workflowId: 'user-U024BE7LH',
+      workflowType: 'GreetingWorkflow',
+      args: [{ user: 'U024BE7LH' }],
+      signalName: 'signal_name_thats_defined_in_workflow',
+    },
+    {
+      type: 'Cancel',
+      namespace: 'test-namespace',
+      taskQueue: 'test-task-queue-rs',
+      workflowId: 'some-super-long-uuid-string',
+      reason: 'aborted from slack',
+    },
+    {
+      type: 'Terminate',
+      namespace: 'test-namespace',
+      taskQueue: 'test-task-queue-rs',
+      workflowId: 'some-super-long-uuid-string',
+      reason: 'stuck, see INC-1234',
+      details: [{ approvedBy: 'U024BE7LH' }],
+    },
+    {
+      type: 'Update',
+      namespace: 'test-namespace',
+      taskQueue: 'test-task-queue-rs',
+      workflowId: 'some-super-long-uuid-string',
+      updateName: 'update_name_thats_defined_in_workflow',
+      args: [{ form: 'access-request' }],
+      waitStage: 'Accepted',
+    },
+    {
+      type: 'CompleteActivity',
+      namespace: 'test-namespace',
+      taskQueue: 'test-task-queue-rs',
+      workflowId: 'some-super-long-uuid-string',
+      activityId: '7',
+      outcome: 'Failed',
+      reason: 'rejected in slack',
+    },
+  ]
+
+  for (const interaction of interactions) {
+    for (const encoder_version of [Encoder.A, Encoder.S, Encoder.B]) {
+      const { interaction: decoded, userData } = decode(encode(interaction, encoder_version))
+      t.like(decoded, interaction)
+      t.is(userData, undefined)
+    }
+  }
 
+  // the typed object encodes the same as its snake_case JSON
+  t.is(encode(interactions[1]), encodeDefaultFromJsonString(JSON.stringify(build_temporal_interaction_signal())))
+})
+
+test('decode returns the user data section', (t) => {
+  const signal = {
+    type: 'Signal',
+    namespace: 'test-namespace',
+    taskQueue: 'test-task-queue-rs',
+    workflowId: 'some-super-long-uuid-string',
+    signalName: 'signal_name_thats_defined_in_workflow',
+  }
+
+  const encoded_string = encode(signal, Encoder.S, 'button:approve')
+  t.true(encoded_string.endsWith('~button:approve'))
+
+  const decoded = decode(encoded_string)
+  t.is(decoded.userData, 'button:approve')
+  t.is(decoded.interaction.type, 'Signal')
+  t.is(decoded.interaction.signalName, signal.signalName)
+})
+
+test('encode rejects an unknown type', (t) => {
+  t.throws(() => encode({ type: 'Abort', namespace: 'test-namespace' }), { message: /unknown interaction type `Abort`/ })
+  t.throws(() => encode({ namespace: 'test-namespace' }), { message: /needs a string `type`/ })
+})
+
+test('encode rejects an unknown update wait stage', (t) => {
+  const update = {
+    type: 'Update',
+    namespace: 'test-namespace',
+    taskQueue: 'test-task-queue-rs',
+    workflowId: 'some-super-long-uuid-string',
+    updateName: 'update_name_thats_defined_in_workflow',
+    waitStage: 'Admitted',
+  }
+  t.throws(() => encode(update), { message: /unknown waitStage `Admitted`/ })
+})
+
+test('execute start options round trip', (t) => {
+  const execute = {
+    type: 'Execute',
+    namespace: 'test-namespace',
+    taskQueue: 'template-taskqueue',
+    workflowId: '1',
+    workflowType: 'GreetingWorkflow',
+    options: { runTimeoutSecs: 3600, idReusePolicy: 'RejectDuplicate' },
+  }
+
+  const { interaction: decoded } = decode(encode(execute))
+  t.like(decoded, execute)
+  t.is(JSON.parse(decodeToJsonString(encode(execute))).options.run_timeout_secs, 3600)
+
+  t.throws(() => encode({ ...execute, options: { idReusePolicy: 'AllowAll' } }), {
+    message: /unknown idReusePolicy `AllowAll`/,
+  })
+})
+
+test('decode errors have the same code as decodeToJsonString', (t) => {
+  t.throws(() => decode('A~E:Signal,N:test-namespace,T:test-task-queue-rs'), { code: 'MISSING_KEY' })
+  t.throws(() => decode('not encoded'), { code: 'MALFORMED_VERSION' })
+})
@@ -4,7 +4,33 @@
 /* auto-generated by NAPI-RS */
 
 export const enum Encoder {
-  A = 0
+  /** plain text key:value pairs */
+  A = 0,
+  /** key:value pairs of `A`, plus a key id & HMAC of the temporal section so it can't be tampered with */
+  S = 1,
+  /** key id & base64url ChaCha20-Poly1305 ciphertext of the `A` key:value pairs, so they can't be read or tampered with */
+  X = 2,
+  /** base64url of the `A` key:value pairs packed as length-prefixed binary, with UUIDs as 16 raw bytes */
+  B = 3
+}
+/**
+ * A platform field an encoded string is sent in.
+ *
+ * There's no Teams profile on purpose: Teams card actions carry their data as a JSON object
+ * in the card, with no limit of its own, only the size limit of the whole message.
+ * Any encoded string fits well within that, so there's nothing to check it against
+ */
+export const enum Profile {
+  /** Slack `callback_id` of modals, shortcuts & attachments, 255 chars */
+  SlackCallbackId = 0,
+  /** Slack `action_id` of a block element, 255 chars */
+  SlackActionId = 1,
+  /** Slack `block_id` of a block, 255 chars */
+  SlackBlockId = 2,
+  /** Discord `custom_id` of a message component or modal, 100 chars */
+  DiscordCustomId = 3,
+  /** Telegram `callback_data` of an inline keyboard button, 64 bytes */
+  TelegramCallbackData = 4
 }
 /**
  * A Signal struct without the Input Payload.
@@ -18,6 +44,173 @@ export interface TemporalSignalWithoutInput {
   workflowId?: string
   runId?: string
   signalName: string
+  /** unix timestamp (seconds) after which the encoded string no longer decodes */
+  expiresAt?: number
+}
+/** Start a workflow, the `Execute` member of the interaction union taken by `encode` & returned by `decode`. */
+export interface ExecuteInteraction {
+  type: 'Execute'
+  namespace: string
+  taskQueue: string
+  workflowId: string
+  workflowType: string
+  args?: Array<any>
+  /** timeouts, retries & the rest of how the workflow is started */
+  options?: WorkflowStartOptions
+  /** unix timestamp (seconds) after which the encoded string no longer decodes */
+  expiresAt?: number
+}
+/** How a workflow is started, Temporal's defaults are used for anything unset. */
+export interface WorkflowStartOptions {
+  /** seconds the workflow can take, including retries & continue-as-new */
+  executionTimeoutSecs?: number
+  /** seconds a single run of the workflow can take */
+  runTimeoutSecs?: number
+  /** seconds a single workflow task can take */
+  taskTimeoutSecs?: number
+  /** whether a workflow can start with the id of one that already ran */
+  idReusePolicy?: 'AllowDuplicate' | 'AllowDuplicateFailedOnly' | 'RejectDuplicate' | 'TerminateIfRunning'
+  /** failed workflows aren't retried unless this is set */
+  retryPolicy?: WorkflowRetryPolicy
+  /** start the workflow on a cron schedule, e.g. `0 9 * * MON` */
+  cronSchedule?: string
+  /** shown with the workflow, but can't be searched by */
+  memo?: Record<string, any>
+  /** values the workflow can be listed by, each must be registered with Temporal */
+  searchAttributes?: Record<string, any>
+}
+/** How a failed workflow is retried. */
+export interface WorkflowRetryPolicy {
+  /** seconds before the first retry */
+  initialIntervalSecs?: number
+  /** how much longer each interval is than the last */
+  backoffCoefficient?: number
+  /** the longest interval in seconds, however many retries */
+  maximumIntervalSecs?: number
+  /** attempts including the first, unlimited when unset or 0 */
+  maximumAttempts?: number
+  /** error types that fail the workflow without a retry */
+  nonRetryableErrorTypes?: Array<string>
+}
+/** Signal a running workflow, the `Signal` member of the interaction union taken by `encode` & returned by `decode`. */
+export interface SignalInteraction {
+  type: 'Signal'
+  namespace: string
+  taskQueue: string
+  workflowId?: string
+  runId?: string
+  signalName: string
+  input?: Array<any>
+  identity?: string
+  requestId?: string
+  control?: string
+  /** unix timestamp (seconds) after which the encoded string no longer decodes */
+  expiresAt?: number
+}
+/** Query a running workflow, the `Query` member of the interaction union taken by `encode` & returned by `decode`. */
+export interface QueryInteraction {
+  type: 'Query'
+  namespace: string
+  taskQueue: string
+  workflowId?: string
+  runId?: string
+  queryType: string
+  queryArgs?: Array<any>
+  /** unix timestamp (seconds) after which the encoded string no longer decodes */
+  expiresAt?: number
+}
+/** Signal a workflow, starting it first if it isn't running, the `SignalWithStart` member of the interaction union taken by `encode` & returned by `decode`. */
+export interface SignalWithStartInteraction {
+  type: 'SignalWithStart'
+  namespace: string
+  taskQueue: string
+  workflowId: string
+  workflowType: string
+  /** the args the workflow is started with, if it isn't running */
+  args?: Array<any>
+  signalName: string
+  input?: Array<any>
+  identity?: string
+  requestId?: string
+  control?: string
+  /** unix timestamp (seconds) after which the encoded string no longer decodes */
+  expiresAt?: number
+}
+/** Request that a running workflow cancels itself, the `Cancel` member of the interaction union taken by `encode` & returned by `decode`. */
+export interface CancelInteraction {
+  type: 'Cancel'
+  namespace: string
+  taskQueue: string
+  workflowId: string
+  runId?: string
+  /** why the workflow was cancelled, recorded in its history */
+  reason?: string
+  identity?: string
+  requestId?: string
+  /** unix timestamp (seconds) after which the encoded string no longer decodes */
+  expiresAt?: number
+}
+/** Stop a running workflow immediately, the `Terminate` member of the interaction union taken by `encode` & returned by `decode`. */
+export interface TerminateInteraction {
+  type: 'Terminate'
+  namespace: string
+  taskQueue: string
+  workflowId: string
+  runId?: string
+  /** why the workflow was terminated, recorded in its history */
+  reason?: string
+  /** recorded in the terminated workflow's history along with the reason */
+  details?: Array<any>
+  identity?: string
+  /** unix timestamp (seconds) after which the encoded string no longer decodes */
+  expiresAt?: number
+}
+/** Send an update to a running workflow and wait for its result, the `Update` member of the interaction union taken by `encode` & returned by `decode`. */
+export interface UpdateInteraction {
+  type: 'Update'
+  namespace: string
+  taskQueue: string
+  workflowId: string
+  runId?: string
+  /** the update handler's name, as defined in the workflow */
+  updateName: string
+  args?: Array<any>
+  /** how far the update gets before Temporal responds, `Completed` when unset */
+  waitStage?: 'Accepted' | 'Completed'
+  identity?: string
+  /** dedupes retried updates, a new UUID when unset */
+  updateId?: string
+  /** unix timestamp (seconds) after which the encoded string no longer decodes */
+  expiresAt?: number
+}
+/**
+ * Finish an activity that is completed asynchronously, the `CompleteActivity` member of the interaction union taken by `encode` & returned by `decode`.
+ *
+ * The activity is found by its `taskToken`, or by `workflowId` & `activityId` when the token is too long to encode.
+ */
+export interface CompleteActivityInteraction {
+  type: 'CompleteActivity'
+  namespace: string
+  taskQueue: string
+  /** the activity's task token as base64url, without padding */
+  taskToken?: string
+  workflowId?: string
+  runId?: string
+  activityId?: string
+  /** how the activity finishes, `Completed` when unset */
+  outcome?: 'Completed' | 'Failed' | 'Canceled'
+  /** the activity's result, or the details of its failure or cancellation */
+  result?: Array<any>
+  /** the failure message, when the activity is failed */
+  reason?: string
+  identity?: string
+  /** unix timestamp (seconds) after which the encoded string no longer decodes */
+  expiresAt?: number
+}
+/** The result of `decode`: the interaction, and the user data section after it if there is one. */
+export interface DecodedInteraction {
+  interaction: ExecuteInteraction | SignalInteraction | QueryInteraction | SignalWithStartInteraction | CancelInteraction | TerminateInteraction | UpdateInteraction | CompleteActivityInteraction
+  userData?: string
 }
 /**
  * Convert workflow metadata into an encoded string for embedding into a webhook event, using the default encoding.
@@ -33,11 +226,43 @@ export function encodeSignalNoArgsDefault(signal: TemporalSignalWithoutInput): s
  * the Temporal API Gateway receives the event it can decode the string and route the event to your workflow.
  */
 export function encodeSignalNoArgsWithVersion(encoderVersion: Encoder, signal: TemporalSignalWithoutInput): string
+/**
+ * Convert workflow metadata into an encoded string, and fail if it's too long for the profile's field.
+ *
+ * Example: use `Profile.DiscordCustomId` to find out before Discord rejects a 101 char `custom_id`.
+ */
+export function encodeSignalNoArgsChecked(encoderVersion: Encoder, signal: TemporalSignalWithoutInput, profile: Profile): string
 /**
  * Encode a TemporalInteraction struct provided as a JSON string.
  *
- * panics if JSON structure is not correct.
+ * Fails if the JSON doesn't match the `TemporalInteraction` type, see `encode` for a typed alternative.
  */
 export function encodeDefaultFromJsonString(jsonString: string): string
-/** Decode an encoded string into a JSON string representing a TemporalInteraction struct. */
+/** Encode a TemporalInteraction struct provided as a JSON string, and fail if it's too long for the profile's field. */
+export function encodeCheckedFromJsonString(jsonString: string, profile: Profile, encoderVersion?: Encoder | undefined | null): string
+/**
+ * Decode an encoded string into a JSON string representing a TemporalInteraction struct.
+ *
+ * Errors have a `code` like `MISSING_KEY` or `EXPIRED` when the encoded string is invalid.
+ */
 export function decodeToJsonString(encodedString: string): string
+/**
+ * Encode an interaction object, picked by its `type`, with the default encoding unless a version is given.
+ *
+ * `userData` is added after the temporal info, and is returned by `decode`.
+ */
+export function encode(interaction: ExecuteInteraction | SignalInteraction | QueryInteraction | SignalWithStartInteraction | CancelInteraction | TerminateInteraction | UpdateInteraction | CompleteActivityInteraction, encoderVersion?: Encoder | undefined | null, userData?: string | undefined | null): string
+/**
+ * Decode an encoded string into an interaction object, with the user data section if there is one.
+ *
+ * Errors have a `code` like `MISSING_KEY` or `EXPIRED` when the encoded string is invalid.
+ */
+export function decode(encodedString: string): DecodedInteraction
+/**
+ * Set the keys used to sign encoded strings with `Encoder.S`, as a comma separated list of `key_id:secret` pairs.
+ * The first key is used for signing. Must be called before the first encode/decode.
+ *
+ * If not called, keys are read from the `TEMPORAL_JSON_SIGNING_KEYS` env var.
+ */
+export function setSigningKeys(keyList: string, requireSignature?: boolean | undefined | null): void
+export * from './temporal_interaction'
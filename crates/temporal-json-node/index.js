@@ -246,10 +246,16 @@ if (!nativeBinding) {
   throw new Error(`Failed to load native binding`)
 }
 
-const { Encoder, encodeSignalNoArgsDefault, encodeSignalNoArgsWithVersion, encodeDefaultFromJsonString, decodeToJsonString } = nativeBinding
+const { Encoder, Profile, encodeSignalNoArgsDefault, encodeSignalNoArgsWithVersion, encodeSignalNoArgsChecked, encodeDefaultFromJsonString, encodeCheckedFromJsonString, decodeToJsonString, encode, decode, setSigningKeys } = nativeBinding
 
 module.exports.Encoder = Encoder
+module.exports.Profile = Profile
 module.exports.encodeSignalNoArgsDefault = encodeSignalNoArgsDefault
 module.exports.encodeSignalNoArgsWithVersion = encodeSignalNoArgsWithVersion
+module.exports.encodeSignalNoArgsChecked = encodeSignalNoArgsChecked
 module.exports.encodeDefaultFromJsonString = encodeDefaultFromJsonString
+module.exports.encodeCheckedFromJsonString = encodeCheckedFromJsonString
 module.exports.decodeToJsonString = decodeToJsonString
+module.exports.encode = encode
+module.exports.decode = decode
+module.exports.setSigningKeys = setSigningKeys
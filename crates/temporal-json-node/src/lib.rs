@@ -7,9 +7,19 @@
 extern crate napi_derive;
 
 pub mod encoder {
-  use napi::Status;
-  use temporal_json::TemporalInteraction;
-  pub use temporal_json::{Encoder, SignalTemporal};
+  use napi::bindgen_prelude::{Either8, FromNapiValue};
+  use napi::{Env, JsObject, NapiRaw, Status};
+  use std::collections::HashMap;
+  pub use temporal_json::{
+    ActivityOutcome, CancelTemporal, CompleteActivityTemporal, Encoder, ExecuteTemporalWorkflow,
+    Profile, QueryTemporal, SignalTemporal, SignalWithStartTemporal, SigningKeys,
+    TerminateTemporal, UpdateTemporal, UpdateWaitStage, WorkflowIdReusePolicy,
+  };
+  use temporal_json::{DecodeError, TemporalInteraction};
+
+  /// every `type` of the interaction objects, for errors
+  const INTERACTION_TYPES: &str =
+    "Execute, Signal, Query, SignalWithStart, Cancel, Terminate, Update or CompleteActivity";
 
   #[napi(object)]
   /// A Signal struct without the Input Payload.
@@ -22,6 +32,8 @@ pub mod encoder {
     pub workflow_id: Option<String>,
     pub run_id: Option<String>,
     pub signal_name: String,
+    /// unix timestamp (seconds) after which the encoded string no longer decodes
+    pub expires_at: Option<i64>,
   }
 
   impl Into<SignalTemporal> for TemporalSignalWithoutInput {
@@ -32,18 +44,698 @@ pub mod encoder {
         workflow_id: self.workflow_id,
         run_id: self.run_id,
         signal_name: self.signal_name,
+        expires_at: expires_at_from_js(self.expires_at),
         ..Default::default()
       }
     }
   }
 
+  /// napi has no u64, a deadline before the epoch has already passed
+  fn expires_at_from_js(expires_at: Option<i64>) -> Option<u64> {
+    expires_at.map(|expires_at| expires_at.max(0) as u64)
+  }
+
+  fn expires_at_to_js(expires_at: Option<u64>) -> Option<i64> {
+    expires_at.map(|expires_at| i64::try_from(expires_at).unwrap_or(i64::MAX))
+  }
+
+  #[napi(object)]
+  /// Start a workflow, the `Execute` member of the interaction union taken by `encode` & returned by `decode`.
+  pub struct ExecuteInteraction {
+    #[napi(js_name = "type", ts_type = "'Execute'")]
+    pub kind: String,
+    pub namespace: String,
+    pub task_queue: String,
+    pub workflow_id: String,
+    pub workflow_type: String,
+    pub args: Option<Vec<serde_json::Value>>,
+    /// timeouts, retries & the rest of how the workflow is started
+    pub options: Option<WorkflowStartOptions>,
+    /// unix timestamp (seconds) after which the encoded string no longer decodes
+    pub expires_at: Option<i64>,
+  }
+
+  impl TryFrom<ExecuteInteraction> for ExecuteTemporalWorkflow {
+    type Error = napi::Error;
+
+    fn try_from(execute: ExecuteInteraction) -> napi::Result<Self> {
+      Ok(ExecuteTemporalWorkflow {
+        namespace: execute.namespace,
+        task_queue: execute.task_queue,
+        workflow_id: execute.workflow_id,
+        workflow_type: execute.workflow_type,
+        args: execute.args,
+        options: execute.options.map(TryInto::try_into).transpose()?,
+        expires_at: expires_at_from_js(execute.expires_at),
+      })
+    }
+  }
+
+  impl From<ExecuteTemporalWorkflow> for ExecuteInteraction {
+    fn from(execute: ExecuteTemporalWorkflow) -> Self {
+      ExecuteInteraction {
+        kind: "Execute".to_string(),
+        namespace: execute.namespace,
+        task_queue: execute.task_queue,
+        workflow_id: execute.workflow_id,
+        workflow_type: execute.workflow_type,
+        args: execute.args,
+        options: execute.options.map(Into::into),
+        expires_at: expires_at_to_js(execute.expires_at),
+      }
+    }
+  }
+
+  #[napi(object)]
+  /// How a workflow is started, Temporal's defaults are used for anything unset.
+  pub struct WorkflowStartOptions {
+    /// seconds the workflow can take, including retries & continue-as-new
+    pub execution_timeout_secs: Option<u32>,
+    /// seconds a single run of the workflow can take
+    pub run_timeout_secs: Option<u32>,
+    /// seconds a single workflow task can take
+    pub task_timeout_secs: Option<u32>,
+    /// whether a workflow can start with the id of one that already ran
+    #[napi(
+      ts_type = "'AllowDuplicate' | 'AllowDuplicateFailedOnly' | 'RejectDuplicate' | 'TerminateIfRunning'"
+    )]
+    pub id_reuse_policy: Option<String>,
+    /// failed workflows aren't retried unless this is set
+    pub retry_policy: Option<WorkflowRetryPolicy>,
+    /// start the workflow on a cron schedule, e.g. `0 9 * * MON`
+    pub cron_schedule: Option<String>,
+    /// shown with the workflow, but can't be searched by
+    pub memo: Option<HashMap<String, serde_json::Value>>,
+    /// values the workflow can be listed by, each must be registered with Temporal
+    pub search_attributes: Option<HashMap<String, serde_json::Value>>,
+  }
+
+  #[napi(object)]
+  /// How a failed workflow is retried.
+  pub struct WorkflowRetryPolicy {
+    /// seconds before the first retry
+    pub initial_interval_secs: Option<u32>,
+    /// how much longer each interval is than the last
+    pub backoff_coefficient: Option<f64>,
+    /// the longest interval in seconds, however many retries
+    pub maximum_interval_secs: Option<u32>,
+    /// attempts including the first, unlimited when unset or 0
+    pub maximum_attempts: Option<u32>,
+    /// error types that fail the workflow without a retry
+    pub non_retryable_error_types: Option<Vec<String>>,
+  }
+
+  impl TryFrom<WorkflowStartOptions> for temporal_json::WorkflowStartOptions {
+    type Error = napi::Error;
+
+    fn try_from(options: WorkflowStartOptions) -> napi::Result<Self> {
+      let id_reuse_policy = options
+        .id_reuse_policy
+        .map(|policy| {
+          policy.parse::<WorkflowIdReusePolicy>().map_err(|_| {
+            napi::Error::new(
+              Status::InvalidArg,
+              format!(
+                "unknown idReusePolicy `{}`, expected AllowDuplicate, AllowDuplicateFailedOnly, RejectDuplicate or TerminateIfRunning",
+                policy
+              ),
+            )
+          })
+        })
+        .transpose()?;
+
+      let retry_policy = options
+        .retry_policy
+        .map(|retry_policy| {
+          let backoff_coefficient = retry_policy
+            .backoff_coefficient
+            .map(|coefficient| {
+              serde_json::Number::from_f64(coefficient).ok_or_else(|| {
+                napi::Error::new(
+                  Status::InvalidArg,
+                  format!(
+                    "backoffCoefficient `{}` is not a finite number",
+                    coefficient
+                  ),
+                )
+              })
+            })
+            .transpose()?;
+
+          Ok::<_, napi::Error>(temporal_json::WorkflowRetryPolicy {
+            initial_interval_secs: retry_policy.initial_interval_secs.map(u64::from),
+            backoff_coefficient,
+            maximum_interval_secs: retry_policy.maximum_interval_secs.map(u64::from),
+            maximum_attempts: retry_policy.maximum_attempts,
+            non_retryable_error_types: retry_policy.non_retryable_error_types,
+          })
+        })
+        .transpose()?;
+
+      Ok(temporal_json::WorkflowStartOptions {
+        execution_timeout_secs: options.execution_timeout_secs.map(u64::from),
+        run_timeout_secs: options.run_timeout_secs.map(u64::from),
+        task_timeout_secs: options.task_timeout_secs.map(u64::from),
+        id_reuse_policy,
+        retry_policy,
+        cron_schedule: options.cron_schedule,
+        memo: options.memo.map(|memo| memo.into_iter().collect()),
+        search_attributes: options
+          .search_attributes
+          .map(|search_attributes| search_attributes.into_iter().collect()),
+      })
+    }
+  }
+
+  /// napi has no u64, longer timeouts than a u32 of seconds are clamped
+  fn secs_to_js(secs: Option<u64>) -> Option<u32> {
+    secs.map(|secs| u32::try_from(secs).unwrap_or(u32::MAX))
+  }
+
+  impl From<temporal_json::WorkflowStartOptions> for WorkflowStartOptions {
+    fn from(options: temporal_json::WorkflowStartOptions) -> Self {
+      WorkflowStartOptions {
+        execution_timeout_secs: secs_to_js(options.execution_timeout_secs),
+        run_timeout_secs: secs_to_js(options.run_timeout_secs),
+        task_timeout_secs: secs_to_js(options.task_timeout_secs),
+        id_reuse_policy: options.id_reuse_policy.map(|policy| policy.to_string()),
+        retry_policy: options
+          .retry_policy
+          .map(|retry_policy| WorkflowRetryPolicy {
+            initial_interval_secs: secs_to_js(retry_policy.initial_interval_secs),
+            backoff_coefficient: retry_policy
+              .backoff_coefficient
+              .and_then(|coefficient| coefficient.as_f64()),
+            maximum_interval_secs: secs_to_js(retry_policy.maximum_interval_secs),
+            maximum_attempts: retry_policy.maximum_attempts,
+            non_retryable_error_types: retry_policy.non_retryable_error_types,
+          }),
+        cron_schedule: options.cron_schedule,
+        memo: options.memo.map(|memo| memo.into_iter().collect()),
+        search_attributes: options
+          .search_attributes
+          .map(|search_attributes| search_attributes.into_iter().collect()),
+      }
+    }
+  }
+
+  #[napi(object)]
+  /// Signal a running workflow, the `Signal` member of the interaction union taken by `encode` & returned by `decode`.
+  pub struct SignalInteraction {
+    #[napi(js_name = "type", ts_type = "'Signal'")]
+    pub kind: String,
+    pub namespace: String,
+    pub task_queue: String,
+    pub workflow_id: Option<String>,
+    pub run_id: Option<String>,
+    pub signal_name: String,
+    pub input: Option<Vec<serde_json::Value>>,
+    pub identity: Option<String>,
+    pub request_id: Option<String>,
+    pub control: Option<String>,
+    /// unix timestamp (seconds) after which the encoded string no longer decodes
+    pub expires_at: Option<i64>,
+  }
+
+  impl From<SignalInteraction> for SignalTemporal {
+    fn from(signal: SignalInteraction) -> Self {
+      SignalTemporal {
+        namespace: signal.namespace,
+        task_queue: signal.task_queue,
+        workflow_id: signal.workflow_id,
+        run_id: signal.run_id,
+        signal_name: signal.signal_name,
+        input: signal.input,
+        identity: signal.identity,
+        request_id: signal.request_id,
+        control: signal.control,
+        expires_at: expires_at_from_js(signal.expires_at),
+      }
+    }
+  }
+
+  impl From<SignalTemporal> for SignalInteraction {
+    fn from(signal: SignalTemporal) -> Self {
+      SignalInteraction {
+        kind: "Signal".to_string(),
+        namespace: signal.namespace,
+        task_queue: signal.task_queue,
+        workflow_id: signal.workflow_id,
+        run_id: signal.run_id,
+        signal_name: signal.signal_name,
+        input: signal.input,
+        identity: signal.identity,
+        request_id: signal.request_id,
+        control: signal.control,
+        expires_at: expires_at_to_js(signal.expires_at),
+      }
+    }
+  }
+
+  #[napi(object)]
+  /// Query a running workflow, the `Query` member of the interaction union taken by `encode` & returned by `decode`.
+  pub struct QueryInteraction {
+    #[napi(js_name = "type", ts_type = "'Query'")]
+    pub kind: String,
+    pub namespace: String,
+    pub task_queue: String,
+    pub workflow_id: Option<String>,
+    pub run_id: Option<String>,
+    pub query_type: String,
+    pub query_args: Option<Vec<serde_json::Value>>,
+    /// unix timestamp (seconds) after which the encoded string no longer decodes
+    pub expires_at: Option<i64>,
+  }
+
+  impl From<QueryInteraction> for QueryTemporal {
+    fn from(query: QueryInteraction) -> Self {
+      QueryTemporal {
+        namespace: query.namespace,
+        task_queue: query.task_queue,
+        workflow_id: query.workflow_id,
+        run_id: query.run_id,
+        query_type: query.query_type,
+        query_args: query.query_args,
+        expires_at: expires_at_from_js(query.expires_at),
+      }
+    }
+  }
+
+  impl From<QueryTemporal> for QueryInteraction {
+    fn from(query: QueryTemporal) -> Self {
+      QueryInteraction {
+        kind: "Query".to_string(),
+        namespace: query.namespace,
+        task_queue: query.task_queue,
+        workflow_id: query.workflow_id,
+        run_id: query.run_id,
+        query_type: query.query_type,
+        query_args: query.query_args,
+        expires_at: expires_at_to_js(query.expires_at),
+      }
+    }
+  }
+
+  #[napi(object)]
+  /// Signal a workflow, starting it first if it isn't running, the `SignalWithStart` member of the interaction union taken by `encode` & returned by `decode`.
+  pub struct SignalWithStartInteraction {
+    #[napi(js_name = "type", ts_type = "'SignalWithStart'")]
+    pub kind: String,
+    pub namespace: String,
+    pub task_queue: String,
+    pub workflow_id: String,
+    pub workflow_type: String,
+    /// the args the workflow is started with, if it isn't running
+    pub args: Option<Vec<serde_json::Value>>,
+    pub signal_name: String,
+    pub input: Option<Vec<serde_json::Value>>,
+    pub identity: Option<String>,
+    pub request_id: Option<String>,
+    pub control: Option<String>,
+    /// unix timestamp (seconds) after which the encoded string no longer decodes
+    pub expires_at: Option<i64>,
+  }
+
+  impl From<SignalWithStartInteraction> for SignalWithStartTemporal {
+    fn from(signal_with_start: SignalWithStartInteraction) -> Self {
+      SignalWithStartTemporal {
+        namespace: signal_with_start.namespace,
+        task_queue: signal_with_start.task_queue,
+        workflow_id: signal_with_start.workflow_id,
+        workflow_type: signal_with_start.workflow_type,
+        args: signal_with_start.args,
+        signal_name: signal_with_start.signal_name,
+        input: signal_with_start.input,
+        identity: signal_with_start.identity,
+        request_id: signal_with_start.request_id,
+        control: signal_with_start.control,
+        expires_at: expires_at_from_js(signal_with_start.expires_at),
+      }
+    }
+  }
+
+  impl From<SignalWithStartTemporal> for SignalWithStartInteraction {
+    fn from(signal_with_start: SignalWithStartTemporal) -> Self {
+      SignalWithStartInteraction {
+        kind: "SignalWithStart".to_string(),
+        namespace: signal_with_start.namespace,
+        task_queue: signal_with_start.task_queue,
+        workflow_id: signal_with_start.workflow_id,
+        workflow_type: signal_with_start.workflow_type,
+        args: signal_with_start.args,
+        signal_name: signal_with_start.signal_name,
+        input: signal_with_start.input,
+        identity: signal_with_start.identity,
+        request_id: signal_with_start.request_id,
+        control: signal_with_start.control,
+        expires_at: expires_at_to_js(signal_with_start.expires_at),
+      }
+    }
+  }
+
+  #[napi(object)]
+  /// Request that a running workflow cancels itself, the `Cancel` member of the interaction union taken by `encode` & returned by `decode`.
+  pub struct CancelInteraction {
+    #[napi(js_name = "type", ts_type = "'Cancel'")]
+    pub kind: String,
+    pub namespace: String,
+    pub task_queue: String,
+    pub workflow_id: String,
+    pub run_id: Option<String>,
+    /// why the workflow was cancelled, recorded in its history
+    pub reason: Option<String>,
+    pub identity: Option<String>,
+    pub request_id: Option<String>,
+    /// unix timestamp (seconds) after which the encoded string no longer decodes
+    pub expires_at: Option<i64>,
+  }
+
+  impl From<CancelInteraction> for CancelTemporal {
+    fn from(cancel: CancelInteraction) -> Self {
+      CancelTemporal {
+        namespace: cancel.namespace,
+        task_queue: cancel.task_queue,
+        workflow_id: cancel.workflow_id,
+        run_id: cancel.run_id,
+        reason: cancel.reason,
+        identity: cancel.identity,
+        request_id: cancel.request_id,
+        expires_at: expires_at_from_js(cancel.expires_at),
+      }
+    }
+  }
+
+  impl From<CancelTemporal> for CancelInteraction {
+    fn from(cancel: CancelTemporal) -> Self {
+      CancelInteraction {
+        kind: "Cancel".to_string(),
+        namespace: cancel.namespace,
+        task_queue: cancel.task_queue,
+        workflow_id: cancel.workflow_id,
+        run_id: cancel.run_id,
+        reason: cancel.reason,
+        identity: cancel.identity,
+        request_id: cancel.request_id,
+        expires_at: expires_at_to_js(cancel.expires_at),
+      }
+    }
+  }
+
+  #[napi(object)]
+  /// Stop a running workflow immediately, the `Terminate` member of the interaction union taken by `encode` & returned by `decode`.
+  pub struct TerminateInteraction {
+    #[napi(js_name = "type", ts_type = "'Terminate'")]
+    pub kind: String,
+    pub namespace: String,
+    pub task_queue: String,
+    pub workflow_id: String,
+    pub run_id: Option<String>,
+    /// why the workflow was terminated, recorded in its history
+    pub reason: Option<String>,
+    /// recorded in the terminated workflow's history along with the reason
+    pub details: Option<Vec<serde_json::Value>>,
+    pub identity: Option<String>,
+    /// unix timestamp (seconds) after which the encoded string no longer decodes
+    pub expires_at: Option<i64>,
+  }
+
+  impl From<TerminateInteraction> for TerminateTemporal {
+    fn from(terminate: TerminateInteraction) -> Self {
+      TerminateTemporal {
+        namespace: terminate.namespace,
+        task_queue: terminate.task_queue,
+        workflow_id: terminate.workflow_id,
+        run_id: terminate.run_id,
+        reason: terminate.reason,
+        details: terminate.details,
+        identity: terminate.identity,
+        expires_at: expires_at_from_js(terminate.expires_at),
+      }
+    }
+  }
+
+  impl From<TerminateTemporal> for TerminateInteraction {
+    fn from(terminate: TerminateTemporal) -> Self {
+      TerminateInteraction {
+        kind: "Terminate".to_string(),
+        namespace: terminate.namespace,
+        task_queue: terminate.task_queue,
+        workflow_id: terminate.workflow_id,
+        run_id: terminate.run_id,
+        reason: terminate.reason,
+        details: terminate.details,
+        identity: terminate.identity,
+        expires_at: expires_at_to_js(terminate.expires_at),
+      }
+    }
+  }
+
+  #[napi(object)]
+  /// Send an update to a running workflow and wait for its result, the `Update` member of the interaction union taken by `encode` & returned by `decode`.
+  pub struct UpdateInteraction {
+    #[napi(js_name = "type", ts_type = "'Update'")]
+    pub kind: String,
+    pub namespace: String,
+    pub task_queue: String,
+    pub workflow_id: String,
+    pub run_id: Option<String>,
+    /// the update handler's name, as defined in the workflow
+    pub update_name: String,
+    pub args: Option<Vec<serde_json::Value>>,
+    /// how far the update gets before Temporal responds, `Completed` when unset
+    #[napi(ts_type = "'Accepted' | 'Completed'")]
+    pub wait_stage: Option<String>,
+    pub identity: Option<String>,
+    /// dedupes retried updates, a new UUID when unset
+    pub update_id: Option<String>,
+    /// unix timestamp (seconds) after which the encoded string no longer decodes
+    pub expires_at: Option<i64>,
+  }
+
+  impl TryFrom<UpdateInteraction> for UpdateTemporal {
+    type Error = napi::Error;
+
+    fn try_from(update: UpdateInteraction) -> napi::Result<Self> {
+      let wait_stage = update
+        .wait_stage
+        .map(|wait_stage| {
+          wait_stage.parse::<UpdateWaitStage>().map_err(|_| {
+            napi::Error::new(
+              Status::InvalidArg,
+              format!(
+                "unknown waitStage `{}`, expected Accepted or Completed",
+                wait_stage
+              ),
+            )
+          })
+        })
+        .transpose()?;
+
+      Ok(UpdateTemporal {
+        namespace: update.namespace,
+        task_queue: update.task_queue,
+        workflow_id: update.workflow_id,
+        run_id: update.run_id,
+        update_name: update.update_name,
+        args: update.args,
+        wait_stage,
+        identity: update.identity,
+        update_id: update.update_id,
+        expires_at: expires_at_from_js(update.expires_at),
+      })
+    }
+  }
+
+  impl From<UpdateTemporal> for UpdateInteraction {
+    fn from(update: UpdateTemporal) -> Self {
+      UpdateInteraction {
+        kind: "Update".to_string(),
+        namespace: update.namespace,
+        task_queue: update.task_queue,
+        workflow_id: update.workflow_id,
+        run_id: update.run_id,
+        update_name: update.update_name,
+        args: update.args,
+        wait_stage: update.wait_stage.map(|wait_stage| wait_stage.to_string()),
+        identity: update.identity,
+        update_id: update.update_id,
+        expires_at: expires_at_to_js(update.expires_at),
+      }
+    }
+  }
+
+  #[napi(object)]
+  /// Finish an activity that is completed asynchronously, the `CompleteActivity` member of the interaction union taken by `encode` & returned by `decode`.
+  ///
+  /// The activity is found by its `taskToken`, or by `workflowId` & `activityId` when the token is too long to encode.
+  pub struct CompleteActivityInteraction {
+    #[napi(js_name = "type", ts_type = "'CompleteActivity'")]
+    pub kind: String,
+    pub namespace: String,
+    pub task_queue: String,
+    /// the activity's task token as base64url, without padding
+    pub task_token: Option<String>,
+    pub workflow_id: Option<String>,
+    pub run_id: Option<String>,
+    pub activity_id: Option<String>,
+    /// how the activity finishes, `Completed` when unset
+    #[napi(ts_type = "'Completed' | 'Failed' | 'Canceled'")]
+    pub outcome: Option<String>,
+    /// the activity's result, or the details of its failure or cancellation
+    pub result: Option<Vec<serde_json::Value>>,
+    /// the failure message, when the activity is failed
+    pub reason: Option<String>,
+    pub identity: Option<String>,
+    /// unix timestamp (seconds) after which the encoded string no longer decodes
+    pub expires_at: Option<i64>,
+  }
+
+  impl TryFrom<CompleteActivityInteraction> for CompleteActivityTemporal {
+    type Error = napi::Error;
+
+    fn try_from(complete: CompleteActivityInteraction) -> napi::Result<Self> {
+      let outcome = complete
+        .outcome
+        .map(|outcome| {
+          outcome.parse::<ActivityOutcome>().map_err(|_| {
+            napi::Error::new(
+              Status::InvalidArg,
+              format!(
+                "unknown outcome `{}`, expected Completed, Failed or Canceled",
+                outcome
+              ),
+            )
+          })
+        })
+        .transpose()?;
+
+      Ok(CompleteActivityTemporal {
+        namespace: complete.namespace,
+        task_queue: complete.task_queue,
+        task_token: complete.task_token,
+        workflow_id: complete.workflow_id,
+        run_id: complete.run_id,
+        activity_id: complete.activity_id,
+        outcome,
+        result: complete.result,
+        reason: complete.reason,
+        identity: complete.identity,
+        expires_at: expires_at_from_js(complete.expires_at),
+      })
+    }
+  }
+
+  impl From<CompleteActivityTemporal> for CompleteActivityInteraction {
+    fn from(complete: CompleteActivityTemporal) -> Self {
+      CompleteActivityInteraction {
+        kind: "CompleteActivity".to_string(),
+        namespace: complete.namespace,
+        task_queue: complete.task_queue,
+        task_token: complete.task_token,
+        workflow_id: complete.workflow_id,
+        run_id: complete.run_id,
+        activity_id: complete.activity_id,
+        outcome: complete.outcome.map(|outcome| outcome.to_string()),
+        result: complete.result,
+        reason: complete.reason,
+        identity: complete.identity,
+        expires_at: expires_at_to_js(complete.expires_at),
+      }
+    }
+  }
+
+  #[napi(object, object_from_js = false)]
+  /// The result of `decode`: the interaction, and the user data section after it if there is one.
+  pub struct DecodedInteraction {
+    #[napi(
+      ts_type = "ExecuteInteraction | SignalInteraction | QueryInteraction | SignalWithStartInteraction | CancelInteraction | TerminateInteraction | UpdateInteraction | CompleteActivityInteraction"
+    )]
+    pub interaction: Either8<
+      ExecuteInteraction,
+      SignalInteraction,
+      QueryInteraction,
+      SignalWithStartInteraction,
+      CancelInteraction,
+      TerminateInteraction,
+      UpdateInteraction,
+      CompleteActivityInteraction,
+    >,
+    pub user_data: Option<String>,
+  }
+
+  impl From<(TemporalInteraction, Option<String>)> for DecodedInteraction {
+    fn from((interaction, user_data): (TemporalInteraction, Option<String>)) -> Self {
+      let interaction = match interaction {
+        TemporalInteraction::Execute(execute) => Either8::A(execute.into()),
+        TemporalInteraction::Signal(signal) => Either8::B(signal.into()),
+        TemporalInteraction::Query(query) => Either8::C(query.into()),
+        TemporalInteraction::SignalWithStart(signal_with_start) => {
+          Either8::D(signal_with_start.into())
+        }
+        TemporalInteraction::Cancel(cancel) => Either8::E(cancel.into()),
+        TemporalInteraction::Terminate(terminate) => Either8::F(terminate.into()),
+        TemporalInteraction::Update(update) => Either8::G(update.into()),
+        TemporalInteraction::CompleteActivity(complete) => Either8::H(complete.into()),
+      };
+      DecodedInteraction {
+        interaction,
+        user_data,
+      }
+    }
+  }
+
+  /// Read one of the interaction objects, picking the struct by its `type`
+  fn interaction_from_js(env: Env, interaction: JsObject) -> napi::Result<TemporalInteraction> {
+    let kind: String = interaction.get_named_property("type").map_err(|_| {
+      napi::Error::new(
+        Status::InvalidArg,
+        format!("interaction needs a string `type` of {}", INTERACTION_TYPES),
+      )
+    })?;
+
+    // the object & env are the ones napi passed to this call, so the raw handles are live
+    unsafe {
+      match kind.as_str() {
+        "Execute" => ExecuteInteraction::from_napi_value(env.raw(), interaction.raw())
+          .and_then(|execute| Ok(TemporalInteraction::Execute(execute.try_into()?))),
+        "Signal" => SignalInteraction::from_napi_value(env.raw(), interaction.raw())
+          .map(|signal| TemporalInteraction::Signal(signal.into())),
+        "Query" => QueryInteraction::from_napi_value(env.raw(), interaction.raw())
+          .map(|query| TemporalInteraction::Query(query.into())),
+        "SignalWithStart" => {
+          SignalWithStartInteraction::from_napi_value(env.raw(), interaction.raw())
+            .map(|signal_with_start| TemporalInteraction::SignalWithStart(signal_with_start.into()))
+        }
+        "Cancel" => CancelInteraction::from_napi_value(env.raw(), interaction.raw())
+          .map(|cancel| TemporalInteraction::Cancel(cancel.into())),
+        "Terminate" => TerminateInteraction::from_napi_value(env.raw(), interaction.raw())
+          .map(|terminate| TemporalInteraction::Terminate(terminate.into())),
+        "Update" => UpdateInteraction::from_napi_value(env.raw(), interaction.raw())
+          .and_then(|update| Ok(TemporalInteraction::Update(update.try_into()?))),
+        "CompleteActivity" => {
+          CompleteActivityInteraction::from_napi_value(env.raw(), interaction.raw())
+            .and_then(|complete| Ok(TemporalInteraction::CompleteActivity(complete.try_into()?)))
+        }
+        _ => Err(napi::Error::new(
+          Status::InvalidArg,
+          format!(
+            "unknown interaction type `{}`, expected {}",
+            kind, INTERACTION_TYPES
+          ),
+        )),
+      }
+    }
+  }
+
   #[napi]
   /// Convert workflow metadata into an encoded string for embedding into a webhook event, using the default encoding.
   ///
   /// Example: use this string as the `callback_id` for a slack interaction, and when
   /// the Temporal API Gateway receives the event it can decode the string and route the event to your workflow.
-  fn encode_signal_no_args_default(signal: TemporalSignalWithoutInput) -> String {
-    Encoder::default().encode(TemporalInteraction::Signal(signal.into()))
+  fn encode_signal_no_args_default(signal: TemporalSignalWithoutInput) -> napi::Result<String> {
+    Encoder::default()
+      .encode(TemporalInteraction::Signal(signal.into()))
+      .map_err(|err| napi::Error::new(Status::GenericFailure, format!("failed to encode, {}", err)))
   }
 
   #[napi]
@@ -54,14 +746,30 @@ pub mod encoder {
   fn encode_signal_no_args_with_version(
     encoder_version: Encoder,
     signal: TemporalSignalWithoutInput,
-  ) -> String {
-    encoder_version.encode(TemporalInteraction::Signal(signal.into()))
+  ) -> napi::Result<String> {
+    encoder_version
+      .encode(TemporalInteraction::Signal(signal.into()))
+      .map_err(|err| napi::Error::new(Status::GenericFailure, format!("failed to encode, {}", err)))
+  }
+
+  #[napi]
+  /// Convert workflow metadata into an encoded string, and fail if it's too long for the profile's field.
+  ///
+  /// Example: use `Profile.DiscordCustomId` to find out before Discord rejects a 101 char `custom_id`.
+  fn encode_signal_no_args_checked(
+    encoder_version: Encoder,
+    signal: TemporalSignalWithoutInput,
+    profile: Profile,
+  ) -> napi::Result<String> {
+    encoder_version
+      .encode_checked(TemporalInteraction::Signal(signal.into()), profile)
+      .map_err(|err| napi::Error::new(Status::GenericFailure, format!("failed to encode, {}", err)))
   }
 
   #[napi]
   /// Encode a TemporalInteraction struct provided as a JSON string.
   ///
-  /// panics if JSON structure is not correct.
+  /// Fails if the JSON doesn't match the `TemporalInteraction` type, see `encode` for a typed alternative.
   fn encode_default_from_json_string(json_string: String) -> napi::Result<String> {
     Encoder::encode_default_from_json_string(&json_string).map_err(|err| {
       napi::Error::new(
@@ -71,14 +779,102 @@ pub mod encoder {
     })
   }
 
+  #[napi]
+  /// Encode a TemporalInteraction struct provided as a JSON string, and fail if it's too long for the profile's field.
+  fn encode_checked_from_json_string(
+    json_string: String,
+    profile: Profile,
+    encoder_version: Option<Encoder>,
+  ) -> napi::Result<String> {
+    serde_json::from_str(&json_string)
+      .map_err(anyhow::Error::from)
+      .and_then(|interaction| {
+        encoder_version
+          .unwrap_or_default()
+          .encode_checked(interaction, profile)
+      })
+      .map_err(|err| {
+        napi::Error::new(
+          Status::GenericFailure,
+          format!("failed to encode from json string, {}", err),
+        )
+      })
+  }
+
   #[napi]
   /// Decode an encoded string into a JSON string representing a TemporalInteraction struct.
-  fn decode_to_json_string(encoded_string: String) -> napi::Result<String> {
+  ///
+  /// Errors have a `code` like `MISSING_KEY` or `EXPIRED` when the encoded string is invalid.
+  fn decode_to_json_string(encoded_string: String) -> napi::Result<String, &'static str> {
     Encoder::decode_to_json_string(&encoded_string).map_err(|err| {
       napi::Error::new(
-        Status::GenericFailure,
+        decode_error_code(&err),
         format!("failed to decode from encoded string, {}", err),
       )
     })
   }
+
+  #[napi(
+    ts_args_type = "interaction: ExecuteInteraction | SignalInteraction | QueryInteraction | SignalWithStartInteraction | CancelInteraction | TerminateInteraction | UpdateInteraction | CompleteActivityInteraction, encoderVersion?: Encoder | undefined | null, userData?: string | undefined | null"
+  )]
+  /// Encode an interaction object, picked by its `type`, with the default encoding unless a version is given.
+  ///
+  /// `userData` is added after the temporal info, and is returned by `decode`.
+  fn encode(
+    env: Env,
+    interaction: JsObject,
+    encoder_version: Option<Encoder>,
+    user_data: Option<String>,
+  ) -> napi::Result<String> {
+    let interaction = interaction_from_js(env, interaction)?;
+    let encoder_version = encoder_version.unwrap_or_default();
+
+    match user_data {
+      Some(user_data) => encoder_version.encode_with_user_data(interaction, &user_data),
+      None => encoder_version.encode(interaction),
+    }
+    .map_err(|err| napi::Error::new(Status::GenericFailure, format!("failed to encode, {}", err)))
+  }
+
+  #[napi]
+  /// Decode an encoded string into an interaction object, with the user data section if there is one.
+  ///
+  /// Errors have a `code` like `MISSING_KEY` or `EXPIRED` when the encoded string is invalid.
+  fn decode(encoded_string: String) -> napi::Result<DecodedInteraction, &'static str> {
+    Encoder::decode_with_user_data(&encoded_string)
+      .map(DecodedInteraction::from)
+      .map_err(|err| {
+        napi::Error::new(
+          err.code(),
+          format!("failed to decode from encoded string, {}", err),
+        )
+      })
+  }
+
+  /// the `DecodeError` variant's code, for the JS error's `code` property
+  fn decode_error_code(err: &anyhow::Error) -> &'static str {
+    err
+      .downcast_ref::<DecodeError>()
+      .map_or(Status::GenericFailure.as_ref(), DecodeError::code)
+  }
+
+  #[napi]
+  /// Set the keys used to sign encoded strings with `Encoder.S`, as a comma separated list of `key_id:secret` pairs.
+  /// The first key is used for signing. Must be called before the first encode/decode.
+  ///
+  /// If not called, keys are read from the `TEMPORAL_JSON_SIGNING_KEYS` env var.
+  fn set_signing_keys(key_list: String, require_signature: Option<bool>) -> napi::Result<()> {
+    SigningKeys::from_key_list(&key_list)
+      .and_then(|keys| {
+        temporal_json::set_signing_keys(
+          keys.with_require_signature(require_signature.unwrap_or_default()),
+        )
+      })
+      .map_err(|err| {
+        napi::Error::new(
+          Status::GenericFailure,
+          format!("failed to set signing keys, {}", err),
+        )
+      })
+  }
 }
@@ -0,0 +1,241 @@
+/* generated by `cargo xtask codegen` from the temporal-json JSON Schemas, do not edit */
+
+export type TemporalInteraction =
+  /** start a workflow */
+  | {
+    args?: Array<unknown> | null
+    /** unix timestamp (seconds) after which the encoded string no longer decodes */
+    expires_at?: number | null
+    namespace: string
+    /** timeouts, retries & the rest of how the workflow is started */
+    options?: 
+      | WorkflowStartOptions
+      | null
+    task_queue: string
+    type: "Execute"
+    workflow_id: string
+    /** the Workflow's Function name */
+    workflow_type: string
+  }
+  /** signal a running workflow */
+  | {
+    control?: string | null
+    /** unix timestamp (seconds) after which the encoded string no longer decodes */
+    expires_at?: number | null
+    identity?: string | null
+    input?: Array<unknown> | null
+    namespace: string
+    request_id?: string | null
+    run_id?: string | null
+    signal_name: string
+    task_queue: string
+    type: "Signal"
+    workflow_id?: string | null
+  }
+  /** query a running workflow */
+  | {
+    /** unix timestamp (seconds) after which the encoded string no longer decodes */
+    expires_at?: number | null
+    namespace: string
+    query_args?: Array<unknown> | null
+    query_type: string
+    run_id?: string | null
+    task_queue: string
+    type: "Query"
+    workflow_id?: string | null
+  }
+  /** signal a workflow, starting it first if it isn't running */
+  | {
+    /** the args the workflow is started with, if it isn't running */
+    args?: Array<unknown> | null
+    control?: string | null
+    /** unix timestamp (seconds) after which the encoded string no longer decodes */
+    expires_at?: number | null
+    identity?: string | null
+    input?: Array<unknown> | null
+    namespace: string
+    request_id?: string | null
+    signal_name: string
+    task_queue: string
+    type: "SignalWithStart"
+    workflow_id: string
+    /** the Workflow's Function name, if it has to be started */
+    workflow_type: string
+  }
+  /** request that a running workflow cancels itself */
+  | {
+    /** unix timestamp (seconds) after which the encoded string no longer decodes */
+    expires_at?: number | null
+    identity?: string | null
+    namespace: string
+    /** why the workflow was cancelled, recorded in its history */
+    reason?: string | null
+    request_id?: string | null
+    run_id?: string | null
+    task_queue: string
+    type: "Cancel"
+    workflow_id: string
+  }
+  /** stop a running workflow immediately, without running its cleanup */
+  | {
+    /** recorded in the terminated workflow's history along with the reason */
+    details?: Array<unknown> | null
+    /** unix timestamp (seconds) after which the encoded string no longer decodes */
+    expires_at?: number | null
+    identity?: string | null
+    namespace: string
+    /** why the workflow was terminated, recorded in its history */
+    reason?: string | null
+    run_id?: string | null
+    task_queue: string
+    type: "Terminate"
+    workflow_id: string
+  }
+  /** send an update to a running workflow and wait for what its handler returns */
+  | {
+    args?: Array<unknown> | null
+    /** unix timestamp (seconds) after which the encoded string no longer decodes */
+    expires_at?: number | null
+    identity?: string | null
+    namespace: string
+    run_id?: string | null
+    task_queue: string
+    type: "Update"
+    /** dedupes retried updates, a new UUID when unset */
+    update_id?: string | null
+    /** the update handler's name, as defined in the workflow */
+    update_name: string
+    /** how far the update gets before Temporal responds, `Completed` when unset */
+    wait_stage?: 
+      | UpdateWaitStage
+      | null
+    workflow_id: string
+  }
+  /** finish an activity that is waiting to be completed asynchronously */
+  | {
+    activity_id?: string | null
+    /** unix timestamp (seconds) after which the encoded string no longer decodes */
+    expires_at?: number | null
+    identity?: string | null
+    namespace: string
+    /** how the activity finishes, `Completed` when unset */
+    outcome?: 
+      | ActivityOutcome
+      | null
+    /** the failure message, when the activity is failed */
+    reason?: string | null
+    /** the activity's result, or the details of its failure or cancellation */
+    result?: Array<unknown> | null
+    run_id?: string | null
+    task_queue: string
+    /** the activity's task token as base64url, without padding */
+    task_token?: string | null
+    type: "CompleteActivity"
+    workflow_id?: string | null
+  }
+
+/** What Temporal returned for a TemporalInteraction */
+export type TemporalInteractionResponse =
+  | {
+    run_id: string
+    type: "ExecuteWorkflow"
+  }
+  | {
+    type: "Signal"
+  }
+  | {
+    /** the workflow's execution status, when Temporal rejected the query */
+    query_rejected?: number | null
+    /** each payload the query returned, as JSON */
+    query_result?: Array<unknown> | null
+    type: "Query"
+  }
+  | {
+    /** the run that was signalled, whether it was already running or just started */
+    run_id: string
+    type: "SignalWithStart"
+  }
+  | {
+    type: "Cancel"
+  }
+  | {
+    type: "Terminate"
+  }
+  | {
+    /** the update handler's failure message, when it rejected or failed the update */
+    failure?: string | null
+    /** each payload the update handler returned, as JSON */
+    result?: Array<unknown> | null
+    /** how far the update got, `Accepted` updates have no result yet */
+    stage: UpdateWaitStage
+    type: "Update"
+    update_id: string
+  }
+  | {
+    type: "CompleteActivity"
+  }
+
+/** How an asynchronously completed activity finishes */
+export type ActivityOutcome =
+  /** the activity returns its result */
+  | "Completed"
+  /** the activity fails with the reason, and is retried by its retry policy */
+  | "Failed"
+  /** the activity is cancelled, only once the workflow has requested it */
+  | "Canceled"
+
+/** How far an update gets before Temporal responds */
+export type UpdateWaitStage =
+  /** the workflow's validator accepted the update, its handler may still be running */
+  | "Accepted"
+  /** the workflow's handler returned a result or failed */
+  | "Completed"
+
+/** Whether a workflow can start with the id of one that already ran */
+export type WorkflowIdReusePolicy =
+  /** if the last workflow with the id has closed, whatever its result */
+  | "AllowDuplicate"
+  /** if the last workflow with the id failed, was cancelled, terminated or timed out */
+  | "AllowDuplicateFailedOnly"
+  /** never */
+  | "RejectDuplicate"
+  /** and any running workflow with the id is terminated first */
+  | "TerminateIfRunning"
+
+/** How a failed workflow is retried */
+export type WorkflowRetryPolicy = {
+  /** how much longer each interval is than the last */
+  backoff_coefficient?: number | null
+  /** seconds before the first retry */
+  initial_interval_secs?: number | null
+  /** attempts including the first, unlimited when unset or 0 */
+  maximum_attempts?: number | null
+  /** the longest interval in seconds, however many retries */
+  maximum_interval_secs?: number | null
+  /** error types that fail the workflow without a retry */
+  non_retryable_error_types?: Array<string> | null
+}
+
+/** How a workflow is started, Temporal's defaults are used for anything unset */
+export type WorkflowStartOptions = {
+  /** start the workflow on a cron schedule, e.g. `0 9 * * MON` */
+  cron_schedule?: string | null
+  /** seconds the workflow can take, including retries & continue-as-new */
+  execution_timeout_secs?: number | null
+  /** whether a workflow can start with the id of one that already ran */
+  id_reuse_policy?: 
+    | WorkflowIdReusePolicy
+    | null
+  /** shown with the workflow, but can't be searched by */
+  memo?: Record<string, unknown> | null
+  /** failed workflows aren't retried unless this is set */
+  retry_policy?: 
+    | WorkflowRetryPolicy
+    | null
+  /** seconds a single run of the workflow can take */
+  run_timeout_secs?: number | null
+  /** values the workflow can be listed by, each must be registered with Temporal */
+  search_attributes?: Record<string, unknown> | null
+  /** seconds a single workflow task can take */
+  task_timeout_secs?: number | null
+}
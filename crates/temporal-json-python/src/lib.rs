@@ -0,0 +1,1131 @@
+//! Python bindings for the `temporal-json` crate
+//! Provides an encoder & decoder interface for the temporal api gateway, mirroring `temporal-json-node`
+
+use pyo3::{
+    basic::CompareOp, create_exception, exceptions::PyValueError, prelude::*, types::PyDict,
+};
+use pythonize::{depythonize, pythonize};
+use serde::Serialize;
+use serde_json::Value;
+use temporal_json::{
+    ActivityOutcome, CancelTemporal, CompleteActivityTemporal, ExecuteTemporalWorkflow,
+    QueryTemporal, SignalTemporal, SignalWithStartTemporal, TemporalInteraction, TerminateTemporal,
+    UpdateTemporal, UpdateWaitStage, WorkflowStartOptions,
+};
+pub use temporal_json::{Encoder, Profile, SigningKeys};
+
+create_exception!(
+    temporal_json,
+    DecodeError,
+    PyValueError,
+    "An encoded string couldn't be decoded. Its `code` says why, like `MISSING_KEY` or `EXPIRED`."
+);
+
+// Interaction classes ////////////////////////////////////////////////////////
+
+#[pyclass(module = "temporal_json")]
+/// A Signal without the Input Payload.
+///
+/// This is useful for routing webhook events back to the workflow,
+/// and the event itself will be sent as the Signal's Input to the workflow.
+pub struct TemporalSignalWithoutInput {
+    #[pyo3(get, set)]
+    pub namespace: String,
+    #[pyo3(get, set)]
+    pub task_queue: String,
+    #[pyo3(get, set)]
+    pub workflow_id: Option<String>,
+    #[pyo3(get, set)]
+    pub run_id: Option<String>,
+    #[pyo3(get, set)]
+    pub signal_name: String,
+    /// unix timestamp (seconds) after which the encoded string no longer decodes
+    #[pyo3(get, set)]
+    pub expires_at: Option<u64>,
+}
+
+#[pymethods]
+impl TemporalSignalWithoutInput {
+    #[new]
+    #[pyo3(signature = (namespace, task_queue, signal_name, workflow_id=None, run_id=None, expires_at=None))]
+    fn new(
+        namespace: String,
+        task_queue: String,
+        signal_name: String,
+        workflow_id: Option<String>,
+        run_id: Option<String>,
+        expires_at: Option<u64>,
+    ) -> Self {
+        Self {
+            namespace,
+            task_queue,
+            workflow_id,
+            run_id,
+            signal_name,
+            expires_at,
+        }
+    }
+
+    fn __repr__(&self, py: Python) -> PyResult<String> {
+        dataclass_repr(py, "TemporalSignalWithoutInput", &self.to_signal())
+    }
+
+    fn __richcmp__(&self, other: PyRef<Self>, op: CompareOp, py: Python) -> PyObject {
+        compare(py, op, self.to_signal() == other.to_signal())
+    }
+}
+
+impl TemporalSignalWithoutInput {
+    fn to_signal(&self) -> SignalTemporal {
+        SignalTemporal {
+            namespace: self.namespace.clone(),
+            task_queue: self.task_queue.clone(),
+            workflow_id: self.workflow_id.clone(),
+            run_id: self.run_id.clone(),
+            signal_name: self.signal_name.clone(),
+            expires_at: self.expires_at,
+            ..Default::default()
+        }
+    }
+}
+
+#[pyclass(module = "temporal_json", name = "ExecuteTemporalWorkflow")]
+/// Start a workflow
+pub struct PyExecuteTemporalWorkflow {
+    #[pyo3(get, set)]
+    pub namespace: String,
+    #[pyo3(get, set)]
+    pub task_queue: String,
+    #[pyo3(get, set)]
+    pub workflow_id: String,
+    /// the Workflow's Function name
+    #[pyo3(get, set)]
+    pub workflow_type: String,
+    /// list of JSON-serializable values
+    #[pyo3(get, set)]
+    pub args: Option<PyObject>,
+    /// dict of `WorkflowStartOptions`, like `{"run_timeout_secs": 3600}`
+    #[pyo3(get, set)]
+    pub options: Option<PyObject>,
+    /// unix timestamp (seconds) after which the encoded string no longer decodes
+    #[pyo3(get, set)]
+    pub expires_at: Option<u64>,
+}
+
+#[pymethods]
+impl PyExecuteTemporalWorkflow {
+    #[new]
+    #[pyo3(signature = (namespace, task_queue, workflow_id, workflow_type, args=None, options=None, expires_at=None))]
+    fn new(
+        namespace: String,
+        task_queue: String,
+        workflow_id: String,
+        workflow_type: String,
+        args: Option<PyObject>,
+        options: Option<PyObject>,
+        expires_at: Option<u64>,
+    ) -> Self {
+        Self {
+            namespace,
+            task_queue,
+            workflow_id,
+            workflow_type,
+            args,
+            options,
+            expires_at,
+        }
+    }
+
+    fn __repr__(&self, py: Python) -> PyResult<String> {
+        dataclass_repr(py, "ExecuteTemporalWorkflow", &self.to_rust(py)?)
+    }
+
+    fn __richcmp__(&self, other: PyRef<Self>, op: CompareOp, py: Python) -> PyResult<PyObject> {
+        Ok(compare(py, op, self.to_rust(py)? == other.to_rust(py)?))
+    }
+}
+
+impl PyExecuteTemporalWorkflow {
+    fn to_rust(&self, py: Python) -> PyResult<ExecuteTemporalWorkflow> {
+        Ok(ExecuteTemporalWorkflow {
+            namespace: self.namespace.clone(),
+            task_queue: self.task_queue.clone(),
+            workflow_id: self.workflow_id.clone(),
+            workflow_type: self.workflow_type.clone(),
+            args: json_values(py, &self.args)?,
+            options: start_options(py, &self.options)?,
+            expires_at: self.expires_at,
+        })
+    }
+
+    fn from_rust(py: Python, execute: ExecuteTemporalWorkflow) -> PyResult<Self> {
+        Ok(Self {
+            namespace: execute.namespace,
+            task_queue: execute.task_queue,
+            workflow_id: execute.workflow_id,
+            workflow_type: execute.workflow_type,
+            args: py_values(py, execute.args)?,
+            options: execute
+                .options
+                .map(|options| pythonize(py, &options))
+                .transpose()?,
+            expires_at: execute.expires_at,
+        })
+    }
+}
+
+#[pyclass(module = "temporal_json", name = "SignalTemporal")]
+/// Signal a running workflow
+pub struct PySignalTemporal {
+    #[pyo3(get, set)]
+    pub namespace: String,
+    #[pyo3(get, set)]
+    pub task_queue: String,
+    #[pyo3(get, set)]
+    pub workflow_id: Option<String>,
+    #[pyo3(get, set)]
+    pub run_id: Option<String>,
+    #[pyo3(get, set)]
+    pub signal_name: String,
+    /// list of JSON-serializable values
+    #[pyo3(get, set)]
+    pub input: Option<PyObject>,
+    #[pyo3(get, set)]
+    pub identity: Option<String>,
+    #[pyo3(get, set)]
+    pub request_id: Option<String>,
+    #[pyo3(get, set)]
+    pub control: Option<String>,
+    /// unix timestamp (seconds) after which the encoded string no longer decodes
+    #[pyo3(get, set)]
+    pub expires_at: Option<u64>,
+}
+
+#[pymethods]
+impl PySignalTemporal {
+    #[new]
+    #[pyo3(signature = (namespace, task_queue, signal_name, workflow_id=None, run_id=None, input=None, identity=None, request_id=None, control=None, expires_at=None))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        namespace: String,
+        task_queue: String,
+        signal_name: String,
+        workflow_id: Option<String>,
+        run_id: Option<String>,
+        input: Option<PyObject>,
+        identity: Option<String>,
+        request_id: Option<String>,
+        control: Option<String>,
+        expires_at: Option<u64>,
+    ) -> Self {
+        Self {
+            namespace,
+            task_queue,
+            workflow_id,
+            run_id,
+            signal_name,
+            input,
+            identity,
+            request_id,
+            control,
+            expires_at,
+        }
+    }
+
+    fn __repr__(&self, py: Python) -> PyResult<String> {
+        dataclass_repr(py, "SignalTemporal", &self.to_rust(py)?)
+    }
+
+    fn __richcmp__(&self, other: PyRef<Self>, op: CompareOp, py: Python) -> PyResult<PyObject> {
+        Ok(compare(py, op, self.to_rust(py)? == other.to_rust(py)?))
+    }
+}
+
+impl PySignalTemporal {
+    fn to_rust(&self, py: Python) -> PyResult<SignalTemporal> {
+        Ok(SignalTemporal {
+            namespace: self.namespace.clone(),
+            task_queue: self.task_queue.clone(),
+            workflow_id: self.workflow_id.clone(),
+            run_id: self.run_id.clone(),
+            signal_name: self.signal_name.clone(),
+            input: json_values(py, &self.input)?,
+            identity: self.identity.clone(),
+            request_id: self.request_id.clone(),
+            control: self.control.clone(),
+            expires_at: self.expires_at,
+        })
+    }
+
+    fn from_rust(py: Python, signal: SignalTemporal) -> PyResult<Self> {
+        Ok(Self {
+            namespace: signal.namespace,
+            task_queue: signal.task_queue,
+            workflow_id: signal.workflow_id,
+            run_id: signal.run_id,
+            signal_name: signal.signal_name,
+            input: py_values(py, signal.input)?,
+            identity: signal.identity,
+            request_id: signal.request_id,
+            control: signal.control,
+            expires_at: signal.expires_at,
+        })
+    }
+}
+
+#[pyclass(module = "temporal_json", name = "QueryTemporal")]
+/// Query a running workflow
+pub struct PyQueryTemporal {
+    #[pyo3(get, set)]
+    pub namespace: String,
+    #[pyo3(get, set)]
+    pub task_queue: String,
+    #[pyo3(get, set)]
+    pub workflow_id: Option<String>,
+    #[pyo3(get, set)]
+    pub run_id: Option<String>,
+    #[pyo3(get, set)]
+    pub query_type: String,
+    /// list of JSON-serializable values
+    #[pyo3(get, set)]
+    pub query_args: Option<PyObject>,
+    /// unix timestamp (seconds) after which the encoded string no longer decodes
+    #[pyo3(get, set)]
+    pub expires_at: Option<u64>,
+}
+
+#[pymethods]
+impl PyQueryTemporal {
+    #[new]
+    #[pyo3(signature = (namespace, task_queue, query_type, workflow_id=None, run_id=None, query_args=None, expires_at=None))]
+    fn new(
+        namespace: String,
+        task_queue: String,
+        query_type: String,
+        workflow_id: Option<String>,
+        run_id: Option<String>,
+        query_args: Option<PyObject>,
+        expires_at: Option<u64>,
+    ) -> Self {
+        Self {
+            namespace,
+            task_queue,
+            workflow_id,
+            run_id,
+            query_type,
+            query_args,
+            expires_at,
+        }
+    }
+
+    fn __repr__(&self, py: Python) -> PyResult<String> {
+        dataclass_repr(py, "QueryTemporal", &self.to_rust(py)?)
+    }
+
+    fn __richcmp__(&self, other: PyRef<Self>, op: CompareOp, py: Python) -> PyResult<PyObject> {
+        Ok(compare(py, op, self.to_rust(py)? == other.to_rust(py)?))
+    }
+}
+
+impl PyQueryTemporal {
+    fn to_rust(&self, py: Python) -> PyResult<QueryTemporal> {
+        Ok(QueryTemporal {
+            namespace: self.namespace.clone(),
+            task_queue: self.task_queue.clone(),
+            workflow_id: self.workflow_id.clone(),
+            run_id: self.run_id.clone(),
+            query_type: self.query_type.clone(),
+            query_args: json_values(py, &self.query_args)?,
+            expires_at: self.expires_at,
+        })
+    }
+
+    fn from_rust(py: Python, query: QueryTemporal) -> PyResult<Self> {
+        Ok(Self {
+            namespace: query.namespace,
+            task_queue: query.task_queue,
+            workflow_id: query.workflow_id,
+            run_id: query.run_id,
+            query_type: query.query_type,
+            query_args: py_values(py, query.query_args)?,
+            expires_at: query.expires_at,
+        })
+    }
+}
+
+#[pyclass(module = "temporal_json", name = "SignalWithStartTemporal")]
+/// Signal a workflow, starting it first if it isn't running
+pub struct PySignalWithStartTemporal {
+    #[pyo3(get, set)]
+    pub namespace: String,
+    #[pyo3(get, set)]
+    pub task_queue: String,
+    #[pyo3(get, set)]
+    pub workflow_id: String,
+    /// the Workflow's Function name, if it has to be started
+    #[pyo3(get, set)]
+    pub workflow_type: String,
+    /// list of JSON-serializable values the workflow is started with, if it isn't running
+    #[pyo3(get, set)]
+    pub args: Option<PyObject>,
+    #[pyo3(get, set)]
+    pub signal_name: String,
+    /// list of JSON-serializable values
+    #[pyo3(get, set)]
+    pub input: Option<PyObject>,
+    #[pyo3(get, set)]
+    pub identity: Option<String>,
+    #[pyo3(get, set)]
+    pub request_id: Option<String>,
+    #[pyo3(get, set)]
+    pub control: Option<String>,
+    /// unix timestamp (seconds) after which the encoded string no longer decodes
+    #[pyo3(get, set)]
+    pub expires_at: Option<u64>,
+}
+
+#[pymethods]
+impl PySignalWithStartTemporal {
+    #[new]
+    #[pyo3(signature = (namespace, task_queue, workflow_id, workflow_type, signal_name, args=None, input=None, identity=None, request_id=None, control=None, expires_at=None))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        namespace: String,
+        task_queue: String,
+        workflow_id: String,
+        workflow_type: String,
+        signal_name: String,
+        args: Option<PyObject>,
+        input: Option<PyObject>,
+        identity: Option<String>,
+        request_id: Option<String>,
+        control: Option<String>,
+        expires_at: Option<u64>,
+    ) -> Self {
+        Self {
+            namespace,
+            task_queue,
+            workflow_id,
+            workflow_type,
+            args,
+            signal_name,
+            input,
+            identity,
+            request_id,
+            control,
+            expires_at,
+        }
+    }
+
+    fn __repr__(&self, py: Python) -> PyResult<String> {
+        dataclass_repr(py, "SignalWithStartTemporal", &self.to_rust(py)?)
+    }
+
+    fn __richcmp__(&self, other: PyRef<Self>, op: CompareOp, py: Python) -> PyResult<PyObject> {
+        Ok(compare(py, op, self.to_rust(py)? == other.to_rust(py)?))
+    }
+}
+
+impl PySignalWithStartTemporal {
+    fn to_rust(&self, py: Python) -> PyResult<SignalWithStartTemporal> {
+        Ok(SignalWithStartTemporal {
+            namespace: self.namespace.clone(),
+            task_queue: self.task_queue.clone(),
+            workflow_id: self.workflow_id.clone(),
+            workflow_type: self.workflow_type.clone(),
+            args: json_values(py, &self.args)?,
+            signal_name: self.signal_name.clone(),
+            input: json_values(py, &self.input)?,
+            identity: self.identity.clone(),
+            request_id: self.request_id.clone(),
+            control: self.control.clone(),
+            expires_at: self.expires_at,
+        })
+    }
+
+    fn from_rust(py: Python, signal_with_start: SignalWithStartTemporal) -> PyResult<Self> {
+        Ok(Self {
+            namespace: signal_with_start.namespace,
+            task_queue: signal_with_start.task_queue,
+            workflow_id: signal_with_start.workflow_id,
+            workflow_type: signal_with_start.workflow_type,
+            args: py_values(py, signal_with_start.args)?,
+            signal_name: signal_with_start.signal_name,
+            input: py_values(py, signal_with_start.input)?,
+            identity: signal_with_start.identity,
+            request_id: signal_with_start.request_id,
+            control: signal_with_start.control,
+            expires_at: signal_with_start.expires_at,
+        })
+    }
+}
+
+#[pyclass(module = "temporal_json", name = "CancelTemporal")]
+/// Request that a running workflow cancels itself
+pub struct PyCancelTemporal {
+    #[pyo3(get, set)]
+    pub namespace: String,
+    #[pyo3(get, set)]
+    pub task_queue: String,
+    #[pyo3(get, set)]
+    pub workflow_id: String,
+    #[pyo3(get, set)]
+    pub run_id: Option<String>,
+    /// why the workflow was cancelled, recorded in its history
+    #[pyo3(get, set)]
+    pub reason: Option<String>,
+    #[pyo3(get, set)]
+    pub identity: Option<String>,
+    #[pyo3(get, set)]
+    pub request_id: Option<String>,
+    /// unix timestamp (seconds) after which the encoded string no longer decodes
+    #[pyo3(get, set)]
+    pub expires_at: Option<u64>,
+}
+
+#[pymethods]
+impl PyCancelTemporal {
+    #[new]
+    #[pyo3(signature = (namespace, task_queue, workflow_id, run_id=None, reason=None, identity=None, request_id=None, expires_at=None))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        namespace: String,
+        task_queue: String,
+        workflow_id: String,
+        run_id: Option<String>,
+        reason: Option<String>,
+        identity: Option<String>,
+        request_id: Option<String>,
+        expires_at: Option<u64>,
+    ) -> Self {
+        Self {
+            namespace,
+            task_queue,
+            workflow_id,
+            run_id,
+            reason,
+            identity,
+            request_id,
+            expires_at,
+        }
+    }
+
+    fn __repr__(&self, py: Python) -> PyResult<String> {
+        dataclass_repr(py, "CancelTemporal", &self.to_rust())
+    }
+
+    fn __richcmp__(&self, other: PyRef<Self>, op: CompareOp, py: Python) -> PyObject {
+        compare(py, op, self.to_rust() == other.to_rust())
+    }
+}
+
+impl PyCancelTemporal {
+    fn to_rust(&self) -> CancelTemporal {
+        CancelTemporal {
+            namespace: self.namespace.clone(),
+            task_queue: self.task_queue.clone(),
+            workflow_id: self.workflow_id.clone(),
+            run_id: self.run_id.clone(),
+            reason: self.reason.clone(),
+            identity: self.identity.clone(),
+            request_id: self.request_id.clone(),
+            expires_at: self.expires_at,
+        }
+    }
+
+    fn from_rust(cancel: CancelTemporal) -> Self {
+        Self {
+            namespace: cancel.namespace,
+            task_queue: cancel.task_queue,
+            workflow_id: cancel.workflow_id,
+            run_id: cancel.run_id,
+            reason: cancel.reason,
+            identity: cancel.identity,
+            request_id: cancel.request_id,
+            expires_at: cancel.expires_at,
+        }
+    }
+}
+
+#[pyclass(module = "temporal_json", name = "TerminateTemporal")]
+/// Stop a running workflow immediately, without running its cleanup
+pub struct PyTerminateTemporal {
+    #[pyo3(get, set)]
+    pub namespace: String,
+    #[pyo3(get, set)]
+    pub task_queue: String,
+    #[pyo3(get, set)]
+    pub workflow_id: String,
+    #[pyo3(get, set)]
+    pub run_id: Option<String>,
+    /// why the workflow was terminated, recorded in its history
+    #[pyo3(get, set)]
+    pub reason: Option<String>,
+    /// list of JSON-serializable values, recorded in its history along with the reason
+    #[pyo3(get, set)]
+    pub details: Option<PyObject>,
+    #[pyo3(get, set)]
+    pub identity: Option<String>,
+    /// unix timestamp (seconds) after which the encoded string no longer decodes
+    #[pyo3(get, set)]
+    pub expires_at: Option<u64>,
+}
+
+#[pymethods]
+impl PyTerminateTemporal {
+    #[new]
+    #[pyo3(signature = (namespace, task_queue, workflow_id, run_id=None, reason=None, details=None, identity=None, expires_at=None))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        namespace: String,
+        task_queue: String,
+        workflow_id: String,
+        run_id: Option<String>,
+        reason: Option<String>,
+        details: Option<PyObject>,
+        identity: Option<String>,
+        expires_at: Option<u64>,
+    ) -> Self {
+        Self {
+            namespace,
+            task_queue,
+            workflow_id,
+            run_id,
+            reason,
+            details,
+            identity,
+            expires_at,
+        }
+    }
+
+    fn __repr__(&self, py: Python) -> PyResult<String> {
+        dataclass_repr(py, "TerminateTemporal", &self.to_rust(py)?)
+    }
+
+    fn __richcmp__(&self, other: PyRef<Self>, op: CompareOp, py: Python) -> PyResult<PyObject> {
+        Ok(compare(py, op, self.to_rust(py)? == other.to_rust(py)?))
+    }
+}
+
+impl PyTerminateTemporal {
+    fn to_rust(&self, py: Python) -> PyResult<TerminateTemporal> {
+        Ok(TerminateTemporal {
+            namespace: self.namespace.clone(),
+            task_queue: self.task_queue.clone(),
+            workflow_id: self.workflow_id.clone(),
+            run_id: self.run_id.clone(),
+            reason: self.reason.clone(),
+            details: json_values(py, &self.details)?,
+            identity: self.identity.clone(),
+            expires_at: self.expires_at,
+        })
+    }
+
+    fn from_rust(py: Python, terminate: TerminateTemporal) -> PyResult<Self> {
+        Ok(Self {
+            namespace: terminate.namespace,
+            task_queue: terminate.task_queue,
+            workflow_id: terminate.workflow_id,
+            run_id: terminate.run_id,
+            reason: terminate.reason,
+            details: py_values(py, terminate.details)?,
+            identity: terminate.identity,
+            expires_at: terminate.expires_at,
+        })
+    }
+}
+
+#[pyclass(module = "temporal_json", name = "UpdateTemporal")]
+/// Send an update to a running workflow and wait for what its handler returns
+pub struct PyUpdateTemporal {
+    #[pyo3(get, set)]
+    pub namespace: String,
+    #[pyo3(get, set)]
+    pub task_queue: String,
+    #[pyo3(get, set)]
+    pub workflow_id: String,
+    #[pyo3(get, set)]
+    pub run_id: Option<String>,
+    /// the update handler's name, as defined in the workflow
+    #[pyo3(get, set)]
+    pub update_name: String,
+    /// list of JSON-serializable values
+    #[pyo3(get, set)]
+    pub args: Option<PyObject>,
+    /// how far the update gets before Temporal responds, `"Accepted"` or `"Completed"` (the default)
+    #[pyo3(get, set)]
+    pub wait_stage: Option<String>,
+    #[pyo3(get, set)]
+    pub identity: Option<String>,
+    /// dedupes retried updates, a new UUID when unset
+    #[pyo3(get, set)]
+    pub update_id: Option<String>,
+    /// unix timestamp (seconds) after which the encoded string no longer decodes
+    #[pyo3(get, set)]
+    pub expires_at: Option<u64>,
+}
+
+#[pymethods]
+impl PyUpdateTemporal {
+    #[new]
+    #[pyo3(signature = (namespace, task_queue, workflow_id, update_name, run_id=None, args=None, wait_stage=None, identity=None, update_id=None, expires_at=None))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        namespace: String,
+        task_queue: String,
+        workflow_id: String,
+        update_name: String,
+        run_id: Option<String>,
+        args: Option<PyObject>,
+        wait_stage: Option<String>,
+        identity: Option<String>,
+        update_id: Option<String>,
+        expires_at: Option<u64>,
+    ) -> Self {
+        Self {
+            namespace,
+            task_queue,
+            workflow_id,
+            run_id,
+            update_name,
+            args,
+            wait_stage,
+            identity,
+            update_id,
+            expires_at,
+        }
+    }
+
+    fn __repr__(&self, py: Python) -> PyResult<String> {
+        dataclass_repr(py, "UpdateTemporal", &self.to_rust(py)?)
+    }
+
+    fn __richcmp__(&self, other: PyRef<Self>, op: CompareOp, py: Python) -> PyResult<PyObject> {
+        Ok(compare(py, op, self.to_rust(py)? == other.to_rust(py)?))
+    }
+}
+
+impl PyUpdateTemporal {
+    fn to_rust(&self, py: Python) -> PyResult<UpdateTemporal> {
+        let wait_stage = self
+            .wait_stage
+            .as_ref()
+            .map(|wait_stage| {
+                wait_stage.parse::<UpdateWaitStage>().map_err(|_| {
+                    to_value_error(format!(
+                        "unknown wait_stage `{}`, expected Accepted or Completed",
+                        wait_stage
+                    ))
+                })
+            })
+            .transpose()?;
+
+        Ok(UpdateTemporal {
+            namespace: self.namespace.clone(),
+            task_queue: self.task_queue.clone(),
+            workflow_id: self.workflow_id.clone(),
+            run_id: self.run_id.clone(),
+            update_name: self.update_name.clone(),
+            args: json_values(py, &self.args)?,
+            wait_stage,
+            identity: self.identity.clone(),
+            update_id: self.update_id.clone(),
+            expires_at: self.expires_at,
+        })
+    }
+
+    fn from_rust(py: Python, update: UpdateTemporal) -> PyResult<Self> {
+        Ok(Self {
+            namespace: update.namespace,
+            task_queue: update.task_queue,
+            workflow_id: update.workflow_id,
+            run_id: update.run_id,
+            update_name: update.update_name,
+            args: py_values(py, update.args)?,
+            wait_stage: update.wait_stage.map(|wait_stage| wait_stage.to_string()),
+            identity: update.identity,
+            update_id: update.update_id,
+            expires_at: update.expires_at,
+        })
+    }
+}
+
+#[pyclass(module = "temporal_json", name = "CompleteActivityTemporal")]
+/// Finish an activity that is completed asynchronously, by its task token or by workflow & activity id
+pub struct PyCompleteActivityTemporal {
+    #[pyo3(get, set)]
+    pub namespace: String,
+    #[pyo3(get, set)]
+    pub task_queue: String,
+    /// the activity's task token as base64url, without padding
+    #[pyo3(get, set)]
+    pub task_token: Option<String>,
+    #[pyo3(get, set)]
+    pub workflow_id: Option<String>,
+    #[pyo3(get, set)]
+    pub run_id: Option<String>,
+    #[pyo3(get, set)]
+    pub activity_id: Option<String>,
+    /// `"Completed"` (the default), `"Failed"` or `"Canceled"`
+    #[pyo3(get, set)]
+    pub outcome: Option<String>,
+    /// list of JSON-serializable values, the activity's result or the details of its failure or cancellation
+    #[pyo3(get, set)]
+    pub result: Option<PyObject>,
+    /// the failure message, when the activity is failed
+    #[pyo3(get, set)]
+    pub reason: Option<String>,
+    #[pyo3(get, set)]
+    pub identity: Option<String>,
+    /// unix timestamp (seconds) after which the encoded string no longer decodes
+    #[pyo3(get, set)]
+    pub expires_at: Option<u64>,
+}
+
+#[pymethods]
+impl PyCompleteActivityTemporal {
+    #[new]
+    #[pyo3(signature = (namespace, task_queue, task_token=None, workflow_id=None, run_id=None, activity_id=None, outcome=None, result=None, reason=None, identity=None, expires_at=None))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        namespace: String,
+        task_queue: String,
+        task_token: Option<String>,
+        workflow_id: Option<String>,
+        run_id: Option<String>,
+        activity_id: Option<String>,
+        outcome: Option<String>,
+        result: Option<PyObject>,
+        reason: Option<String>,
+        identity: Option<String>,
+        expires_at: Option<u64>,
+    ) -> Self {
+        Self {
+            namespace,
+            task_queue,
+            task_token,
+            workflow_id,
+            run_id,
+            activity_id,
+            outcome,
+            result,
+            reason,
+            identity,
+            expires_at,
+        }
+    }
+
+    fn __repr__(&self, py: Python) -> PyResult<String> {
+        dataclass_repr(py, "CompleteActivityTemporal", &self.to_rust(py)?)
+    }
+
+    fn __richcmp__(&self, other: PyRef<Self>, op: CompareOp, py: Python) -> PyResult<PyObject> {
+        Ok(compare(py, op, self.to_rust(py)? == other.to_rust(py)?))
+    }
+}
+
+impl PyCompleteActivityTemporal {
+    fn to_rust(&self, py: Python) -> PyResult<CompleteActivityTemporal> {
+        let outcome = self
+            .outcome
+            .as_ref()
+            .map(|outcome| {
+                outcome.parse::<ActivityOutcome>().map_err(|_| {
+                    to_value_error(format!(
+                        "unknown outcome `{}`, expected Completed, Failed or Canceled",
+                        outcome
+                    ))
+                })
+            })
+            .transpose()?;
+
+        Ok(CompleteActivityTemporal {
+            namespace: self.namespace.clone(),
+            task_queue: self.task_queue.clone(),
+            task_token: self.task_token.clone(),
+            workflow_id: self.workflow_id.clone(),
+            run_id: self.run_id.clone(),
+            activity_id: self.activity_id.clone(),
+            outcome,
+            result: json_values(py, &self.result)?,
+            reason: self.reason.clone(),
+            identity: self.identity.clone(),
+            expires_at: self.expires_at,
+        })
+    }
+
+    fn from_rust(py: Python, complete: CompleteActivityTemporal) -> PyResult<Self> {
+        Ok(Self {
+            namespace: complete.namespace,
+            task_queue: complete.task_queue,
+            task_token: complete.task_token,
+            workflow_id: complete.workflow_id,
+            run_id: complete.run_id,
+            activity_id: complete.activity_id,
+            outcome: complete.outcome.map(|outcome| outcome.to_string()),
+            result: py_values(py, complete.result)?,
+            reason: complete.reason,
+            identity: complete.identity,
+            expires_at: complete.expires_at,
+        })
+    }
+}
+
+/// Any of the interaction classes, for `encode`
+#[derive(FromPyObject)]
+enum Interaction<'a> {
+    Execute(PyRef<'a, PyExecuteTemporalWorkflow>),
+    Signal(PyRef<'a, PySignalTemporal>),
+    Query(PyRef<'a, PyQueryTemporal>),
+    SignalWithStart(PyRef<'a, PySignalWithStartTemporal>),
+    Cancel(PyRef<'a, PyCancelTemporal>),
+    Terminate(PyRef<'a, PyTerminateTemporal>),
+    Update(PyRef<'a, PyUpdateTemporal>),
+    CompleteActivity(PyRef<'a, PyCompleteActivityTemporal>),
+}
+
+impl Interaction<'_> {
+    fn to_rust(&self, py: Python) -> PyResult<TemporalInteraction> {
+        Ok(match self {
+            Interaction::Execute(execute) => TemporalInteraction::Execute(execute.to_rust(py)?),
+            Interaction::Signal(signal) => TemporalInteraction::Signal(signal.to_rust(py)?),
+            Interaction::Query(query) => TemporalInteraction::Query(query.to_rust(py)?),
+            Interaction::SignalWithStart(signal_with_start) => {
+                TemporalInteraction::SignalWithStart(signal_with_start.to_rust(py)?)
+            }
+            Interaction::Cancel(cancel) => TemporalInteraction::Cancel(cancel.to_rust()),
+            Interaction::Terminate(terminate) => {
+                TemporalInteraction::Terminate(terminate.to_rust(py)?)
+            }
+            Interaction::Update(update) => TemporalInteraction::Update(update.to_rust(py)?),
+            Interaction::CompleteActivity(complete) => {
+                TemporalInteraction::CompleteActivity(complete.to_rust(py)?)
+            }
+        })
+    }
+}
+
+/// `ClassName(field=repr(value), ...)` like a dataclass, in the order of the Rust struct's fields
+fn dataclass_repr(py: Python, class_name: &str, value: &impl Serialize) -> PyResult<String> {
+    let fields = pythonize(py, value)?;
+    let fields: &PyDict = fields.as_ref(py).downcast()?;
+
+    let fields = fields
+        .iter()
+        .map(|(name, value)| Ok(format!("{}={}", name, value.repr()?)))
+        .collect::<PyResult<Vec<_>>>()?;
+
+    Ok(format!("{}({})", class_name, fields.join(", ")))
+}
+
+fn compare(py: Python, op: CompareOp, is_equal: bool) -> PyObject {
+    match op {
+        CompareOp::Eq => is_equal.into_py(py),
+        CompareOp::Ne => (!is_equal).into_py(py),
+        _ => py.NotImplemented(),
+    }
+}
+
+/// a python list of JSON-serializable values, as JSON
+fn json_values(py: Python, values: &Option<PyObject>) -> PyResult<Option<Vec<Value>>> {
+    values
+        .as_ref()
+        .map(|values| depythonize(values.as_ref(py)))
+        .transpose()
+        .map_err(PyErr::from)
+}
+
+fn py_values(py: Python, values: Option<Vec<Value>>) -> PyResult<Option<PyObject>> {
+    values
+        .map(|values| pythonize(py, &values))
+        .transpose()
+        .map_err(PyErr::from)
+}
+
+/// a python dict of start options, `ValueError` when it has an unknown policy or a wrong type
+fn start_options(py: Python, options: &Option<PyObject>) -> PyResult<Option<WorkflowStartOptions>> {
+    options
+        .as_ref()
+        .map(|options| {
+            depythonize(options.as_ref(py))
+                .map_err(|err| to_value_error(format!("invalid options, {}", err)))
+        })
+        .transpose()
+}
+
+fn to_value_error(err: impl std::fmt::Display) -> PyErr {
+    PyValueError::new_err(err.to_string())
+}
+
+/// `DecodeError` with the variant's `code` when the encoded string is invalid, `ValueError` otherwise
+fn to_decode_error(py: Python, err: anyhow::Error) -> PyErr {
+    let message = format!("failed to decode from encoded string, {}", err);
+    match err.downcast_ref::<temporal_json::DecodeError>() {
+        Some(decode_error) => {
+            let py_err = DecodeError::new_err(message);
+            match py_err.value(py).setattr("code", decode_error.code()) {
+                Ok(()) => py_err,
+                Err(setattr_err) => setattr_err,
+            }
+        }
+        None => PyValueError::new_err(message),
+    }
+}
+
+// Functions //////////////////////////////////////////////////////////////////
+
+#[pyfunction]
+/// Convert workflow metadata into an encoded string for embedding into a webhook event, using the default encoding.
+///
+/// Example: use this string as the `callback_id` for a slack interaction, and when
+/// the Temporal API Gateway receives the event it can decode the string and route the event to your workflow.
+fn encode_signal_no_args_default(signal: PyRef<TemporalSignalWithoutInput>) -> PyResult<String> {
+    Encoder::default()
+        .encode(TemporalInteraction::Signal(signal.to_signal()))
+        .map_err(|err| to_value_error(format!("failed to encode, {}", err)))
+}
+
+#[pyfunction]
+/// Convert workflow metadata into an encoded string for embedding into a webhook event, with a specific encoding version.
+fn encode_signal_no_args_with_version(
+    encoder_version: PyRef<Encoder>,
+    signal: PyRef<TemporalSignalWithoutInput>,
+) -> PyResult<String> {
+    encoder_version
+        .encode(TemporalInteraction::Signal(signal.to_signal()))
+        .map_err(|err| to_value_error(format!("failed to encode, {}", err)))
+}
+
+#[pyfunction]
+/// Convert workflow metadata into an encoded string, and fail if it's too long for the profile's field.
+///
+/// Example: use `Profile.DiscordCustomId` to find out before Discord rejects a 101 char `custom_id`.
+fn encode_signal_no_args_checked(
+    encoder_version: PyRef<Encoder>,
+    signal: PyRef<TemporalSignalWithoutInput>,
+    profile: Profile,
+) -> PyResult<String> {
+    encoder_version
+        .encode_checked(TemporalInteraction::Signal(signal.to_signal()), profile)
+        .map_err(|err| to_value_error(format!("failed to encode, {}", err)))
+}
+
+#[pyfunction]
+/// Encode a TemporalInteraction provided as a JSON string.
+fn encode_default_from_json_string(json_string: &str) -> PyResult<String> {
+    Encoder::encode_default_from_json_string(json_string)
+        .map_err(|err| to_value_error(format!("failed to encode from json string, {}", err)))
+}
+
+#[pyfunction]
+#[pyo3(signature = (json_string, profile, encoder_version=None))]
+/// Encode a TemporalInteraction provided as a JSON string, and fail if it's too long for the profile's field.
+fn encode_checked_from_json_string(
+    json_string: &str,
+    profile: Profile,
+    encoder_version: Option<PyRef<Encoder>>,
+) -> PyResult<String> {
+    serde_json::from_str(json_string)
+        .map_err(anyhow::Error::from)
+        .and_then(|interaction| match encoder_version {
+            Some(encoder_version) => encoder_version.encode_checked(interaction, profile),
+            None => Encoder::default().encode_checked(interaction, profile),
+        })
+        .map_err(|err| to_value_error(format!("failed to encode from json string, {}", err)))
+}
+
+#[pyfunction]
+/// Decode an encoded string into a JSON string representing a TemporalInteraction.
+///
+/// Raises `DecodeError` with a `code` like `MISSING_KEY` or `EXPIRED` when the encoded string is invalid.
+fn decode_to_json_string(py: Python, encoded_string: &str) -> PyResult<String> {
+    Encoder::decode_to_json_string(encoded_string).map_err(|err| to_decode_error(py, err))
+}
+
+#[pyfunction]
+#[pyo3(signature = (interaction, encoder_version=None))]
+/// Encode any of the interaction classes, like `SignalTemporal`, using the default encoding unless `encoder_version` is given.
+fn encode(
+    py: Python,
+    interaction: Interaction,
+    encoder_version: Option<PyRef<Encoder>>,
+) -> PyResult<String> {
+    let interaction = interaction.to_rust(py)?;
+    match encoder_version {
+        Some(encoder_version) => encoder_version.encode(interaction),
+        None => Encoder::default().encode(interaction),
+    }
+    .map_err(|err| to_value_error(format!("failed to encode, {}", err)))
+}
+
+#[pyfunction]
+/// Decode an encoded string into the interaction class of its type, like `SignalTemporal`.
+///
+/// Raises `DecodeError` with a `code` like `MISSING_KEY` or `EXPIRED` when the encoded string is invalid.
+fn decode(py: Python, encoded_string: &str) -> PyResult<PyObject> {
+    let interaction = Encoder::decode(encoded_string)
+        .map_err(|err| to_decode_error(py, anyhow::Error::from(err)))?;
+
+    Ok(match interaction {
+        TemporalInteraction::Execute(execute) => {
+            PyExecuteTemporalWorkflow::from_rust(py, execute)?.into_py(py)
+        }
+        TemporalInteraction::Signal(signal) => PySignalTemporal::from_rust(py, signal)?.into_py(py),
+        TemporalInteraction::Query(query) => PyQueryTemporal::from_rust(py, query)?.into_py(py),
+        TemporalInteraction::SignalWithStart(signal_with_start) => {
+            PySignalWithStartTemporal::from_rust(py, signal_with_start)?.into_py(py)
+        }
+        TemporalInteraction::Cancel(cancel) => PyCancelTemporal::from_rust(cancel).into_py(py),
+        TemporalInteraction::Terminate(terminate) => {
+            PyTerminateTemporal::from_rust(py, terminate)?.into_py(py)
+        }
+        TemporalInteraction::Update(update) => PyUpdateTemporal::from_rust(py, update)?.into_py(py),
+        TemporalInteraction::CompleteActivity(complete) => {
+            PyCompleteActivityTemporal::from_rust(py, complete)?.into_py(py)
+        }
+    })
+}
+
+#[pyfunction]
+#[pyo3(signature = (key_list, require_signature=None))]
+/// Set the keys used to sign encoded strings with `Encoder.S`, as a comma separated list of `key_id:secret` pairs.
+/// The first key is used for signing. Must be called before the first encode/decode.
+///
+/// If not called, keys are read from the `TEMPORAL_JSON_SIGNING_KEYS` env var.
+fn set_signing_keys(key_list: &str, require_signature: Option<bool>) -> PyResult<()> {
+    SigningKeys::from_key_list(key_list)
+        .and_then(|keys| {
+            temporal_json::set_signing_keys(
+                keys.with_require_signature(require_signature.unwrap_or_default()),
+            )
+        })
+        .map_err(|err| to_value_error(format!("failed to set signing keys, {}", err)))
+}
+
+#[pymodule]
+#[pyo3(name = "temporal_json")]
+fn temporal_json_python(py: Python, m: &PyModule) -> PyResult<()> {
+    m.add_class::<Encoder>()?;
+    m.add_class::<Profile>()?;
+    m.add_class::<TemporalSignalWithoutInput>()?;
+    m.add_class::<PyExecuteTemporalWorkflow>()?;
+    m.add_class::<PySignalTemporal>()?;
+    m.add_class::<PyQueryTemporal>()?;
+    m.add_class::<PySignalWithStartTemporal>()?;
+    m.add_class::<PyCancelTemporal>()?;
+    m.add_class::<PyTerminateTemporal>()?;
+    m.add_class::<PyUpdateTemporal>()?;
+    m.add_class::<PyCompleteActivityTemporal>()?;
+    m.add("DecodeError", py.get_type::<DecodeError>())?;
+
+    m.add_function(wrap_pyfunction!(encode_signal_no_args_default, m)?)?;
+    m.add_function(wrap_pyfunction!(encode_signal_no_args_with_version, m)?)?;
+    m.add_function(wrap_pyfunction!(encode_signal_no_args_checked, m)?)?;
+    m.add_function(wrap_pyfunction!(encode_default_from_json_string, m)?)?;
+    m.add_function(wrap_pyfunction!(encode_checked_from_json_string, m)?)?;
+    m.add_function(wrap_pyfunction!(decode_to_json_string, m)?)?;
+    m.add_function(wrap_pyfunction!(encode, m)?)?;
+    m.add_function(wrap_pyfunction!(decode, m)?)?;
+    m.add_function(wrap_pyfunction!(set_signing_keys, m)?)?;
+    Ok(())
+}
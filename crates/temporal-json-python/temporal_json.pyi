@@ -0,0 +1,300 @@
+# type hints for the native `temporal_json` extension, keep in sync with src/lib.rs
+from typing import Any, Dict, List, Literal, Optional, TypedDict, Union
+
+class Encoder:
+    """Version of the encoded string format"""
+
+    A: "Encoder"
+    """plain text key:value pairs"""
+    S: "Encoder"
+    """key:value pairs of `A`, plus a key id & HMAC of the temporal section so it can't be tampered with"""
+    X: "Encoder"
+    """key id & base64url ChaCha20-Poly1305 ciphertext of the `A` key:value pairs, so they can't be read or tampered with"""
+    B: "Encoder"
+    """base64url of the `A` key:value pairs packed as length-prefixed binary, with UUIDs as 16 raw bytes"""
+
+class Profile:
+    """Platform field an encoded string is sent in, and its length limit.
+
+    There's no Teams profile: Teams card action data is only limited by the whole message's size
+    """
+
+    SlackCallbackId: "Profile"
+    SlackActionId: "Profile"
+    SlackBlockId: "Profile"
+    DiscordCustomId: "Profile"
+    TelegramCallbackData: "Profile"
+
+class DecodeError(ValueError):
+    """An encoded string couldn't be decoded. Its `code` says why, like `MISSING_KEY` or `EXPIRED`."""
+
+    code: str
+
+class TemporalSignalWithoutInput:
+    """A Signal without the Input Payload, the webhook event is sent as the Signal's Input."""
+
+    namespace: str
+    task_queue: str
+    workflow_id: Optional[str]
+    run_id: Optional[str]
+    signal_name: str
+    expires_at: Optional[int]
+    def __init__(
+        self,
+        namespace: str,
+        task_queue: str,
+        signal_name: str,
+        workflow_id: Optional[str] = None,
+        run_id: Optional[str] = None,
+        expires_at: Optional[int] = None,
+    ) -> None: ...
+
+class WorkflowRetryPolicy(TypedDict, total=False):
+    """How a failed workflow is retried"""
+
+    initial_interval_secs: Optional[int]
+    backoff_coefficient: Optional[float]
+    maximum_interval_secs: Optional[int]
+    maximum_attempts: Optional[int]
+    non_retryable_error_types: Optional[List[str]]
+
+class WorkflowStartOptions(TypedDict, total=False):
+    """How a workflow is started, Temporal's defaults are used for anything unset"""
+
+    execution_timeout_secs: Optional[int]
+    run_timeout_secs: Optional[int]
+    task_timeout_secs: Optional[int]
+    id_reuse_policy: Optional[
+        Literal["AllowDuplicate", "AllowDuplicateFailedOnly", "RejectDuplicate", "TerminateIfRunning"]
+    ]
+    retry_policy: Optional[WorkflowRetryPolicy]
+    cron_schedule: Optional[str]
+    memo: Optional[Dict[str, Any]]
+    search_attributes: Optional[Dict[str, Any]]
+
+class ExecuteTemporalWorkflow:
+    """Start a workflow"""
+
+    namespace: str
+    task_queue: str
+    workflow_id: str
+    workflow_type: str
+    args: Optional[List[Any]]
+    options: Optional[WorkflowStartOptions]
+    expires_at: Optional[int]
+    def __init__(
+        self,
+        namespace: str,
+        task_queue: str,
+        workflow_id: str,
+        workflow_type: str,
+        args: Optional[List[Any]] = None,
+        options: Optional[WorkflowStartOptions] = None,
+        expires_at: Optional[int] = None,
+    ) -> None: ...
+
+class SignalTemporal:
+    """Signal a running workflow"""
+
+    namespace: str
+    task_queue: str
+    workflow_id: Optional[str]
+    run_id: Optional[str]
+    signal_name: str
+    input: Optional[List[Any]]
+    identity: Optional[str]
+    request_id: Optional[str]
+    control: Optional[str]
+    expires_at: Optional[int]
+    def __init__(
+        self,
+        namespace: str,
+        task_queue: str,
+        signal_name: str,
+        workflow_id: Optional[str] = None,
+        run_id: Optional[str] = None,
+        input: Optional[List[Any]] = None,
+        identity: Optional[str] = None,
+        request_id: Optional[str] = None,
+        control: Optional[str] = None,
+        expires_at: Optional[int] = None,
+    ) -> None: ...
+
+class QueryTemporal:
+    """Query a running workflow"""
+
+    namespace: str
+    task_queue: str
+    workflow_id: Optional[str]
+    run_id: Optional[str]
+    query_type: str
+    query_args: Optional[List[Any]]
+    expires_at: Optional[int]
+    def __init__(
+        self,
+        namespace: str,
+        task_queue: str,
+        query_type: str,
+        workflow_id: Optional[str] = None,
+        run_id: Optional[str] = None,
+        query_args: Optional[List[Any]] = None,
+        expires_at: Optional[int] = None,
+    ) -> None: ...
+
+class SignalWithStartTemporal:
+    """Signal a workflow, starting it first if it isn't running"""
+
+    namespace: str
+    task_queue: str
+    workflow_id: str
+    workflow_type: str
+    args: Optional[List[Any]]
+    signal_name: str
+    input: Optional[List[Any]]
+    identity: Optional[str]
+    request_id: Optional[str]
+    control: Optional[str]
+    expires_at: Optional[int]
+    def __init__(
+        self,
+        namespace: str,
+        task_queue: str,
+        workflow_id: str,
+        workflow_type: str,
+        signal_name: str,
+        args: Optional[List[Any]] = None,
+        input: Optional[List[Any]] = None,
+        identity: Optional[str] = None,
+        request_id: Optional[str] = None,
+        control: Optional[str] = None,
+        expires_at: Optional[int] = None,
+    ) -> None: ...
+
+class CancelTemporal:
+    """Request that a running workflow cancels itself"""
+
+    namespace: str
+    task_queue: str
+    workflow_id: str
+    run_id: Optional[str]
+    reason: Optional[str]
+    identity: Optional[str]
+    request_id: Optional[str]
+    expires_at: Optional[int]
+    def __init__(
+        self,
+        namespace: str,
+        task_queue: str,
+        workflow_id: str,
+        run_id: Optional[str] = None,
+        reason: Optional[str] = None,
+        identity: Optional[str] = None,
+        request_id: Optional[str] = None,
+        expires_at: Optional[int] = None,
+    ) -> None: ...
+
+class TerminateTemporal:
+    """Stop a running workflow immediately, without running its cleanup"""
+
+    namespace: str
+    task_queue: str
+    workflow_id: str
+    run_id: Optional[str]
+    reason: Optional[str]
+    details: Optional[List[Any]]
+    identity: Optional[str]
+    expires_at: Optional[int]
+    def __init__(
+        self,
+        namespace: str,
+        task_queue: str,
+        workflow_id: str,
+        run_id: Optional[str] = None,
+        reason: Optional[str] = None,
+        details: Optional[List[Any]] = None,
+        identity: Optional[str] = None,
+        expires_at: Optional[int] = None,
+    ) -> None: ...
+
+class UpdateTemporal:
+    """Send an update to a running workflow and wait for what its handler returns"""
+
+    namespace: str
+    task_queue: str
+    workflow_id: str
+    run_id: Optional[str]
+    update_name: str
+    args: Optional[List[Any]]
+    wait_stage: Optional[Literal["Accepted", "Completed"]]
+    identity: Optional[str]
+    update_id: Optional[str]
+    expires_at: Optional[int]
+    def __init__(
+        self,
+        namespace: str,
+        task_queue: str,
+        workflow_id: str,
+        update_name: str,
+        run_id: Optional[str] = None,
+        args: Optional[List[Any]] = None,
+        wait_stage: Optional[Literal["Accepted", "Completed"]] = None,
+        identity: Optional[str] = None,
+        update_id: Optional[str] = None,
+        expires_at: Optional[int] = None,
+    ) -> None: ...
+
+class CompleteActivityTemporal:
+    """Finish an activity that is completed asynchronously, by its task token or by workflow & activity id"""
+
+    namespace: str
+    task_queue: str
+    task_token: Optional[str]
+    workflow_id: Optional[str]
+    run_id: Optional[str]
+    activity_id: Optional[str]
+    outcome: Optional[Literal["Completed", "Failed", "Canceled"]]
+    result: Optional[List[Any]]
+    reason: Optional[str]
+    identity: Optional[str]
+    expires_at: Optional[int]
+    def __init__(
+        self,
+        namespace: str,
+        task_queue: str,
+        task_token: Optional[str] = None,
+        workflow_id: Optional[str] = None,
+        run_id: Optional[str] = None,
+        activity_id: Optional[str] = None,
+        outcome: Optional[Literal["Completed", "Failed", "Canceled"]] = None,
+        result: Optional[List[Any]] = None,
+        reason: Optional[str] = None,
+        identity: Optional[str] = None,
+        expires_at: Optional[int] = None,
+    ) -> None: ...
+
+TemporalInteraction = Union[
+    ExecuteTemporalWorkflow,
+    SignalTemporal,
+    QueryTemporal,
+    SignalWithStartTemporal,
+    CancelTemporal,
+    TerminateTemporal,
+    UpdateTemporal,
+    CompleteActivityTemporal,
+]
+
+def encode_signal_no_args_default(signal: TemporalSignalWithoutInput) -> str: ...
+def encode_signal_no_args_with_version(
+    encoder_version: Encoder, signal: TemporalSignalWithoutInput
+) -> str: ...
+def encode_signal_no_args_checked(
+    encoder_version: Encoder, signal: TemporalSignalWithoutInput, profile: Profile
+) -> str: ...
+def encode_default_from_json_string(json_string: str) -> str: ...
+def encode_checked_from_json_string(
+    json_string: str, profile: Profile, encoder_version: Optional[Encoder] = None
+) -> str: ...
+def decode_to_json_string(encoded_string: str) -> str: ...
+def encode(interaction: TemporalInteraction, encoder_version: Optional[Encoder] = None) -> str: ...
+def decode(encoded_string: str) -> TemporalInteraction: ...
+def set_signing_keys(key_list: str, require_signature: Optional[bool] = None) -> None: ...
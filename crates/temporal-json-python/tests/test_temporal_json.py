@@ -0,0 +1,283 @@
+import json
+import time
+
+import pytest
+
+from temporal_json import (
+    CancelTemporal,
+    CompleteActivityTemporal,
+    DecodeError,
+    Encoder,
+    ExecuteTemporalWorkflow,
+    Profile,
+    QueryTemporal,
+    SignalTemporal,
+    SignalWithStartTemporal,
+    TemporalSignalWithoutInput,
+    TerminateTemporal,
+    UpdateTemporal,
+    decode,
+    decode_to_json_string,
+    encode,
+    encode_checked_from_json_string,
+    encode_default_from_json_string,
+    encode_signal_no_args_checked,
+    encode_signal_no_args_default,
+    encode_signal_no_args_with_version,
+    set_signing_keys,
+)
+
+# signing keys can only be set once, before anything is encoded or decoded
+set_signing_keys("1:test-secret-that-is-not-used-anywhere-else")
+
+
+def build_temporal_interaction_exec_wf():
+    return {
+        "type": "Execute",
+        "namespace": "test-namespace",
+        "task_queue": "template-taskqueue",
+        "workflow_id": "1",
+        "workflow_type": "GreetingWorkflow",
+        "args": [{"name": "saxon", "team": "noxasaxon"}],
+    }
+
+
+def build_temporal_interaction_signal():
+    return {
+        "type": "Signal",
+        "namespace": "test-namespace",
+        "task_queue": "test-task-queue-rs",
+        "workflow_id": "some-super-long-uuid-string",
+        "run_id": "some-equally-long-uuid-string",
+        "signal_name": "signal_name_thats_defined_in_workflow",
+    }
+
+
+def build_temporal_interaction_query():
+    return {
+        "type": "Query",
+        "namespace": "test-namespace",
+        "task_queue": "test-task-queue-rs",
+        "workflow_id": "some-super-long-uuid-string",
+        "run_id": "some-equally-long-uuid-string",
+        "query_type": "query_type_thats_defined_in_workflow",
+        "query_args": [{"arg1": "value1"}, 2],
+    }
+
+
+def without_type(temporal_interaction):
+    """keyword args of the typed class"""
+    return {key: value for key, value in temporal_interaction.items() if key != "type"}
+
+
+def build_signal_without_input(**kwargs):
+    return TemporalSignalWithoutInput(
+        namespace="test-namespace",
+        task_queue="test-task-queue-rs",
+        workflow_id="some-super-long-uuid-string",
+        signal_name="signal_name_thats_defined_in_workflow",
+        **kwargs,
+    )
+
+
+def test_encode_default_from_json_string():
+    as_string = json.dumps(build_temporal_interaction_signal())
+
+    assert encode_default_from_json_string(as_string).startswith("A~")
+
+
+def test_decode_from_json_string():
+    as_string = json.dumps(build_temporal_interaction_signal())
+
+    encoded_string = encode_default_from_json_string(as_string)
+    decoded_json_string = decode_to_json_string(encoded_string)
+
+    assert encode_default_from_json_string(decoded_json_string) == encoded_string
+
+
+def test_all_event_types():
+    for temporal_event_json in [
+        build_temporal_interaction_exec_wf(),
+        build_temporal_interaction_signal(),
+        build_temporal_interaction_query(),
+    ]:
+        encoded_string = encode_default_from_json_string(json.dumps(temporal_event_json))
+        decoded_json_string = decode_to_json_string(encoded_string)
+
+        assert encode_default_from_json_string(decoded_json_string) == encoded_string
+
+
+def test_query_round_trips_with_every_arg():
+    query = build_temporal_interaction_query()
+
+    encoded_string = encode_default_from_json_string(json.dumps(query))
+    decoded = json.loads(decode_to_json_string(encoded_string))
+
+    assert decoded == {**query, "expires_at": None}
+
+
+def test_signal_no_args_default():
+    encoded_string = encode_signal_no_args_default(
+        build_signal_without_input(run_id="some-equally-long-uuid-string")
+    )
+
+    assert encoded_string == encode_default_from_json_string(
+        json.dumps(build_temporal_interaction_signal())
+    )
+
+
+def test_signed_encoder_version():
+    signal = build_signal_without_input(run_id="some-equally-long-uuid-string")
+
+    encoded_string = encode_signal_no_args_with_version(Encoder.S, signal)
+    assert encoded_string.startswith("S~")
+
+    decoded = json.loads(decode_to_json_string(encoded_string))
+    assert decoded["workflow_id"] == signal.workflow_id
+
+    with pytest.raises(DecodeError):
+        decode_to_json_string(encoded_string.replace("W:some", "W:another"))
+
+
+def test_expired_encoded_string():
+    now = int(time.time())
+
+    unexpired = encode_signal_no_args_with_version(
+        Encoder.S, build_signal_without_input(expires_at=now + 60 * 60)
+    )
+    assert json.loads(decode_to_json_string(unexpired))["expires_at"] == now + 60 * 60
+
+    expired = encode_signal_no_args_with_version(
+        Encoder.S, build_signal_without_input(expires_at=now - 1)
+    )
+    with pytest.raises(DecodeError, match="expired") as err:
+        decode_to_json_string(expired)
+    assert err.value.code == "EXPIRED"
+
+
+def test_encode_checked_against_platform_limits():
+    as_string = json.dumps(build_temporal_interaction_signal())
+
+    assert encode_checked_from_json_string(
+        as_string, Profile.SlackActionId
+    ) == encode_default_from_json_string(as_string)
+    with pytest.raises(ValueError, match="over the discord_custom_id limit of 100"):
+        encode_checked_from_json_string(as_string, Profile.DiscordCustomId, Encoder.B)
+
+    with pytest.raises(ValueError, match=r"largest field is `S` \(signal_name\)"):
+        encode_signal_no_args_checked(
+            Encoder.A, build_signal_without_input(), Profile.TelegramCallbackData
+        )
+
+
+def test_decode_errors_have_a_code():
+    for encoded_string, code in [
+        ("A~E:Signal,N:test-namespace,T:test-task-queue-rs", "MISSING_KEY"),
+        ("Z9~E:Signal", "UNKNOWN_VERSION"),
+        ("not encoded", "MALFORMED_VERSION"),
+    ]:
+        with pytest.raises(DecodeError) as err:
+            decode_to_json_string(encoded_string)
+        assert err.value.code == code
+
+
+def test_typed_round_trip():
+    for interaction in [
+        ExecuteTemporalWorkflow(**without_type(build_temporal_interaction_exec_wf())),
+        SignalTemporal(**without_type(build_temporal_interaction_signal())),
+        QueryTemporal(**without_type(build_temporal_interaction_query())),
+        SignalWithStartTemporal(
+            namespace="test-namespace",
+            task_queue="test-task-queue-rs",
+            workflow_id="user-U024BE7LH",
+            workflow_type="GreetingWorkflow",
+            args=[{"user": "U024BE7LH"}],
+            signal_name="signal_name_thats_defined_in_workflow",
+        ),
+        CancelTemporal(
+            namespace="test-namespace",
+            task_queue="test-task-queue-rs",
+            workflow_id="some-super-long-uuid-string",
+            reason="aborted from slack",
+        ),
+        TerminateTemporal(
+            namespace="test-namespace",
+            task_queue="test-task-queue-rs",
+            workflow_id="some-super-long-uuid-string",
+            reason="stuck, see INC-1234",
+            details=[{"approved_by": "U024BE7LH"}],
+        ),
+        UpdateTemporal(
+            namespace="test-namespace",
+            task_queue="test-task-queue-rs",
+            workflow_id="some-super-long-uuid-string",
+            update_name="update_name_thats_defined_in_workflow",
+            args=[{"form": "access-request"}],
+            wait_stage="Accepted",
+        ),
+        CompleteActivityTemporal(
+            namespace="test-namespace",
+            task_queue="test-task-queue-rs",
+            workflow_id="some-super-long-uuid-string",
+            activity_id="7",
+            outcome="Failed",
+            reason="rejected in slack",
+        ),
+    ]:
+        for encoder_version in [None, Encoder.S, Encoder.X, Encoder.B]:
+            decoded = decode(encode(interaction, encoder_version))
+
+            assert type(decoded) is type(interaction)
+            assert decoded == interaction
+
+
+def test_typed_classes_are_dataclass_like():
+    query = QueryTemporal(
+        namespace="test-namespace",
+        task_queue="test-task-queue-rs",
+        query_type="status",
+        query_args=[1],
+    )
+    assert query.workflow_id is None
+    assert repr(query) == (
+        "QueryTemporal(namespace='test-namespace', task_queue='test-task-queue-rs', "
+        "workflow_id=None, run_id=None, query_type='status', query_args=[1], expires_at=None)"
+    )
+
+    query.workflow_id = "some-workflow-id"
+    assert query != QueryTemporal(
+        namespace="test-namespace", task_queue="test-task-queue-rs", query_type="status"
+    )
+    assert json.loads(decode_to_json_string(encode(query)))["workflow_id"] == "some-workflow-id"
+
+    with pytest.raises(TypeError):
+        encode(build_temporal_interaction_query())
+
+
+
+def test_update_wait_stage_is_checked():
+    update = UpdateTemporal(
+        namespace="test-namespace",
+        task_queue="test-task-queue-rs",
+        workflow_id="some-super-long-uuid-string",
+        update_name="update_name_thats_defined_in_workflow",
+        wait_stage="Admitted",
+    )
+    with pytest.raises(ValueError, match="unknown wait_stage `Admitted`"):
+        encode(update)
+
+
+def test_execute_start_options():
+    execute = ExecuteTemporalWorkflow(
+        **without_type(build_temporal_interaction_exec_wf()),
+        options={"run_timeout_secs": 3600, "id_reuse_policy": "RejectDuplicate"},
+    )
+
+    decoded = decode(encode(execute))
+    assert decoded == execute
+    assert decoded.options["run_timeout_secs"] == 3600
+    assert decoded.options["retry_policy"] is None
+
+    execute.options = {"id_reuse_policy": "AllowAll"}
+    with pytest.raises(ValueError, match="invalid options"):
+        encode(execute)
@@ -0,0 +1,330 @@
+//! WebAssembly bindings for the `temporal-json` crate, for browsers & edge runtimes that can't load the Node addon.
+//! Provides the same encoder & decoder interface as `temporal-json-node`
+
+use js_sys::{Error, Reflect};
+use serde::Deserialize;
+use temporal_json::{DecodeError, SignalTemporal, TemporalInteraction};
+pub use temporal_json::{Encoder, Profile, SigningKeys};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_TEMPORAL_SIGNAL_WITHOUT_INPUT: &str = r#"
+/**
+ * A Signal struct without the Input Payload.
+ *
+ * This is useful for routing webhook events back to the workflow,
+ * and the event itself will be sent as the Signal's Input to the workflow.
+ */
+export interface TemporalSignalWithoutInput {
+  namespace: string
+  taskQueue: string
+  workflowId?: string | null
+  runId?: string | null
+  signalName: string
+  /** unix timestamp (seconds) after which the encoded string no longer decodes */
+  expiresAt?: number | null
+}
+"#;
+
+#[wasm_bindgen]
+extern "C" {
+    /// JS object of the `TemporalSignalWithoutInput` interface, read with `SignalWithoutInput`
+    #[wasm_bindgen(typescript_type = "TemporalSignalWithoutInput")]
+    pub type TemporalSignalWithoutInput;
+}
+
+/// The same object as the Node bindings' `TemporalSignalWithoutInput`
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SignalWithoutInput {
+    namespace: String,
+    task_queue: String,
+    workflow_id: Option<String>,
+    run_id: Option<String>,
+    signal_name: String,
+    expires_at: Option<i64>,
+}
+
+impl SignalWithoutInput {
+    fn from_js(signal: TemporalSignalWithoutInput) -> Result<Self, JsError> {
+        serde_wasm_bindgen::from_value(signal.into())
+            .map_err(|err| JsError::new(&format!("not a TemporalSignalWithoutInput, {}", err)))
+    }
+}
+
+impl From<SignalWithoutInput> for SignalTemporal {
+    fn from(signal: SignalWithoutInput) -> Self {
+        SignalTemporal {
+            namespace: signal.namespace,
+            task_queue: signal.task_queue,
+            workflow_id: signal.workflow_id,
+            run_id: signal.run_id,
+            signal_name: signal.signal_name,
+            // a deadline before the epoch has already passed
+            expires_at: signal.expires_at.map(|expires_at| expires_at.max(0) as u64),
+            ..Default::default()
+        }
+    }
+}
+
+#[wasm_bindgen(js_name = encodeSignalNoArgsDefault)]
+/// Convert workflow metadata into an encoded string for embedding into a webhook event, using the default encoding.
+///
+/// Example: use this string as the `callback_id` for a slack interaction, and when
+/// the Temporal API Gateway receives the event it can decode the string and route the event to your workflow.
+pub fn encode_signal_no_args_default(
+    signal: TemporalSignalWithoutInput,
+) -> Result<String, JsError> {
+    encode_signal_no_args_with_version(Encoder::default(), signal)
+}
+
+#[wasm_bindgen(js_name = encodeSignalNoArgsWithVersion)]
+/// Convert workflow metadata into an encoded string for embedding into a webhook event, with a specific encoding version.
+pub fn encode_signal_no_args_with_version(
+    encoder_version: Encoder,
+    signal: TemporalSignalWithoutInput,
+) -> Result<String, JsError> {
+    let signal = SignalWithoutInput::from_js(signal)?;
+    encoder_version
+        .encode(TemporalInteraction::Signal(signal.into()))
+        .map_err(|err| JsError::new(&format!("failed to encode, {}", err)))
+}
+
+#[wasm_bindgen(js_name = encodeSignalNoArgsChecked)]
+/// Convert workflow metadata into an encoded string, and fail if it's too long for the profile's field.
+///
+/// Example: use `Profile.DiscordCustomId` to find out before Discord rejects a 101 char `custom_id`.
+pub fn encode_signal_no_args_checked(
+    encoder_version: Encoder,
+    signal: TemporalSignalWithoutInput,
+    profile: Profile,
+) -> Result<String, JsError> {
+    let signal = SignalWithoutInput::from_js(signal)?;
+    encoder_version
+        .encode_checked(TemporalInteraction::Signal(signal.into()), profile)
+        .map_err(|err| JsError::new(&format!("failed to encode, {}", err)))
+}
+
+#[wasm_bindgen(js_name = encodeDefaultFromJsonString)]
+/// Encode a TemporalInteraction struct provided as a JSON string.
+pub fn encode_default_from_json_string(json_string: &str) -> Result<String, JsError> {
+    Encoder::encode_default_from_json_string(json_string)
+        .map_err(|err| JsError::new(&format!("failed to encode from json string, {}", err)))
+}
+
+#[wasm_bindgen(js_name = encodeCheckedFromJsonString)]
+/// Encode a TemporalInteraction struct provided as a JSON string, and fail if it's too long for the profile's field.
+pub fn encode_checked_from_json_string(
+    json_string: &str,
+    profile: Profile,
+    encoder_version: Option<Encoder>,
+) -> Result<String, JsError> {
+    serde_json::from_str(json_string)
+        .map_err(anyhow::Error::from)
+        .and_then(|interaction| {
+            encoder_version
+                .unwrap_or_default()
+                .encode_checked(interaction, profile)
+        })
+        .map_err(|err| JsError::new(&format!("failed to encode from json string, {}", err)))
+}
+
+#[wasm_bindgen(js_name = decodeToJsonString)]
+/// Decode an encoded string into a JSON string representing a TemporalInteraction struct.
+///
+/// Errors have a `code` like `MISSING_KEY` or `EXPIRED` when the encoded string is invalid.
+pub fn decode_to_json_string(encoded_string: &str) -> Result<String, Error> {
+    Encoder::decode_to_json_string(encoded_string).map_err(|err| {
+        let js_error = Error::new(&format!("failed to decode from encoded string, {}", err));
+        // setting a property on a fresh Error can't fail
+        let _ = Reflect::set(&js_error, &"code".into(), &decode_error_code(&err).into());
+        js_error
+    })
+}
+
+/// the `DecodeError` variant's code, or the Node bindings' code for any other failure
+fn decode_error_code(err: &anyhow::Error) -> &'static str {
+    err.downcast_ref::<DecodeError>()
+        .map_or("GenericFailure", DecodeError::code)
+}
+
+#[wasm_bindgen(js_name = setSigningKeys)]
+/// Set the keys used to sign encoded strings with `Encoder.S`, as a comma separated list of `key_id:secret` pairs.
+/// The first key is used for signing. Must be called before the first encode/decode.
+///
+/// There are no env vars in wasm, so `Encoder.S` & `Encoder.X` fail until this is called.
+pub fn set_signing_keys(key_list: &str, require_signature: Option<bool>) -> Result<(), JsError> {
+    SigningKeys::from_key_list(key_list)
+        .and_then(|keys| {
+            temporal_json::set_signing_keys(
+                keys.with_require_signature(require_signature.unwrap_or_default()),
+            )
+        })
+        .map_err(|err| JsError::new(&format!("failed to set signing keys, {}", err)))
+}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod tests {
+    use serde::Serialize;
+    use serde_json::json;
+    use std::sync::Once;
+    use wasm_bindgen_test::*;
+
+    use super::*;
+
+    static SIGNING_KEYS: Once = Once::new();
+
+    // signing keys can only be set once, before anything is encoded or decoded,
+    // and tests share a wasm instance
+    fn set_test_signing_keys() {
+        SIGNING_KEYS.call_once(|| {
+            set_signing_keys("1:test-secret-that-is-not-used-anywhere-else", None).unwrap()
+        });
+    }
+
+    fn build_temporal_interaction_signal() -> serde_json::Value {
+        json!({
+            "type": "Signal",
+            "namespace": "test-namespace",
+            "task_queue": "test-task-queue-rs",
+            "workflow_id": "some-super-long-uuid-string",
+            "run_id": "some-equally-long-uuid-string",
+            "signal_name": "signal_name_thats_defined_in_workflow",
+        })
+    }
+
+    fn build_temporal_interaction_query() -> serde_json::Value {
+        json!({
+            "type": "Query",
+            "namespace": "test-namespace",
+            "task_queue": "test-task-queue-rs",
+            "workflow_id": "some-super-long-uuid-string",
+            "run_id": "some-equally-long-uuid-string",
+            "query_type": "query_type_thats_defined_in_workflow",
+            "query_args": [{ "arg1": "value1" }, 2],
+        })
+    }
+
+    fn build_signal_without_input(expires_at: Option<i64>) -> TemporalSignalWithoutInput {
+        let signal = json!({
+            "namespace": "test-namespace",
+            "taskQueue": "test-task-queue-rs",
+            "workflowId": "some-super-long-uuid-string",
+            "signalName": "signal_name_thats_defined_in_workflow",
+            "expiresAt": expires_at,
+        });
+        signal
+            .serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+            .unwrap()
+            .unchecked_into()
+    }
+
+    fn error_code(err: &Error) -> String {
+        Reflect::get(err, &"code".into())
+            .unwrap()
+            .as_string()
+            .unwrap()
+    }
+
+    #[wasm_bindgen_test]
+    fn test_decode_from_json_string() {
+        set_test_signing_keys();
+        for temporal_interaction in [
+            build_temporal_interaction_signal(),
+            build_temporal_interaction_query(),
+        ] {
+            let encoded_string =
+                encode_default_from_json_string(&temporal_interaction.to_string()).unwrap();
+            let decoded: serde_json::Value =
+                serde_json::from_str(&decode_to_json_string(&encoded_string).unwrap()).unwrap();
+
+            assert_eq!(
+                encode_default_from_json_string(&decoded.to_string()).unwrap(),
+                encoded_string
+            );
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn test_signed_encoder_version() {
+        set_test_signing_keys();
+
+        for encoder_version in [Encoder::S, Encoder::X] {
+            let encoded_string = encode_signal_no_args_with_version(
+                encoder_version,
+                build_signal_without_input(None),
+            )
+            .unwrap();
+            let decoded: serde_json::Value =
+                serde_json::from_str(&decode_to_json_string(&encoded_string).unwrap()).unwrap();
+            assert_eq!(decoded["workflow_id"], "some-super-long-uuid-string");
+        }
+
+        let encoded_string =
+            encode_signal_no_args_with_version(Encoder::S, build_signal_without_input(None))
+                .unwrap();
+        let err =
+            decode_to_json_string(&encoded_string.replace("W:some", "W:another")).unwrap_err();
+        assert_eq!(error_code(&err), "BAD_SIGNATURE");
+    }
+
+    #[wasm_bindgen_test]
+    fn test_expired_encoded_string() {
+        set_test_signing_keys();
+        let now = (js_sys::Date::now() / 1000.0) as i64;
+
+        let unexpired = encode_signal_no_args_with_version(
+            Encoder::S,
+            build_signal_without_input(Some(now + 60 * 60)),
+        )
+        .unwrap();
+        assert!(decode_to_json_string(&unexpired).is_ok());
+
+        let expired = encode_signal_no_args_with_version(
+            Encoder::S,
+            build_signal_without_input(Some(now - 1)),
+        )
+        .unwrap();
+        let err = decode_to_json_string(&expired).unwrap_err();
+        assert_eq!(error_code(&err), "EXPIRED");
+    }
+
+    #[wasm_bindgen_test]
+    fn test_encode_checked_against_platform_limits() {
+        set_test_signing_keys();
+        let as_string = build_temporal_interaction_signal().to_string();
+
+        assert_eq!(
+            encode_checked_from_json_string(&as_string, Profile::SlackActionId, None).unwrap(),
+            encode_default_from_json_string(&as_string).unwrap()
+        );
+        assert!(encode_checked_from_json_string(
+            &as_string,
+            Profile::DiscordCustomId,
+            Some(Encoder::B)
+        )
+        .is_err());
+        assert!(encode_signal_no_args_checked(
+            Encoder::A,
+            build_signal_without_input(None),
+            Profile::TelegramCallbackData
+        )
+        .is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_decode_errors_have_a_code() {
+        set_test_signing_keys();
+        for (encoded_string, code) in [
+            (
+                "A~E:Signal,N:test-namespace,T:test-task-queue-rs",
+                "MISSING_KEY",
+            ),
+            ("Z9~E:Signal", "UNKNOWN_VERSION"),
+            ("not encoded", "MALFORMED_VERSION"),
+        ] {
+            let err = decode_to_json_string(encoded_string).unwrap_err();
+            assert_eq!(error_code(&err), code);
+        }
+    }
+}
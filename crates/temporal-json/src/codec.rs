@@ -0,0 +1,156 @@
+//! Formats of the temporal section, keyed by the version prefix before the first `~`.
+//!
+//! The built-in `Encoder` versions are always registered. Other crates can add their own formats
+//! with `register_codec` at startup, and `Encoder::decode` will dispatch to them.
+use anyhow::{anyhow, Result};
+use once_cell::sync::Lazy;
+use std::{
+    collections::HashMap,
+    sync::{Arc, PoisonError, RwLock},
+};
+use strum::IntoEnumIterator;
+
+use crate::{DecodeError, Encoder, SigningKeys, TemporalInteraction, ENCODER_SECTION_DELIMITER};
+
+static CODEC_REGISTRY: Lazy<RwLock<CodecRegistry>> =
+    Lazy::new(|| RwLock::new(CodecRegistry::default()));
+
+/// Converts a TemporalInteraction to & from the temporal section of an encoded string,
+/// the part between the version prefix and the optional user data.
+pub trait InteractionCodec: Send + Sync {
+    /// prefix before the first `~` that identifies this format, e.g. `A`
+    fn version(&self) -> &str;
+
+    /// strings can't be altered without `decode` noticing, see `SigningKeys::with_require_signature`
+    fn is_signed(&self) -> bool;
+
+    /// the temporal section, without the version prefix. Must not contain a `~`
+    fn encode(
+        &self,
+        temporal_interaction: TemporalInteraction,
+        keys: &SigningKeys,
+    ) -> Result<String>;
+
+    /// reverse of `encode`, given only the temporal section. Use `DecodeError::Other` for failures
+    /// that don't fit another variant
+    fn decode(
+        &self,
+        temporal_section: &str,
+        keys: &SigningKeys,
+    ) -> Result<TemporalInteraction, DecodeError>;
+}
+
+/// `version~temporal_section` for any codec, registered or not
+pub fn encode_with_codec(
+    codec: &dyn InteractionCodec,
+    temporal_interaction: TemporalInteraction,
+    keys: &SigningKeys,
+) -> Result<String> {
+    Ok(format!(
+        "{}{}{}",
+        codec.version(),
+        ENCODER_SECTION_DELIMITER,
+        codec.encode(temporal_interaction, keys)?
+    ))
+}
+
+/// Codecs by version prefix
+#[derive(Clone)]
+pub struct CodecRegistry {
+    codecs: HashMap<String, Arc<dyn InteractionCodec>>,
+}
+
+impl Default for CodecRegistry {
+    /// every built-in `Encoder` version
+    fn default() -> Self {
+        let codecs = Encoder::iter()
+            .map(|encoder| {
+                let codec: Arc<dyn InteractionCodec> = Arc::new(encoder);
+                (codec.version().to_string(), codec)
+            })
+            .collect();
+
+        Self { codecs }
+    }
+}
+
+impl CodecRegistry {
+    /// Add a codec. Fails if its version is already registered or can't be told apart from the rest of the string.
+    pub fn register(&mut self, codec: Arc<dyn InteractionCodec>) -> Result<()> {
+        let version = codec.version();
+        if version.is_empty() || version.contains(ENCODER_SECTION_DELIMITER) {
+            return Err(anyhow!(
+                "codec version `{}` must be non-empty and can't contain `{}`",
+                version,
+                ENCODER_SECTION_DELIMITER
+            ));
+        }
+        if self.codecs.contains_key(version) {
+            return Err(anyhow!("codec version `{}` is already registered", version));
+        }
+
+        self.codecs.insert(version.to_string(), codec);
+        Ok(())
+    }
+
+    /// Remove every codec except `versions`. Fails if one of `versions` isn't registered.
+    pub fn retain_versions(&mut self, versions: &[&str]) -> Result<()> {
+        if let Some(unknown) = versions
+            .iter()
+            .find(|version| !self.codecs.contains_key(**version))
+        {
+            return Err(anyhow!(
+                "codec version `{}` is not registered, registered versions are {}",
+                unknown,
+                self.versions().join(", ")
+            ));
+        }
+
+        self.codecs
+            .retain(|version, _| versions.contains(&version.as_str()));
+        Ok(())
+    }
+
+    pub fn get(&self, version: &str) -> Option<Arc<dyn InteractionCodec>> {
+        self.codecs.get(version).cloned()
+    }
+
+    /// registered version prefixes, sorted
+    pub fn versions(&self) -> Vec<String> {
+        let mut versions: Vec<String> = self.codecs.keys().cloned().collect();
+        versions.sort();
+        versions
+    }
+}
+
+/// Add a codec to the registry used by `Encoder::decode`. Call at startup, before decoding.
+pub fn register_codec(codec: impl InteractionCodec + 'static) -> Result<()> {
+    CODEC_REGISTRY
+        .write()
+        .unwrap_or_else(PoisonError::into_inner)
+        .register(Arc::new(codec))
+}
+
+/// Make `Encoder::decode` refuse every version except `versions`, built-in or registered.
+pub fn accept_only_versions(versions: &[&str]) -> Result<()> {
+    CODEC_REGISTRY
+        .write()
+        .unwrap_or_else(PoisonError::into_inner)
+        .retain_versions(versions)
+}
+
+/// version prefixes `Encoder::decode` accepts, sorted
+pub fn accepted_versions() -> Vec<String> {
+    CODEC_REGISTRY
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .versions()
+}
+
+/// the codec `Encoder::decode` uses for `version`
+pub fn find_codec(version: &str) -> Option<Arc<dyn InteractionCodec>> {
+    CODEC_REGISTRY
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .get(version)
+}
@@ -0,0 +1,257 @@
+//! Length-prefixed binary packing of the temporal key:value pairs, see `Encoder::B`.
+//!
+//! Each pair starts with a tag byte: the value's kind in the top 3 bits, and the key's letter
+//! (`A` = 0) in the bottom 5 bits. Then the value, depending on the key & kind:
+//! - `E` event type: a single byte
+//! - `D` expiry deadline: a LEB128 varint
+//! - `KIND_UUID`: 16 raw bytes of a lowercase hyphenated UUID
+//! - `KIND_SIX_BIT`: a LEB128 varint char count, then 6 bits per char of the base64url alphabet,
+//!   so the value doesn't grow when the whole encoding is base64url'd
+//! - `KIND_TEXT`: a LEB128 varint length, then the UTF-8 bytes
+use anyhow::{anyhow, Context, Result};
+use std::str::FromStr;
+
+use crate::{KeysToTemporalAction, TemporalInteractionDiscriminants};
+
+const KIND_TEXT: u8 = 0;
+const KIND_UUID: u8 = 1;
+const KIND_SIX_BIT: u8 = 2;
+const KIND_SHIFT: u8 = 5;
+const KEY_MASK: u8 = 0b0001_1111;
+
+const SIX_BIT_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+const UUID_LENGTH_BYTES: usize = 16;
+/// character offsets of the `-` in a hyphenated UUID
+const UUID_HYPHEN_POSITIONS: [usize; 4] = [8, 13, 18, 23];
+
+pub fn pack(kv_pairs: &[(KeysToTemporalAction, String)]) -> Result<Vec<u8>> {
+    let mut packed = Vec::new();
+
+    for (key, value) in kv_pairs {
+        let key_bits = key_to_bits(*key);
+
+        match key {
+            KeysToTemporalAction::E => {
+                packed.push(key_bits);
+                packed.push(event_type_to_byte(
+                    TemporalInteractionDiscriminants::from_str(value)?,
+                ));
+            }
+            KeysToTemporalAction::D => {
+                packed.push(key_bits);
+                write_varint(
+                    &mut packed,
+                    value
+                        .parse()
+                        .context("expiry deadline is not a unix timestamp")?,
+                );
+            }
+            _ => {
+                if let Some(uuid) = parse_uuid(value) {
+                    packed.push(KIND_UUID << KIND_SHIFT | key_bits);
+                    packed.extend_from_slice(&uuid);
+                } else if let Some(six_bit_chars) = to_six_bit_chars(value) {
+                    packed.push(KIND_SIX_BIT << KIND_SHIFT | key_bits);
+                    write_varint(&mut packed, six_bit_chars.len() as u64);
+                    write_six_bit_chars(&mut packed, &six_bit_chars);
+                } else {
+                    packed.push(KIND_TEXT << KIND_SHIFT | key_bits);
+                    write_varint(&mut packed, value.len() as u64);
+                    packed.extend_from_slice(value.as_bytes());
+                }
+            }
+        }
+    }
+
+    Ok(packed)
+}
+
+pub fn unpack(mut packed: &[u8]) -> Result<Vec<(KeysToTemporalAction, String)>> {
+    let mut kv_pairs = Vec::new();
+
+    while let Some((&tag, rest)) = packed.split_first() {
+        packed = rest;
+        let key = key_from_bits(tag & KEY_MASK)?;
+
+        let value = match (key, tag >> KIND_SHIFT) {
+            (KeysToTemporalAction::E, KIND_TEXT) => {
+                event_type_from_byte(take_bytes(&mut packed, 1)?[0])?.to_string()
+            }
+            (KeysToTemporalAction::D, KIND_TEXT) => read_varint(&mut packed)?.to_string(),
+            (_, KIND_TEXT) => {
+                let length = read_length(&mut packed)?;
+                String::from_utf8(take_bytes(&mut packed, length)?.to_vec())
+                    .with_context(|| format!("value of key `{}` is not utf-8", key))?
+            }
+            (_, KIND_UUID) => format_uuid(take_bytes(&mut packed, UUID_LENGTH_BYTES)?),
+            (_, KIND_SIX_BIT) => {
+                let char_count = read_length(&mut packed)?;
+                read_six_bit_chars(&mut packed, char_count)?
+            }
+            (_, kind) => return Err(anyhow!("unknown value kind {} for key `{}`", kind, key)),
+        };
+
+        kv_pairs.push((key, value));
+    }
+
+    Ok(kv_pairs)
+}
+
+fn key_to_bits(key: KeysToTemporalAction) -> u8 {
+    // keys are single uppercase ASCII letters
+    key.to_string().as_bytes()[0] - b'A'
+}
+
+fn key_from_bits(key_bits: u8) -> Result<KeysToTemporalAction> {
+    KeysToTemporalAction::from_str(&char::from(b'A' + key_bits).to_string())
+        .with_context(|| format!("unknown key bits {}", key_bits))
+}
+
+// new event types must be given a new byte, never reuse or renumber these
+fn event_type_to_byte(event_type: TemporalInteractionDiscriminants) -> u8 {
+    match event_type {
+        TemporalInteractionDiscriminants::Execute => 0,
+        TemporalInteractionDiscriminants::Signal => 1,
+        TemporalInteractionDiscriminants::Query => 2,
+        TemporalInteractionDiscriminants::SignalWithStart => 3,
+        TemporalInteractionDiscriminants::Cancel => 4,
+        TemporalInteractionDiscriminants::Terminate => 5,
+        TemporalInteractionDiscriminants::Update => 6,
+        TemporalInteractionDiscriminants::CompleteActivity => 7,
+    }
+}
+
+fn event_type_from_byte(event_type_byte: u8) -> Result<TemporalInteractionDiscriminants> {
+    match event_type_byte {
+        0 => Ok(TemporalInteractionDiscriminants::Execute),
+        1 => Ok(TemporalInteractionDiscriminants::Signal),
+        2 => Ok(TemporalInteractionDiscriminants::Query),
+        3 => Ok(TemporalInteractionDiscriminants::SignalWithStart),
+        4 => Ok(TemporalInteractionDiscriminants::Cancel),
+        5 => Ok(TemporalInteractionDiscriminants::Terminate),
+        6 => Ok(TemporalInteractionDiscriminants::Update),
+        7 => Ok(TemporalInteractionDiscriminants::CompleteActivity),
+        _ => Err(anyhow!("unknown event type byte {}", event_type_byte)),
+    }
+}
+
+/// only lowercase hyphenated UUIDs, so `format_uuid` gives back the exact same string
+fn parse_uuid(value: &str) -> Option<[u8; UUID_LENGTH_BYTES]> {
+    if value.len() != 36 {
+        return None;
+    }
+
+    let mut hex_digits = Vec::with_capacity(UUID_LENGTH_BYTES * 2);
+    for (position, char) in value.bytes().enumerate() {
+        match char {
+            b'-' if UUID_HYPHEN_POSITIONS.contains(&position) => {}
+            b'0'..=b'9' => hex_digits.push(char - b'0'),
+            b'a'..=b'f' => hex_digits.push(char - b'a' + 10),
+            _ => return None,
+        }
+    }
+    if hex_digits.len() != UUID_LENGTH_BYTES * 2 {
+        return None;
+    }
+
+    let mut uuid = [0; UUID_LENGTH_BYTES];
+    for (byte, pair) in uuid.iter_mut().zip(hex_digits.chunks(2)) {
+        *byte = (pair[0] << 4) | pair[1];
+    }
+    Some(uuid)
+}
+
+fn format_uuid(uuid: &[u8]) -> String {
+    let mut formatted = String::with_capacity(36);
+    for (index, byte) in uuid.iter().enumerate() {
+        if [4, 6, 8, 10].contains(&index) {
+            formatted.push('-');
+        }
+        formatted.push_str(&format!("{:02x}", byte));
+    }
+    formatted
+}
+
+/// indexes into `SIX_BIT_ALPHABET`, if every char of `value` is in it
+fn to_six_bit_chars(value: &str) -> Option<Vec<u8>> {
+    value
+        .bytes()
+        .map(|char| {
+            SIX_BIT_ALPHABET
+                .iter()
+                .position(|&alphabet_char| alphabet_char == char)
+                .map(|index| index as u8)
+        })
+        .collect()
+}
+
+fn write_six_bit_chars(packed: &mut Vec<u8>, six_bit_chars: &[u8]) {
+    let mut bits = 0u32;
+    let mut bit_count = 0;
+    for &six_bit_char in six_bit_chars {
+        bits = (bits << 6) | u32::from(six_bit_char);
+        bit_count += 6;
+        if bit_count >= 8 {
+            bit_count -= 8;
+            packed.push((bits >> bit_count) as u8);
+        }
+    }
+    if bit_count > 0 {
+        packed.push((bits << (8 - bit_count)) as u8);
+    }
+}
+
+fn read_six_bit_chars(packed: &mut &[u8], char_count: usize) -> Result<String> {
+    let byte_count = char_count
+        .checked_mul(6)
+        .map(|bit_count| (bit_count + 7) / 8)
+        .ok_or_else(|| anyhow!("value length does not fit in memory"))?;
+    let mut bits = 0u32;
+    let mut bit_count = 0;
+    let mut value = String::with_capacity(char_count);
+    for &byte in take_bytes(packed, byte_count)? {
+        bits = (bits << 8) | u32::from(byte);
+        bit_count += 8;
+        while bit_count >= 6 && value.len() < char_count {
+            bit_count -= 6;
+            value.push(char::from(
+                SIX_BIT_ALPHABET[((bits >> bit_count) & 0b11_1111) as usize],
+            ));
+        }
+    }
+    Ok(value)
+}
+
+fn write_varint(packed: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        packed.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    packed.push(value as u8);
+}
+
+fn read_varint(packed: &mut &[u8]) -> Result<u64> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let byte = take_bytes(packed, 1)?[0];
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(anyhow!("varint is longer than 64 bits"))
+}
+
+fn read_length(packed: &mut &[u8]) -> Result<usize> {
+    usize::try_from(read_varint(packed)?).context("value length does not fit in memory")
+}
+
+fn take_bytes<'a>(packed: &mut &'a [u8], length: usize) -> Result<&'a [u8]> {
+    if packed.len() < length {
+        return Err(anyhow!("compact encoding ended early"));
+    }
+    let (taken, rest) = packed.split_at(length);
+    *packed = rest;
+    Ok(taken)
+}
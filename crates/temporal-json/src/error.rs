@@ -0,0 +1,51 @@
+//! Why an encoded string couldn't be decoded, see `Encoder::decode`.
+use strum::IntoStaticStr;
+use thiserror::Error;
+
+use crate::{KeysToTemporalAction, ENCODER_HELP_MSG};
+
+#[derive(Error, IntoStaticStr, Debug)]
+#[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
+pub enum DecodeError {
+    #[error("Malformed version in encoder string: {}", ENCODER_HELP_MSG)]
+    MalformedVersion,
+    #[error("unknown encoder version `{version}`, accepted versions are {}", .accepted_versions.join(", "))]
+    UnknownVersion {
+        version: String,
+        accepted_versions: Vec<String>,
+    },
+    #[error("encoder version {0} is not signed, and signatures are required")]
+    Unsigned(String),
+    #[error("not a formatted kv pair: `{0}`")]
+    MalformedPair(String),
+    #[error("unknown temporal key `{0}`")]
+    UnknownKey(String),
+    #[error("temporal key `{0}` not supplied in callback_id")]
+    MissingKey(KeysToTemporalAction),
+    #[error("unknown temporal event type `{0}`")]
+    UnknownEventType(String),
+    #[error("value of temporal key `{key}` is malformed: {reason}")]
+    MalformedValue {
+        key: KeysToTemporalAction,
+        reason: String,
+    },
+    /// the temporal section as a whole, for versions that aren't key:value pairs
+    #[error("temporal section is malformed: {0}")]
+    MalformedSection(String),
+    /// the deadline in the `D` key has passed
+    #[error("encoded string expired at {expired_at} (unix timestamp)")]
+    Expired { expired_at: u64 },
+    /// the signature or ciphertext doesn't match any signing key
+    #[error("bad signature: {0}")]
+    BadSignature(String),
+    /// failures of codecs registered outside this crate, or invalid signing keys in the environment
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+impl DecodeError {
+    /// stable name of the variant like `MISSING_KEY`, for error codes in other languages & APIs
+    pub fn code(&self) -> &'static str {
+        self.into()
+    }
+}
@@ -1,18 +1,51 @@
 use anyhow::{anyhow, Context, Result};
-use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, hash::Hash, str::FromStr};
-use strum::{Display, EnumDiscriminants, EnumIter, EnumString, IntoEnumIterator};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use flate2::{read::DeflateDecoder, write::DeflateEncoder, Compression};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    hash::Hash,
+    io::Read,
+    str::FromStr,
+};
+use strum::{Display, EnumDiscriminants, EnumIter, EnumString, IntoEnumIterator, IntoStaticStr};
+
+pub mod codec;
+mod compact;
+pub mod error;
+pub mod profile;
+#[cfg(feature = "json-schema")]
+pub mod schema;
+pub mod signing;
+pub use codec::{register_codec, InteractionCodec};
+pub use error::DecodeError;
+pub use profile::{EncodedLengthError, Profile};
+pub use signing::{set_signing_keys, signing_keys, SigningKeys};
 
 #[cfg(feature = "js")]
 use napi::bindgen_prelude::*;
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, EnumDiscriminants, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 #[serde(tag = "type")]
 #[strum_discriminants(derive(EnumString, Display))]
 pub enum TemporalInteraction {
+    /// start a workflow
     Execute(ExecuteTemporalWorkflow),
+    /// signal a running workflow
     Signal(SignalTemporal),
+    /// query a running workflow
     Query(QueryTemporal),
+    /// signal a workflow, starting it first if it isn't running
+    SignalWithStart(SignalWithStartTemporal),
+    /// request that a running workflow cancels itself
+    Cancel(CancelTemporal),
+    /// stop a running workflow immediately, without running its cleanup
+    Terminate(TerminateTemporal),
+    /// send an update to a running workflow and wait for what its handler returns
+    Update(UpdateTemporal),
+    /// finish an activity that is waiting to be completed asynchronously
+    CompleteActivity(CompleteActivityTemporal),
 }
 
 impl TemporalInteraction {
@@ -23,10 +56,21 @@ impl TemporalInteraction {
             }
             TemporalInteraction::Signal(_) => TemporalInteractionDiscriminants::Signal.to_string(),
             TemporalInteraction::Query(_) => TemporalInteractionDiscriminants::Query.to_string(),
+            TemporalInteraction::SignalWithStart(_) => {
+                TemporalInteractionDiscriminants::SignalWithStart.to_string()
+            }
+            TemporalInteraction::Cancel(_) => TemporalInteractionDiscriminants::Cancel.to_string(),
+            TemporalInteraction::Terminate(_) => {
+                TemporalInteractionDiscriminants::Terminate.to_string()
+            }
+            TemporalInteraction::Update(_) => TemporalInteractionDiscriminants::Update.to_string(),
+            TemporalInteraction::CompleteActivity(_) => {
+                TemporalInteractionDiscriminants::CompleteActivity.to_string()
+            }
         }
     }
 
-    pub fn to_slack_string(self) -> String {
+    pub fn to_slack_string(self) -> Result<String> {
         Encoder::A.encode(self)
     }
 
@@ -41,6 +85,14 @@ impl TemporalInteraction {
                 .workflow_id
                 .as_ref()
                 .map_or("".into(), |some| some.clone()),
+            TemporalInteraction::SignalWithStart(action) => action.workflow_id.clone(),
+            TemporalInteraction::Cancel(action) => action.workflow_id.clone(),
+            TemporalInteraction::Terminate(action) => action.workflow_id.clone(),
+            TemporalInteraction::Update(action) => action.workflow_id.clone(),
+            TemporalInteraction::CompleteActivity(action) => action
+                .workflow_id
+                .as_ref()
+                .map_or("".into(), |some| some.clone()),
         }
     }
 
@@ -49,6 +101,11 @@ impl TemporalInteraction {
             TemporalInteraction::Execute(action) => action.task_queue.clone(),
             TemporalInteraction::Signal(action) => action.task_queue.clone(),
             TemporalInteraction::Query(action) => action.task_queue.clone(),
+            TemporalInteraction::SignalWithStart(action) => action.task_queue.clone(),
+            TemporalInteraction::Cancel(action) => action.task_queue.clone(),
+            TemporalInteraction::Terminate(action) => action.task_queue.clone(),
+            TemporalInteraction::Update(action) => action.task_queue.clone(),
+            TemporalInteraction::CompleteActivity(action) => action.task_queue.clone(),
         }
     }
 
@@ -57,25 +114,85 @@ impl TemporalInteraction {
             TemporalInteraction::Execute(action) => action.namespace.clone(),
             TemporalInteraction::Signal(action) => action.namespace.clone(),
             TemporalInteraction::Query(action) => action.namespace.clone(),
+            TemporalInteraction::SignalWithStart(action) => action.namespace.clone(),
+            TemporalInteraction::Cancel(action) => action.namespace.clone(),
+            TemporalInteraction::Terminate(action) => action.namespace.clone(),
+            TemporalInteraction::Update(action) => action.namespace.clone(),
+            TemporalInteraction::CompleteActivity(action) => action.namespace.clone(),
+        }
+    }
+
+    /// unix timestamp (seconds) after which the encoded string no longer decodes
+    pub fn expires_at(&self) -> Option<u64> {
+        match self {
+            TemporalInteraction::Execute(action) => action.expires_at,
+            TemporalInteraction::Signal(action) => action.expires_at,
+            TemporalInteraction::Query(action) => action.expires_at,
+            TemporalInteraction::SignalWithStart(action) => action.expires_at,
+            TemporalInteraction::Cancel(action) => action.expires_at,
+            TemporalInteraction::Terminate(action) => action.expires_at,
+            TemporalInteraction::Update(action) => action.expires_at,
+            TemporalInteraction::CompleteActivity(action) => action.expires_at,
         }
     }
 
+    /// Add args after any that were already encoded, e.g. the inbound webhook event.
     pub fn add_data_args(self, args: Option<Vec<serde_json::Value>>) -> Self {
+        fn append(
+            existing: Option<Vec<serde_json::Value>>,
+            args: Option<Vec<serde_json::Value>>,
+        ) -> Option<Vec<serde_json::Value>> {
+            match (existing, args) {
+                (Some(mut existing), Some(args)) => {
+                    existing.extend(args);
+                    Some(existing)
+                }
+                (existing, args) => existing.or(args),
+            }
+        }
+
         match self {
-            Self::Execute(exec) => Self::Execute(ExecuteTemporalWorkflow { args, ..exec }),
+            Self::Execute(exec) => Self::Execute(ExecuteTemporalWorkflow {
+                args: append(exec.args, args),
+                ..exec
+            }),
             Self::Signal(signal) => Self::Signal(SignalTemporal {
-                input: args,
+                input: append(signal.input, args),
                 ..signal
             }),
             Self::Query(query) => Self::Query(QueryTemporal {
-                query_args: args,
+                query_args: append(query.query_args, args),
                 ..query
             }),
+            // the event is for the signal, the workflow is started with its encoded args
+            Self::SignalWithStart(signal_with_start) => {
+                Self::SignalWithStart(SignalWithStartTemporal {
+                    input: append(signal_with_start.input, args),
+                    ..signal_with_start
+                })
+            }
+            // a cancel request has no payload to carry the event
+            Self::Cancel(cancel) => Self::Cancel(cancel),
+            // the event is recorded in the terminated workflow's history, e.g. who clicked
+            Self::Terminate(terminate) => Self::Terminate(TerminateTemporal {
+                details: append(terminate.details, args),
+                ..terminate
+            }),
+            Self::Update(update) => Self::Update(UpdateTemporal {
+                args: append(update.args, args),
+                ..update
+            }),
+            // the activity returns the event, or records it with its failure or cancellation
+            Self::CompleteActivity(complete) => Self::CompleteActivity(CompleteActivityTemporal {
+                result: append(complete.result, args),
+                ..complete
+            }),
         }
     }
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Default, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct ExecuteTemporalWorkflow {
     pub namespace: String,
     pub task_queue: String,
@@ -83,10 +200,66 @@ pub struct ExecuteTemporalWorkflow {
     /// the Workflow's Function name
     pub workflow_type: String,
     pub args: Option<Vec<serde_json::Value>>,
+    /// timeouts, retries & the rest of how the workflow is started
+    pub options: Option<WorkflowStartOptions>,
+    /// unix timestamp (seconds) after which the encoded string no longer decodes
+    pub expires_at: Option<u64>,
+}
+
+/// How a workflow is started, Temporal's defaults are used for anything unset
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Default, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct WorkflowStartOptions {
+    /// seconds the workflow can take, including retries & continue-as-new
+    pub execution_timeout_secs: Option<u64>,
+    /// seconds a single run of the workflow can take
+    pub run_timeout_secs: Option<u64>,
+    /// seconds a single workflow task can take
+    pub task_timeout_secs: Option<u64>,
+    /// whether a workflow can start with the id of one that already ran
+    pub id_reuse_policy: Option<WorkflowIdReusePolicy>,
+    /// failed workflows aren't retried unless this is set
+    pub retry_policy: Option<WorkflowRetryPolicy>,
+    /// start the workflow on a cron schedule, e.g. `0 9 * * MON`
+    pub cron_schedule: Option<String>,
+    /// shown with the workflow, but can't be searched by
+    pub memo: Option<BTreeMap<String, serde_json::Value>>,
+    /// values the workflow can be listed by, each must be registered with Temporal
+    pub search_attributes: Option<BTreeMap<String, serde_json::Value>>,
+}
+
+/// Whether a workflow can start with the id of one that already ran
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, EnumString, Display)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub enum WorkflowIdReusePolicy {
+    /// if the last workflow with the id has closed, whatever its result
+    AllowDuplicate,
+    /// if the last workflow with the id failed, was cancelled, terminated or timed out
+    AllowDuplicateFailedOnly,
+    /// never
+    RejectDuplicate,
+    /// and any running workflow with the id is terminated first
+    TerminateIfRunning,
 }
 
+/// How a failed workflow is retried
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Default, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct WorkflowRetryPolicy {
+    /// seconds before the first retry
+    pub initial_interval_secs: Option<u64>,
+    /// how much longer each interval is than the last
+    pub backoff_coefficient: Option<serde_json::Number>,
+    /// the longest interval in seconds, however many retries
+    pub maximum_interval_secs: Option<u64>,
+    /// attempts including the first, unlimited when unset or 0
+    pub maximum_attempts: Option<u32>,
+    /// error types that fail the workflow without a retry
+    pub non_retryable_error_types: Option<Vec<String>>,
+}
 
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Default, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct SignalTemporal {
     pub namespace: String,
     pub task_queue: String,
@@ -97,6 +270,8 @@ pub struct SignalTemporal {
     pub identity: Option<String>,
     pub request_id: Option<String>,
     pub control: Option<String>,
+    /// unix timestamp (seconds) after which the encoded string no longer decodes
+    pub expires_at: Option<u64>,
 }
 
 impl SignalTemporal {
@@ -106,12 +281,14 @@ impl SignalTemporal {
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Default, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct TemporalWorkflowExecutionInfo {
     pub workflow_id: String,
     pub run_id: String,
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct QueryTemporal {
     pub namespace: String,
     pub task_queue: String,
@@ -119,6 +296,8 @@ pub struct QueryTemporal {
     pub run_id: Option<String>,
     pub query_type: String,
     pub query_args: Option<Vec<serde_json::Value>>,
+    /// unix timestamp (seconds) after which the encoded string no longer decodes
+    pub expires_at: Option<u64>,
 }
 
 impl QueryTemporal {
@@ -126,208 +305,440 @@ impl QueryTemporal {
         self.run_id.as_ref().map_or("".into(), |some| some.clone())
     }
 
+    /// every query arg as a JSON array string, how they're stored in the `U` key
     pub fn query_args(&self) -> String {
         self.query_args.as_ref().map_or("".into(), |some| {
-            some.first()
-                .map_or_else(|| "".to_string(), |arg| arg.to_string())
+            serde_json::Value::Array(some.clone()).to_string()
+        })
+    }
+}
+
+/// A Signal that starts the workflow first if it isn't running, e.g. a per-user workflow
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Default, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct SignalWithStartTemporal {
+    pub namespace: String,
+    pub task_queue: String,
+    pub workflow_id: String,
+    /// the Workflow's Function name, if it has to be started
+    pub workflow_type: String,
+    /// the args the workflow is started with, if it isn't running
+    pub args: Option<Vec<serde_json::Value>>,
+    pub signal_name: String,
+    pub input: Option<Vec<serde_json::Value>>,
+    pub identity: Option<String>,
+    pub request_id: Option<String>,
+    pub control: Option<String>,
+    /// unix timestamp (seconds) after which the encoded string no longer decodes
+    pub expires_at: Option<u64>,
+}
+
+/// Request that a running workflow cancels itself, e.g. an "Abort deployment" button
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Default, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct CancelTemporal {
+    pub namespace: String,
+    pub task_queue: String,
+    pub workflow_id: String,
+    pub run_id: Option<String>,
+    /// why the workflow was cancelled, recorded in its history
+    pub reason: Option<String>,
+    pub identity: Option<String>,
+    pub request_id: Option<String>,
+    /// unix timestamp (seconds) after which the encoded string no longer decodes
+    pub expires_at: Option<u64>,
+}
+
+/// Stop a running workflow immediately. The workflow can't clean up, so prefer `CancelTemporal`
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Default, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct TerminateTemporal {
+    pub namespace: String,
+    pub task_queue: String,
+    pub workflow_id: String,
+    pub run_id: Option<String>,
+    /// why the workflow was terminated, recorded in its history
+    pub reason: Option<String>,
+    /// recorded in the terminated workflow's history along with the reason
+    pub details: Option<Vec<serde_json::Value>>,
+    pub identity: Option<String>,
+    /// unix timestamp (seconds) after which the encoded string no longer decodes
+    pub expires_at: Option<u64>,
+}
+
+impl TerminateTemporal {
+    /// every detail as a JSON array string, how they're stored in the `V` key
+    pub fn details(&self) -> String {
+        self.details.as_ref().map_or("".into(), |some| {
+            serde_json::Value::Array(some.clone()).to_string()
         })
     }
 }
 
+/// Send an update to a running workflow, e.g. so a Slack modal can be validated by the workflow before it closes
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Default, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct UpdateTemporal {
+    pub namespace: String,
+    pub task_queue: String,
+    pub workflow_id: String,
+    pub run_id: Option<String>,
+    /// the update handler's name, as defined in the workflow
+    pub update_name: String,
+    pub args: Option<Vec<serde_json::Value>>,
+    /// how far the update gets before Temporal responds, `Completed` when unset
+    pub wait_stage: Option<UpdateWaitStage>,
+    pub identity: Option<String>,
+    /// dedupes retried updates, a new UUID when unset
+    pub update_id: Option<String>,
+    /// unix timestamp (seconds) after which the encoded string no longer decodes
+    pub expires_at: Option<u64>,
+}
+
+/// How far an update gets before Temporal responds
+#[derive(
+    Serialize, Deserialize, Debug, PartialEq, Eq, Default, Clone, Copy, EnumString, Display,
+)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub enum UpdateWaitStage {
+    /// the workflow's validator accepted the update, its handler may still be running
+    Accepted,
+    /// the workflow's handler returned a result or failed
+    #[default]
+    Completed,
+}
+
+/// Finish an activity that returned `complete_async`, e.g. a human approval step.
+///
+/// The activity is found by its `task_token`, or by `workflow_id` & `activity_id`
+/// when the token is too long to encode.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Default, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct CompleteActivityTemporal {
+    pub namespace: String,
+    pub task_queue: String,
+    /// the activity's task token as base64url, without padding
+    pub task_token: Option<String>,
+    pub workflow_id: Option<String>,
+    pub run_id: Option<String>,
+    pub activity_id: Option<String>,
+    /// how the activity finishes, `Completed` when unset
+    pub outcome: Option<ActivityOutcome>,
+    /// the activity's result, or the details of its failure or cancellation
+    pub result: Option<Vec<serde_json::Value>>,
+    /// the failure message, when the activity is failed
+    pub reason: Option<String>,
+    pub identity: Option<String>,
+    /// unix timestamp (seconds) after which the encoded string no longer decodes
+    pub expires_at: Option<u64>,
+}
+
+impl CompleteActivityTemporal {
+    /// whether the activity can be completed without its task token
+    pub fn has_activity_id(&self) -> bool {
+        self.workflow_id.is_some() && self.activity_id.is_some()
+    }
+
+    /// the task token's raw bytes, as Temporal expects them
+    pub fn task_token_bytes(&self) -> Result<Option<Vec<u8>>> {
+        self.task_token
+            .as_ref()
+            .map(|task_token| {
+                URL_SAFE_NO_PAD
+                    .decode(task_token)
+                    .context("task token is not base64url")
+            })
+            .transpose()
+    }
+}
+
+/// How an asynchronously completed activity finishes
+#[derive(
+    Serialize, Deserialize, Debug, PartialEq, Eq, Default, Clone, Copy, EnumString, Display,
+)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub enum ActivityOutcome {
+    /// the activity returns its result
+    #[default]
+    Completed,
+    /// the activity fails with the reason, and is retried by its retry policy
+    Failed,
+    /// the activity is cancelled, only once the workflow has requested it
+    Canceled,
+}
+
+/// What Temporal returned for a TemporalInteraction
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+#[serde(tag = "type")]
+pub enum TemporalInteractionResponse {
+    ExecuteWorkflow(TemporalExecuteWorkflowResponse),
+    Signal(TemporalSignalResponse),
+    Query(TemporalQueryResponse),
+    SignalWithStart(TemporalSignalWithStartResponse),
+    Cancel(TemporalCancelResponse),
+    Terminate(TemporalTerminateResponse),
+    Update(TemporalUpdateResponse),
+    CompleteActivity(TemporalCompleteActivityResponse),
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct TemporalExecuteWorkflowResponse {
+    pub run_id: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct TemporalSignalResponse {}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct TemporalSignalWithStartResponse {
+    /// the run that was signalled, whether it was already running or just started
+    pub run_id: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct TemporalCancelResponse {}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct TemporalTerminateResponse {}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct TemporalCompleteActivityResponse {}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct TemporalUpdateResponse {
+    pub update_id: String,
+    /// how far the update got, `Accepted` updates have no result yet
+    pub stage: UpdateWaitStage,
+    /// each payload the update handler returned, as JSON
+    pub result: Option<Vec<serde_json::Value>>,
+    /// the update handler's failure message, when it rejected or failed the update
+    pub failure: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct TemporalQueryResponse {
+    /// the workflow's execution status, when Temporal rejected the query
+    pub query_rejected: Option<i32>,
+    /// each payload the query returned, as JSON
+    pub query_result: Option<Vec<serde_json::Value>>,
+}
+
 pub const SLACK_INFO_DELIMITER: &str = ",";
 pub const TEMPORAL_KEY_DELIMITER: &str = ":";
 pub const ENCODER_SECTION_DELIMITER: &str = "~";
 pub const ENCODER_HELP_MSG: &str =
     "Encoder string format: version~temporal_key:temporal_value~user_data";
+/// longest encoded string including user data, Slack's limit for a `callback_id` or `action_id`
+pub const ENCODED_MAX_LENGTH: usize = 255;
+/// longest `A` value for `Execute`, `SignalWithStart` & `Update` args, or `J` start options,
+/// leaving room for the rest of a 255 char callback_id
+pub const EXECUTE_ARGS_MAX_ENCODED_LENGTH: usize = 100;
+/// longest `O` task token, longer tokens are left out when the activity can be completed by id
+pub const TASK_TOKEN_MAX_ENCODED_LENGTH: usize = 100;
+/// workflow args & options are small & static, refuse to inflate anything bigger
+const EXECUTE_ARGS_MAX_DECOMPRESSED_BYTES: u64 = 16 * 1024;
+/// starts an escaped delimiter inside a value, e.g. `%2C` for `,`
+pub const ESCAPE_CHAR: char = '%';
+/// percent-encoding of the chars that can't appear as-is in a value, `%` itself first
+const ESCAPED_CHARS: [(char, &str); 4] = [
+    (ESCAPE_CHAR, "%25"),
+    (',', "%2C"),
+    (':', "%3A"),
+    ('~', "%7E"),
+];
 
-#[derive(EnumIter, EnumString, Display, PartialEq, Eq, Hash, Debug)]
+#[derive(EnumIter, EnumString, Display, IntoStaticStr, PartialEq, Eq, Hash, Debug, Default)]
 #[cfg_attr(feature = "js", napi_derive::napi)]
+#[cfg_attr(feature = "python", pyo3::pyclass(module = "temporal_json"))]
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
 pub enum Encoder {
+    /// plain text key:value pairs
+    #[default]
     A,
-}
-
-impl Default for Encoder {
-    fn default() -> Self {
-        Self::A
-    }
+    /// key:value pairs of `A`, plus a key id & HMAC of the temporal section so it can't be tampered with
+    S,
+    /// key id & base64url ChaCha20-Poly1305 ciphertext of the `A` key:value pairs, so they can't be read or tampered with
+    X,
+    /// base64url of the `A` key:value pairs packed as length-prefixed binary, with UUIDs as 16 raw bytes
+    B,
 }
 
 impl Encoder {
-    pub fn from_encoded_str(encoded: &str) -> Result<(Self, &str)> {
+    /// the built-in version of an encoded string, see `codec::find_codec` for registered versions
+    pub fn from_encoded_str(encoded: &str) -> Result<(Self, &str), DecodeError> {
         let (version_str, encoded_without_version) = encoded
             .split_once(ENCODER_SECTION_DELIMITER)
-            .ok_or_else(|| anyhow!("Malformed version in encoder string: {}", ENCODER_HELP_MSG))?;
+            .ok_or(DecodeError::MalformedVersion)?;
 
         // return tuple of (Encoder, rest_of_string_without_version)
         Self::from_str(version_str)
-            .context("invalid version string")
+            .map_err(|_| DecodeError::UnknownVersion {
+                version: version_str.to_string(),
+                accepted_versions: Self::iter().map(|version| version.to_string()).collect(),
+            })
             .map(|version| (version, encoded_without_version))
     }
 
-    pub fn encode(&self, temporal_interaction: TemporalInteraction) -> String {
+    /// versions that can't be altered without the gateway noticing
+    pub fn is_signed(&self) -> bool {
         match self {
-            Encoder::A => {
-                let mut kv_pairs = Vec::new();
-
-                let namespace = temporal_interaction.namespace();
-                let task_queue = temporal_interaction.task_queue();
-                let workflow_id = temporal_interaction.workflow_id();
-
-                // set event type from outer enum variant
-                kv_pairs
-                    .push(KeysToTemporalAction::E.to_kv(&temporal_interaction.to_type_string()));
-
-                match temporal_interaction {
-                    TemporalInteraction::Execute(action) => {
-                        for key in KeysToTemporalAction::iter() {
-                            kv_pairs.push(key.to_kv(match key {
-                                KeysToTemporalAction::W => &workflow_id,
-                                KeysToTemporalAction::N => &namespace,
-                                KeysToTemporalAction::T => &task_queue,
-                                KeysToTemporalAction::Y => &action.workflow_type,
-                                _ => continue,
-                            }))
-                        }
-                    }
-                    TemporalInteraction::Signal(action) => {
-                        for key in KeysToTemporalAction::iter() {
-                            kv_pairs.push(match key {
-                                KeysToTemporalAction::W => key.to_kv(&workflow_id),
-                                KeysToTemporalAction::N => key.to_kv(&namespace),
-                                KeysToTemporalAction::T => key.to_kv(&task_queue),
-                                KeysToTemporalAction::R => key.to_kv(&action.run_id()),
-                                KeysToTemporalAction::S => key.to_kv(&action.signal_name),
-                                _ => continue,
-                            })
-                        }
-                    }
-                    TemporalInteraction::Query(action) => {
-                        for key in KeysToTemporalAction::iter() {
-                            match key {
-                                KeysToTemporalAction::W => key.to_kv(&workflow_id),
-                                KeysToTemporalAction::N => key.to_kv(&namespace),
-                                KeysToTemporalAction::T => key.to_kv(&task_queue),
-                                KeysToTemporalAction::Q => key.to_kv(&action.query_type),
-                                KeysToTemporalAction::U => key.to_kv(&action.query_args()),
-                                _ => continue,
-                            };
-                        }
-                    }
-                }
+            Encoder::A | Encoder::B => false,
+            Encoder::S | Encoder::X => true,
+        }
+    }
 
-                format!(
-                    "{}{}{}",
-                    self,
-                    ENCODER_SECTION_DELIMITER,
-                    kv_pairs.join(",")
-                )
-            }
+    /// Encode with the process-wide `signing::signing_keys()`
+    pub fn encode(&self, temporal_interaction: TemporalInteraction) -> Result<String> {
+        self.encode_with_keys(temporal_interaction, signing_keys()?)
+    }
+
+    pub fn encode_with_keys(
+        &self,
+        temporal_interaction: TemporalInteraction,
+        keys: &SigningKeys,
+    ) -> Result<String> {
+        codec::encode_with_codec(self, temporal_interaction, keys)
+    }
+
+    /// Encode, and fail with an `EncodedLengthError` if the string is too long for the `profile`'s field.
+    pub fn encode_checked(
+        &self,
+        temporal_interaction: TemporalInteraction,
+        profile: Profile,
+    ) -> Result<String> {
+        self.encode_checked_with_keys(temporal_interaction, profile, signing_keys()?)
+    }
+
+    pub fn encode_checked_with_keys(
+        &self,
+        temporal_interaction: TemporalInteraction,
+        profile: Profile,
+        keys: &SigningKeys,
+    ) -> Result<String> {
+        let encoded = self.encode_with_keys(temporal_interaction.clone(), keys)?;
+
+        let length = profile.length_of(&encoded);
+        if length <= profile.max_length() {
+            return Ok(encoded);
+        }
+
+        // sizes from the readable pairs, which `S`, `X` & `B` grow or shrink roughly evenly
+        let (largest_key, largest_value_length) = temporal_kv_pairs(temporal_interaction)?
+            .into_iter()
+            .map(|(key, value)| (key, profile.length_of(&escape_value(&value))))
+            .max_by_key(|(_, value_length)| *value_length)
+            .ok_or_else(|| anyhow!("temporal section has no kv pairs"))?;
+
+        Err(EncodedLengthError {
+            profile,
+            length,
+            largest_key,
+            largest_value_length,
+        }
+        .into())
+    }
+
+    /// Encode, then add `user_data` after a `~` delimiter. Fails if the whole string is longer than `ENCODED_MAX_LENGTH`.
+    pub fn encode_with_user_data(
+        &self,
+        temporal_interaction: TemporalInteraction,
+        user_data: &str,
+    ) -> Result<String> {
+        self.encode_with_user_data_and_keys(temporal_interaction, user_data, signing_keys()?)
+    }
+
+    pub fn encode_with_user_data_and_keys(
+        &self,
+        temporal_interaction: TemporalInteraction,
+        user_data: &str,
+        keys: &SigningKeys,
+    ) -> Result<String> {
+        let encoded = self.encode_with_keys(temporal_interaction, keys)?;
+        let with_user_data = format!("{}{}{}", encoded, ENCODER_SECTION_DELIMITER, user_data);
+
+        let length = with_user_data.chars().count();
+        if length > ENCODED_MAX_LENGTH {
+            let temporal_length = encoded.chars().count();
+            return Err(anyhow!(
+                "encoded string with user data is {} chars, over the limit of {}. The temporal section takes {} chars, leaving {} for user data",
+                length,
+                ENCODED_MAX_LENGTH,
+                temporal_length,
+                ENCODED_MAX_LENGTH.saturating_sub(temporal_length + ENCODER_SECTION_DELIMITER.len())
+            ));
         }
+
+        Ok(with_user_data)
     }
 
     /// an encoded callback_id is a string with key:value pairs, with comma separation between pairs.
-    /// Users can supply their own data after the encoding by adding a delimiter `~` after the encoding before their data.
+    /// Users can supply their own data after the encoding, see `encode_with_user_data`.
     /// character limit for entire string is 255, and the temporal info takes up around 170 chars.
     ///
     /// `"A~E:Signal,W:some-super-long-uuid-string,N:test-namespace,T:test-task-queue-rs,R:some-equally-long-uuid-string,S:signal_name_thats_defined_in_workflow~Some User Defined Data Under 80 chars"`
-    pub fn decode(encoded_str: &str) -> Result<TemporalInteraction> {
-        let (encoder_version, encoded_str_without_version) = Self::from_encoded_str(encoded_str)?;
-
-        match encoder_version {
-            Encoder::A => {
-                // a comma separated string of key:value pairs. keys are KeysToTemporalAction variants
-                let temporal_encoded_str = encoded_str_without_version
-                    .split_once(ENCODER_SECTION_DELIMITER)
-                    .map_or_else(
-                        || encoded_str_without_version,
-                        |(temporal_str, _user_str)| temporal_str,
-                    );
-
-                let kv_pairs = temporal_encoded_str
-                    .split(SLACK_INFO_DELIMITER)
-                    .map(|kv_pair| {
-                        kv_pair
-                            .split_once(TEMPORAL_KEY_DELIMITER)
-                            .ok_or_else(|| anyhow!("not a formatted kv pair"))
-                    })
-                    .collect::<Result<Vec<_>, _>>()?;
-
-                let mut encoder_map: HashMap<KeysToTemporalAction, &str> = HashMap::default();
-                for (k, v) in kv_pairs {
-                    let formatted_key = KeysToTemporalAction::from_str(k)?;
-                    encoder_map.insert(formatted_key, v);
-                }
+    ///
+    /// Decodes with the process-wide `signing::signing_keys()`
+    pub fn decode(encoded_str: &str) -> Result<TemporalInteraction, DecodeError> {
+        Self::decode_with_keys(encoded_str, signing_keys()?)
+    }
 
-                let temporal_event_type_str = encoder_map
-                    .remove(&KeysToTemporalAction::E)
-                    .ok_or_else(|| anyhow!("temporal event type not supplied in callback_id"))?;
-
-                let temporal_event_type =
-                    TemporalInteractionDiscriminants::from_str(temporal_event_type_str)?;
-
-                let namespace = KeysToTemporalAction::N.get_value(&mut encoder_map)?.into();
-                let task_queue = KeysToTemporalAction::T.get_value(&mut encoder_map)?.into();
-
-                let temporal_event_without_payload = match temporal_event_type {
-                    TemporalInteractionDiscriminants::Execute => {
-                        TemporalInteraction::Execute(ExecuteTemporalWorkflow {
-                            namespace,
-                            task_queue,
-                            workflow_id: KeysToTemporalAction::W
-                                .get_value(&mut encoder_map)?
-                                .into(),
-                            workflow_type: KeysToTemporalAction::Y
-                                .get_value(&mut encoder_map)?
-                                .into(),
-                            args: None,
-                        })
-                    }
-                    TemporalInteractionDiscriminants::Signal => {
-                        TemporalInteraction::Signal(SignalTemporal {
-                            namespace,
-                            task_queue,
-                            workflow_id: KeysToTemporalAction::W
-                                .get_value(&mut encoder_map)
-                                .ok()
-                                .map(|s| s.into()),
-                            run_id: KeysToTemporalAction::R
-                                .get_value(&mut encoder_map)
-                                .ok()
-                                .map(|s| s.into()),
-                            signal_name: KeysToTemporalAction::S
-                                .get_value(&mut encoder_map)?
-                                .into(),
-                            input: None,
-                            ..Default::default()
-                        })
-                    }
-                    TemporalInteractionDiscriminants::Query => {
-                        TemporalInteraction::Query(QueryTemporal {
-                            namespace,
-                            task_queue,
-                            workflow_id: KeysToTemporalAction::W
-                                .get_value(&mut encoder_map)
-                                .ok()
-                                .map(|s| s.into()),
-                            run_id: KeysToTemporalAction::R
-                                .get_value(&mut encoder_map)
-                                .ok()
-                                .map(|s| s.into()),
-                            query_type: KeysToTemporalAction::Q.get_value(&mut encoder_map)?.into(),
-                            query_args: None,
-                        })
-                    }
-                };
+    pub fn decode_with_keys(
+        encoded_str: &str,
+        keys: &SigningKeys,
+    ) -> Result<TemporalInteraction, DecodeError> {
+        Self::decode_with_user_data_and_keys(encoded_str, keys)
+            .map(|(temporal_interaction, _user_data)| temporal_interaction)
+    }
 
-                Ok(temporal_event_without_payload)
-            }
+    /// Decode, and return the user data section after the temporal info if there is one.
+    pub fn decode_with_user_data(
+        encoded_str: &str,
+    ) -> Result<(TemporalInteraction, Option<String>), DecodeError> {
+        Self::decode_with_user_data_and_keys(encoded_str, signing_keys()?)
+    }
+
+    pub fn decode_with_user_data_and_keys(
+        encoded_str: &str,
+        keys: &SigningKeys,
+    ) -> Result<(TemporalInteraction, Option<String>), DecodeError> {
+        let (version_str, encoded_str_without_version) = encoded_str
+            .split_once(ENCODER_SECTION_DELIMITER)
+            .ok_or(DecodeError::MalformedVersion)?;
+        let codec = codec::find_codec(version_str).ok_or_else(|| DecodeError::UnknownVersion {
+            version: version_str.to_string(),
+            accepted_versions: codec::accepted_versions(),
+        })?;
+
+        if keys.require_signature && !codec.is_signed() {
+            return Err(DecodeError::Unsigned(version_str.to_string()));
         }
+
+        // the user data section is never part of the temporal info
+        let (temporal_encoded_str, user_data) = encoded_str_without_version
+            .split_once(ENCODER_SECTION_DELIMITER)
+            .map_or_else(
+                || (encoded_str_without_version, None),
+                |(temporal_str, user_str)| (temporal_str, Some(user_str.to_string())),
+            );
+
+        let temporal_interaction = codec.decode(temporal_encoded_str, keys)?;
+
+        Ok((temporal_interaction, user_data))
     }
 
     pub fn encode_default_from_json_string(temporal_action_as_json_str: &str) -> Result<String> {
         let interaction: TemporalInteraction = serde_json::from_str(temporal_action_as_json_str)?;
 
-        Ok(Self::default().encode(interaction))
+        Self::default().encode(interaction)
     }
 
     pub fn decode_to_json_string(encoded_str: &str) -> Result<String> {
@@ -338,128 +749,1917 @@ impl Encoder {
     }
 }
 
-#[derive(EnumIter, EnumString, Display, PartialEq, Eq, Hash, Debug)]
-pub enum KeysToTemporalAction {
-    /// Temporal Event Type (signal, query, execute)
-    E,
-    /// Workflow_id
-    W,
-    /// Namespace
-    N,
-    /// Taskqueue
-    T,
-    /// workflow tYpe aka fn name
-    Y,
-    /// workflow Run_id
-    R,
-    /// Signal name
-    S,
-    /// Query type
-    Q,
-    /// qUery args
-    U,
-}
-
-impl KeysToTemporalAction {
-    pub fn to_kv(&self, value: &str) -> String {
-        format!("{}{}{}", self, TEMPORAL_KEY_DELIMITER, value)
+impl InteractionCodec for Encoder {
+    fn version(&self) -> &str {
+        self.into()
     }
 
-    pub fn get_value<'a>(&self, encoder_map: &mut HashMap<Self, &'a str>) -> Result<&'a str> {
-        encoder_map.remove(self).ok_or_else(|| {
-            anyhow!(
-                "temporal key: `{:?}` not supplied in callback_id. encoder_map =  {:?}",
-                self,
-                encoder_map
-            )
-        })
+    fn is_signed(&self) -> bool {
+        Encoder::is_signed(self)
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use serde_json::json;
+    fn encode(
+        &self,
+        temporal_interaction: TemporalInteraction,
+        keys: &SigningKeys,
+    ) -> Result<String> {
+        match self {
+            Encoder::A => Ok(encode_kv_pairs(temporal_interaction)?.join(SLACK_INFO_DELIMITER)),
+            Encoder::S => {
+                let key_id = keys
+                    .active_key_id()
+                    .ok_or_else(|| anyhow!("no signing key configured for encoder {}", self))?;
 
-    use super::*;
-    use crate::{SignalTemporal, TemporalInteraction};
+                let mut kv_pairs = encode_kv_pairs(temporal_interaction)?;
+                kv_pairs.push(KeysToTemporalAction::K.to_kv(key_id));
+                let signed_kv_pairs = kv_pairs.join(SLACK_INFO_DELIMITER);
 
-    fn build_mock_signal() -> TemporalInteraction {
-        TemporalInteraction::Signal(SignalTemporal {
-            namespace: "test-namespace".into(),
-            task_queue: "test-task-queue-rs".into(),
-            workflow_id: Some("some-super-long-uuid-string".into()),
-            run_id: Some("some-equally-long-uuid-string".into()),
-            signal_name: "signal_name_thats_defined_in_workflow".into(),
-            input: None,
-            ..Default::default()
-        })
-    }
+                // the signature covers the version and every temporal kv pair before it
+                let signature = keys.sign(
+                    key_id,
+                    &format!("{}{}{}", self, ENCODER_SECTION_DELIMITER, signed_kv_pairs),
+                )?;
 
-    fn build_mock_wf_exec() -> TemporalInteraction {
-        TemporalInteraction::Execute(ExecuteTemporalWorkflow {
-            namespace: "test-namespace".into(),
-            task_queue: "test-task-queue-rs".into(),
-            workflow_id: "some-super-long-uuid-string".into(),
-            workflow_type: "some-wf-function-name".into(),
-            args: Some(vec![json!({
-                    "arg1" : "value1"
-            })]),
-        })
-    }
+                Ok(format!(
+                    "{}{}{}",
+                    signed_kv_pairs,
+                    SLACK_INFO_DELIMITER,
+                    KeysToTemporalAction::H.to_kv(&signature)
+                ))
+            }
+            Encoder::X => {
+                let key_id = keys
+                    .active_key_id()
+                    .ok_or_else(|| anyhow!("no signing key configured for encoder {}", self))?;
 
-    #[test]
-    fn test_encode_slack_callback_id() {
-        let temporal_interaction = build_mock_signal();
-        let encoder = Encoder::A;
-        let callback_id = encoder.encode(temporal_interaction);
+                // the version & key id stay readable, and are authenticated along with the ciphertext
+                let key_id_kv = KeysToTemporalAction::K.to_kv(key_id);
+                let header = format!("{}{}{}", self, ENCODER_SECTION_DELIMITER, key_id_kv);
+                let plaintext = encode_kv_pairs(temporal_interaction)?.join(SLACK_INFO_DELIMITER);
+                let sealed = keys.seal(key_id, header.as_bytes(), plaintext.as_bytes())?;
 
-        dbg!(&callback_id);
-        dbg!(&callback_id.len()); // 143 as ordered csv, 153 as kv_pairs
+                Ok(format!(
+                    "{}{}{}",
+                    key_id_kv,
+                    SLACK_INFO_DELIMITER,
+                    KeysToTemporalAction::C.to_kv(&URL_SAFE_NO_PAD.encode(sealed))
+                ))
+            }
+            Encoder::B => {
+                Ok(URL_SAFE_NO_PAD
+                    .encode(compact::pack(&temporal_kv_pairs(temporal_interaction)?)?))
+            }
+        }
     }
 
-    #[test]
-    fn test_decode_slack_callback_id() {
-        let temporal_interaction = build_mock_signal();
+    fn decode(
+        &self,
+        temporal_section: &str,
+        keys: &SigningKeys,
+    ) -> Result<TemporalInteraction, DecodeError> {
+        match self {
+            Encoder::A => interaction_from_kv_map(decode_kv_pairs(temporal_section)?),
+            Encoder::S => {
+                let signature_kv_prefix = format!(
+                    "{}{}",
+                    SLACK_INFO_DELIMITER,
+                    KeysToTemporalAction::H.to_kv("")
+                );
+                let (signed_kv_pairs, signature) = temporal_section
+                    .rsplit_once(&signature_kv_prefix)
+                    .ok_or(DecodeError::MissingKey(KeysToTemporalAction::H))?;
 
-        let encoder = Encoder::A;
-        let callback_id = encoder.encode(temporal_interaction);
+                let mut encoder_map = decode_kv_pairs(signed_kv_pairs)?;
+                let key_id = KeysToTemporalAction::K.get_value(&mut encoder_map)?;
 
-        let parsed = Encoder::decode(&callback_id).unwrap();
+                keys.verify(
+                    &key_id,
+                    &format!("{}{}{}", self, ENCODER_SECTION_DELIMITER, signed_kv_pairs),
+                    signature,
+                )
+                .map_err(|err| DecodeError::BadSignature(err.to_string()))?;
 
-        assert_eq!(build_mock_signal(), parsed)
+                interaction_from_kv_map(encoder_map)
+            }
+            Encoder::X => {
+                let mut encoder_map = decode_kv_pairs(temporal_section)?;
+                let key_id = KeysToTemporalAction::K.get_value(&mut encoder_map)?;
+                let ciphertext = URL_SAFE_NO_PAD
+                    .decode(KeysToTemporalAction::C.get_value(&mut encoder_map)?)
+                    .map_err(|_| DecodeError::MalformedValue {
+                        key: KeysToTemporalAction::C,
+                        reason: "ciphertext is not base64url".into(),
+                    })?;
+
+                let header = format!(
+                    "{}{}{}",
+                    self,
+                    ENCODER_SECTION_DELIMITER,
+                    KeysToTemporalAction::K.to_kv(&key_id)
+                );
+                let plaintext = keys
+                    .open(&key_id, header.as_bytes(), &ciphertext)
+                    .map_err(|err| DecodeError::BadSignature(err.to_string()))?;
+                let plaintext = String::from_utf8(plaintext).map_err(|_| {
+                    DecodeError::MalformedSection("decrypted temporal section is not utf-8".into())
+                })?;
+
+                interaction_from_kv_map(decode_kv_pairs(&plaintext)?)
+            }
+            Encoder::B => {
+                let packed = URL_SAFE_NO_PAD.decode(temporal_section).map_err(|_| {
+                    DecodeError::MalformedSection("compact encoding is not base64url".into())
+                })?;
+                let kv_pairs = compact::unpack(&packed)
+                    .map_err(|err| DecodeError::MalformedSection(format!("{:#}", err)))?;
+                // binary values are never escaped
+                interaction_from_kv_map(kv_pairs.into_iter().collect())
+            }
+        }
     }
+}
 
-    #[test]
-    fn test_encode_decode_all_encoder_versions() {
-        for encoder_version in Encoder::iter() {
-            for temporal_event in [build_mock_signal(), build_mock_wf_exec()] {
-                // get expected decoded item for each event type
-                let expected_output = match &temporal_event {
-                    TemporalInteraction::Execute(exec_wf) => {
-                        TemporalInteraction::Execute(ExecuteTemporalWorkflow {
-                            args: None,
-                            ..exec_wf.to_owned()
-                        })
-                    }
-                    TemporalInteraction::Signal(_sig_wf) => temporal_event.to_owned(),
-                    TemporalInteraction::Query(_query_wf) => temporal_event.to_owned(),
-                };
+/// the temporal section as `key:value` strings, starting with the event type
+fn encode_kv_pairs(temporal_interaction: TemporalInteraction) -> Result<Vec<String>> {
+    Ok(temporal_kv_pairs(temporal_interaction)?
+        .into_iter()
+        .map(|(key, value)| key.to_kv(&value))
+        .collect())
+}
 
-                // as struct
-                let callback_id = encoder_version.encode(temporal_event.clone());
-                let parsed = Encoder::decode(&callback_id).expect(&format!(
-                    "failed to decode. version {encoder_version} for string {callback_id}"
-                ));
-                assert_eq!(expected_output, parsed);
+/// the temporal section as (key, value) pairs, starting with the event type
+fn temporal_kv_pairs(
+    temporal_interaction: TemporalInteraction,
+) -> Result<Vec<(KeysToTemporalAction, String)>> {
+    let mut kv_pairs = Vec::new();
 
-                // as json string
-                let as_string = serde_json::to_string(&temporal_event).unwrap();
-                let callback_id = Encoder::encode_default_from_json_string(&as_string).unwrap();
-                let parsed = Encoder::decode(&callback_id).expect(&format!(
-                    "failed to decode. version {encoder_version} for string {callback_id}"
-                ));
-                assert_eq!(expected_output, parsed)
+    let namespace = temporal_interaction.namespace();
+    let task_queue = temporal_interaction.task_queue();
+    let workflow_id = temporal_interaction.workflow_id();
+
+    // set event type from outer enum variant
+    kv_pairs.push((
+        KeysToTemporalAction::E,
+        temporal_interaction.to_type_string(),
+    ));
+
+    if let Some(expires_at) = temporal_interaction.expires_at() {
+        kv_pairs.push((KeysToTemporalAction::D, expires_at.to_string()));
+    }
+
+    match temporal_interaction {
+        TemporalInteraction::Execute(action) => {
+            for key in KeysToTemporalAction::iter() {
+                let value = match key {
+                    KeysToTemporalAction::W => workflow_id.clone(),
+                    KeysToTemporalAction::N => namespace.clone(),
+                    KeysToTemporalAction::T => task_queue.clone(),
+                    KeysToTemporalAction::Y => action.workflow_type.clone(),
+                    KeysToTemporalAction::A => match &action.args {
+                        Some(args) => encode_execute_args(args)?,
+                        None => continue,
+                    },
+                    KeysToTemporalAction::J => match &action.options {
+                        Some(options) => encode_start_options(options)?,
+                        None => continue,
+                    },
+                    _ => continue,
+                };
+                kv_pairs.push((key, value))
             }
         }
+        TemporalInteraction::Signal(action) => {
+            for key in KeysToTemporalAction::iter() {
+                let value = match key {
+                    KeysToTemporalAction::W => workflow_id.clone(),
+                    KeysToTemporalAction::N => namespace.clone(),
+                    KeysToTemporalAction::T => task_queue.clone(),
+                    KeysToTemporalAction::R => action.run_id(),
+                    KeysToTemporalAction::S => action.signal_name.clone(),
+                    _ => continue,
+                };
+                kv_pairs.push((key, value))
+            }
+        }
+        TemporalInteraction::Query(action) => {
+            for key in KeysToTemporalAction::iter() {
+                // optional fields are left out when unset, so they decode back to `None`
+                let value = match key {
+                    KeysToTemporalAction::W => action.workflow_id.clone(),
+                    KeysToTemporalAction::N => Some(namespace.clone()),
+                    KeysToTemporalAction::T => Some(task_queue.clone()),
+                    KeysToTemporalAction::R => action.run_id.clone(),
+                    KeysToTemporalAction::Q => Some(action.query_type.clone()),
+                    KeysToTemporalAction::U => {
+                        action.query_args.as_ref().map(|_| action.query_args())
+                    }
+                    _ => continue,
+                };
+                if let Some(value) = value {
+                    kv_pairs.push((key, value))
+                }
+            }
+        }
+        TemporalInteraction::SignalWithStart(action) => {
+            for key in KeysToTemporalAction::iter() {
+                let value = match key {
+                    KeysToTemporalAction::W => workflow_id.clone(),
+                    KeysToTemporalAction::N => namespace.clone(),
+                    KeysToTemporalAction::T => task_queue.clone(),
+                    KeysToTemporalAction::Y => action.workflow_type.clone(),
+                    KeysToTemporalAction::A => match &action.args {
+                        Some(args) => encode_execute_args(args)?,
+                        None => continue,
+                    },
+                    KeysToTemporalAction::S => action.signal_name.clone(),
+                    _ => continue,
+                };
+                kv_pairs.push((key, value))
+            }
+        }
+        TemporalInteraction::Cancel(action) => {
+            for key in KeysToTemporalAction::iter() {
+                let value = match key {
+                    KeysToTemporalAction::W => Some(workflow_id.clone()),
+                    KeysToTemporalAction::N => Some(namespace.clone()),
+                    KeysToTemporalAction::T => Some(task_queue.clone()),
+                    KeysToTemporalAction::R => action.run_id.clone(),
+                    KeysToTemporalAction::M => action.reason.clone(),
+                    _ => continue,
+                };
+                if let Some(value) = value {
+                    kv_pairs.push((key, value))
+                }
+            }
+        }
+        TemporalInteraction::Terminate(action) => {
+            for key in KeysToTemporalAction::iter() {
+                let value = match key {
+                    KeysToTemporalAction::W => Some(workflow_id.clone()),
+                    KeysToTemporalAction::N => Some(namespace.clone()),
+                    KeysToTemporalAction::T => Some(task_queue.clone()),
+                    KeysToTemporalAction::R => action.run_id.clone(),
+                    KeysToTemporalAction::M => action.reason.clone(),
+                    KeysToTemporalAction::V => action.details.as_ref().map(|_| action.details()),
+                    _ => continue,
+                };
+                if let Some(value) = value {
+                    kv_pairs.push((key, value))
+                }
+            }
+        }
+        TemporalInteraction::Update(action) => {
+            for key in KeysToTemporalAction::iter() {
+                let value = match key {
+                    KeysToTemporalAction::W => Some(workflow_id.clone()),
+                    KeysToTemporalAction::N => Some(namespace.clone()),
+                    KeysToTemporalAction::T => Some(task_queue.clone()),
+                    KeysToTemporalAction::A => action
+                        .args
+                        .as_ref()
+                        .map(|args| encode_execute_args(args))
+                        .transpose()?,
+                    KeysToTemporalAction::R => action.run_id.clone(),
+                    KeysToTemporalAction::P => Some(action.update_name.clone()),
+                    KeysToTemporalAction::G => action.wait_stage.map(|stage| stage.to_string()),
+                    _ => continue,
+                };
+                if let Some(value) = value {
+                    kv_pairs.push((key, value))
+                }
+            }
+        }
+        TemporalInteraction::CompleteActivity(action) => {
+            // task tokens are usually too long for a callback_id, the ids are much shorter
+            let task_token = action.task_token.clone().filter(|task_token| {
+                task_token.len() <= TASK_TOKEN_MAX_ENCODED_LENGTH || !action.has_activity_id()
+            });
+            if task_token.is_none() && !action.has_activity_id() {
+                return Err(anyhow!(
+                    "CompleteActivity needs a task_token, or a workflow_id & activity_id"
+                ));
+            }
+
+            for key in KeysToTemporalAction::iter() {
+                let value = match key {
+                    KeysToTemporalAction::W => action.workflow_id.clone(),
+                    KeysToTemporalAction::N => Some(namespace.clone()),
+                    KeysToTemporalAction::T => Some(task_queue.clone()),
+                    KeysToTemporalAction::R => action.run_id.clone(),
+                    KeysToTemporalAction::M => action.reason.clone(),
+                    KeysToTemporalAction::O => task_token.clone(),
+                    KeysToTemporalAction::I => action.activity_id.clone(),
+                    KeysToTemporalAction::F => action.outcome.map(|outcome| outcome.to_string()),
+                    _ => continue,
+                };
+                if let Some(value) = value {
+                    kv_pairs.push((key, value))
+                }
+            }
+        }
+    }
+
+    Ok(kv_pairs)
+}
+
+/// `Execute` args as deflated JSON in base64url, which must fit in `EXECUTE_ARGS_MAX_ENCODED_LENGTH`
+fn encode_execute_args(args: &[serde_json::Value]) -> Result<String> {
+    encode_deflated_json(args, "execute args")
+}
+
+/// deflated JSON in base64url, which must fit in `EXECUTE_ARGS_MAX_ENCODED_LENGTH`.
+/// `what` is plural, for errors
+fn encode_deflated_json<T: Serialize + ?Sized>(value: &T, what: &str) -> Result<String> {
+    let mut deflater = DeflateEncoder::new(Vec::new(), Compression::best());
+    serde_json::to_writer(&mut deflater, value)
+        .with_context(|| format!("unable to convert {} to json", what))?;
+    let encoded = URL_SAFE_NO_PAD.encode(deflater.finish()?);
+
+    if encoded.len() > EXECUTE_ARGS_MAX_ENCODED_LENGTH {
+        return Err(anyhow!(
+            "{} are {} chars once compressed, over the limit of {}",
+            what,
+            encoded.len(),
+            EXECUTE_ARGS_MAX_ENCODED_LENGTH
+        ));
+    }
+
+    Ok(encoded)
+}
+
+/// `Execute` start options like its args, leaving out unset fields to save space
+fn encode_start_options(options: &WorkflowStartOptions) -> Result<String> {
+    fn retain_set_fields(value: &mut serde_json::Value) {
+        if let serde_json::Value::Object(fields) = value {
+            fields.retain(|_, field| !field.is_null());
+        }
+    }
+
+    let mut options = serde_json::to_value(options)?;
+    retain_set_fields(&mut options);
+    if let Some(retry_policy) = options.get_mut("retry_policy") {
+        retain_set_fields(retry_policy);
+    }
+
+    encode_deflated_json(&options, "start options")
+}
+
+fn decode_execute_args(encoded: &str) -> Result<Vec<serde_json::Value>> {
+    decode_deflated_json(encoded, "execute args")
+}
+
+fn decode_deflated_json<T: DeserializeOwned>(encoded: &str, what: &str) -> Result<T> {
+    let compressed = URL_SAFE_NO_PAD
+        .decode(encoded)
+        .with_context(|| format!("{} are not base64url", what))?;
+
+    // never inflate more than the limit, whatever the compressed size claims
+    let mut json = Vec::new();
+    DeflateDecoder::new(compressed.as_slice())
+        .take(EXECUTE_ARGS_MAX_DECOMPRESSED_BYTES + 1)
+        .read_to_end(&mut json)
+        .with_context(|| format!("{} are not deflated", what))?;
+    if json.len() as u64 > EXECUTE_ARGS_MAX_DECOMPRESSED_BYTES {
+        return Err(anyhow!("{} are too large once decompressed", what));
+    }
+
+    serde_json::from_slice(&json).with_context(|| format!("{} are not valid", what))
+}
+
+/// a comma separated string of key:value pairs. keys are KeysToTemporalAction variants.
+/// Values are escaped, so every delimiter in the string separates pairs or keys from values.
+fn decode_kv_pairs(
+    temporal_encoded_str: &str,
+) -> Result<HashMap<KeysToTemporalAction, String>, DecodeError> {
+    let kv_pairs = temporal_encoded_str
+        .split(SLACK_INFO_DELIMITER)
+        .map(|kv_pair| {
+            kv_pair
+                .split_once(TEMPORAL_KEY_DELIMITER)
+                .ok_or_else(|| DecodeError::MalformedPair(kv_pair.to_string()))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut encoder_map: HashMap<KeysToTemporalAction, String> = HashMap::default();
+    for (k, v) in kv_pairs {
+        let formatted_key = KeysToTemporalAction::from_str(k)
+            .map_err(|_| DecodeError::UnknownKey(k.to_string()))?;
+        encoder_map.insert(formatted_key, unescape_value(v));
+    }
+
+    Ok(encoder_map)
+}
+
+fn interaction_from_kv_map(
+    mut encoder_map: HashMap<KeysToTemporalAction, String>,
+) -> Result<TemporalInteraction, DecodeError> {
+    let temporal_event_type_str = KeysToTemporalAction::E.get_value(&mut encoder_map)?;
+
+    let temporal_event_type = TemporalInteractionDiscriminants::from_str(&temporal_event_type_str)
+        .map_err(|_| DecodeError::UnknownEventType(temporal_event_type_str))?;
+
+    // refuse expired strings before anything else is read from them
+    let expires_at = encoder_map
+        .remove(&KeysToTemporalAction::D)
+        .map(|expires_at_str| {
+            expires_at_str
+                .parse::<u64>()
+                .map_err(|_| DecodeError::MalformedValue {
+                    key: KeysToTemporalAction::D,
+                    reason: "expiry deadline is not a unix timestamp".into(),
+                })
+        })
+        .transpose()?;
+    if let Some(expired_at) = expires_at {
+        if unix_now()? >= expired_at {
+            return Err(DecodeError::Expired { expired_at });
+        }
+    }
+
+    let namespace = KeysToTemporalAction::N.get_value(&mut encoder_map)?;
+    let task_queue = KeysToTemporalAction::T.get_value(&mut encoder_map)?;
+
+    let temporal_event_without_payload = match temporal_event_type {
+        TemporalInteractionDiscriminants::Execute => {
+            TemporalInteraction::Execute(ExecuteTemporalWorkflow {
+                namespace,
+                task_queue,
+                workflow_id: KeysToTemporalAction::W.get_value(&mut encoder_map)?,
+                workflow_type: KeysToTemporalAction::Y.get_value(&mut encoder_map)?,
+                args: decoded_execute_args(&mut encoder_map)?,
+                options: KeysToTemporalAction::J
+                    .get_value(&mut encoder_map)
+                    .ok()
+                    .map(|options| decode_deflated_json(&options, "start options"))
+                    .transpose()
+                    .map_err(|err| DecodeError::MalformedValue {
+                        key: KeysToTemporalAction::J,
+                        reason: format!("{:#}", err),
+                    })?,
+                expires_at,
+            })
+        }
+        TemporalInteractionDiscriminants::Signal => TemporalInteraction::Signal(SignalTemporal {
+            namespace,
+            task_queue,
+            workflow_id: KeysToTemporalAction::W.get_value(&mut encoder_map).ok(),
+            run_id: KeysToTemporalAction::R.get_value(&mut encoder_map).ok(),
+            signal_name: KeysToTemporalAction::S.get_value(&mut encoder_map)?,
+            input: None,
+            expires_at,
+            ..Default::default()
+        }),
+        TemporalInteractionDiscriminants::Query => TemporalInteraction::Query(QueryTemporal {
+            namespace,
+            task_queue,
+            workflow_id: KeysToTemporalAction::W.get_value(&mut encoder_map).ok(),
+            run_id: KeysToTemporalAction::R.get_value(&mut encoder_map).ok(),
+            query_type: KeysToTemporalAction::Q.get_value(&mut encoder_map)?,
+            query_args: KeysToTemporalAction::U
+                .get_value(&mut encoder_map)
+                .ok()
+                .map(|query_args| serde_json::from_str(&query_args))
+                .transpose()
+                .map_err(|err| DecodeError::MalformedValue {
+                    key: KeysToTemporalAction::U,
+                    reason: format!("query args are not a JSON array: {}", err),
+                })?,
+            expires_at,
+        }),
+        TemporalInteractionDiscriminants::SignalWithStart => {
+            TemporalInteraction::SignalWithStart(SignalWithStartTemporal {
+                namespace,
+                task_queue,
+                workflow_id: KeysToTemporalAction::W.get_value(&mut encoder_map)?,
+                workflow_type: KeysToTemporalAction::Y.get_value(&mut encoder_map)?,
+                args: decoded_execute_args(&mut encoder_map)?,
+                signal_name: KeysToTemporalAction::S.get_value(&mut encoder_map)?,
+                expires_at,
+                ..Default::default()
+            })
+        }
+        TemporalInteractionDiscriminants::Cancel => TemporalInteraction::Cancel(CancelTemporal {
+            namespace,
+            task_queue,
+            workflow_id: KeysToTemporalAction::W.get_value(&mut encoder_map)?,
+            run_id: KeysToTemporalAction::R.get_value(&mut encoder_map).ok(),
+            reason: KeysToTemporalAction::M.get_value(&mut encoder_map).ok(),
+            expires_at,
+            ..Default::default()
+        }),
+        TemporalInteractionDiscriminants::Terminate => {
+            TemporalInteraction::Terminate(TerminateTemporal {
+                namespace,
+                task_queue,
+                workflow_id: KeysToTemporalAction::W.get_value(&mut encoder_map)?,
+                run_id: KeysToTemporalAction::R.get_value(&mut encoder_map).ok(),
+                reason: KeysToTemporalAction::M.get_value(&mut encoder_map).ok(),
+                details: KeysToTemporalAction::V
+                    .get_value(&mut encoder_map)
+                    .ok()
+                    .map(|details| serde_json::from_str(&details))
+                    .transpose()
+                    .map_err(|err| DecodeError::MalformedValue {
+                        key: KeysToTemporalAction::V,
+                        reason: format!("terminate details are not a JSON array: {}", err),
+                    })?,
+                expires_at,
+                ..Default::default()
+            })
+        }
+        TemporalInteractionDiscriminants::Update => TemporalInteraction::Update(UpdateTemporal {
+            namespace,
+            task_queue,
+            workflow_id: KeysToTemporalAction::W.get_value(&mut encoder_map)?,
+            run_id: KeysToTemporalAction::R.get_value(&mut encoder_map).ok(),
+            update_name: KeysToTemporalAction::P.get_value(&mut encoder_map)?,
+            args: decoded_execute_args(&mut encoder_map)?,
+            wait_stage: KeysToTemporalAction::G
+                .get_value(&mut encoder_map)
+                .ok()
+                .map(|stage| UpdateWaitStage::from_str(&stage))
+                .transpose()
+                .map_err(|_| DecodeError::MalformedValue {
+                    key: KeysToTemporalAction::G,
+                    reason: "wait stage is not Accepted or Completed".into(),
+                })?,
+            expires_at,
+            ..Default::default()
+        }),
+        TemporalInteractionDiscriminants::CompleteActivity => {
+            let complete = CompleteActivityTemporal {
+                namespace,
+                task_queue,
+                task_token: KeysToTemporalAction::O.get_value(&mut encoder_map).ok(),
+                workflow_id: KeysToTemporalAction::W.get_value(&mut encoder_map).ok(),
+                run_id: KeysToTemporalAction::R.get_value(&mut encoder_map).ok(),
+                activity_id: KeysToTemporalAction::I.get_value(&mut encoder_map).ok(),
+                outcome: KeysToTemporalAction::F
+                    .get_value(&mut encoder_map)
+                    .ok()
+                    .map(|outcome| ActivityOutcome::from_str(&outcome))
+                    .transpose()
+                    .map_err(|_| DecodeError::MalformedValue {
+                        key: KeysToTemporalAction::F,
+                        reason: "outcome is not Completed, Failed or Canceled".into(),
+                    })?,
+                reason: KeysToTemporalAction::M.get_value(&mut encoder_map).ok(),
+                expires_at,
+                ..Default::default()
+            };
+            if complete.task_token.is_none() && !complete.has_activity_id() {
+                return Err(DecodeError::MissingKey(KeysToTemporalAction::O));
+            }
+            TemporalInteraction::CompleteActivity(complete)
+        }
+    };
+
+    Ok(temporal_event_without_payload)
+}
+
+/// the optional `A` key's workflow args
+fn decoded_execute_args(
+    encoder_map: &mut HashMap<KeysToTemporalAction, String>,
+) -> Result<Option<Vec<serde_json::Value>>, DecodeError> {
+    KeysToTemporalAction::A
+        .get_value(encoder_map)
+        .ok()
+        .map(|args| decode_execute_args(&args))
+        .transpose()
+        .map_err(|err| DecodeError::MalformedValue {
+            key: KeysToTemporalAction::A,
+            reason: format!("{:#}", err),
+        })
+}
+
+#[derive(EnumIter, EnumString, Display, PartialEq, Eq, Hash, Debug, Clone, Copy)]
+pub enum KeysToTemporalAction {
+    /// Temporal Event Type (signal, query, execute, signal with start, cancel, terminate, update, complete activity)
+    E,
+    /// expiry Deadline as a unix timestamp (seconds), optional
+    D,
+    /// Workflow_id
+    W,
+    /// Namespace
+    N,
+    /// Taskqueue
+    T,
+    /// workflow tYpe aka fn name
+    Y,
+    /// execute, signal with start & update Args, deflated JSON as base64url
+    A,
+    /// workflow Run_id
+    R,
+    /// Signal name
+    S,
+    /// Query type
+    Q,
+    /// qUery args
+    U,
+    /// cancel, terminate or activity failure reason Message
+    M,
+    /// terminate details Values, a JSON array
+    V,
+    /// uPdate name
+    P,
+    /// update wait staGe, `Accepted` or `Completed`
+    G,
+    /// activity task tOken, base64url
+    O,
+    /// activity Id, to complete the activity without its task token
+    I,
+    /// how the activity Finishes, `Completed`, `Failed` or `Canceled`
+    F,
+    /// execute start options, deflated Json as base64url
+    J,
+    /// signing Key id
+    K,
+    /// HMAC signature, always the last kv pair
+    H,
+    /// Ciphertext of the encrypted kv pairs
+    C,
+}
+
+impl KeysToTemporalAction {
+    /// `key:value` with any delimiters in the value escaped
+    pub fn to_kv(&self, value: &str) -> String {
+        format!("{}{}{}", self, TEMPORAL_KEY_DELIMITER, escape_value(value))
+    }
+
+    /// name of the TemporalInteraction field the key holds
+    pub fn field_name(&self) -> &'static str {
+        match self {
+            KeysToTemporalAction::E => "type",
+            KeysToTemporalAction::D => "expires_at",
+            KeysToTemporalAction::W => "workflow_id",
+            KeysToTemporalAction::N => "namespace",
+            KeysToTemporalAction::T => "task_queue",
+            KeysToTemporalAction::Y => "workflow_type",
+            KeysToTemporalAction::A => "args",
+            KeysToTemporalAction::R => "run_id",
+            KeysToTemporalAction::S => "signal_name",
+            KeysToTemporalAction::Q => "query_type",
+            KeysToTemporalAction::U => "query_args",
+            KeysToTemporalAction::M => "reason",
+            KeysToTemporalAction::V => "details",
+            KeysToTemporalAction::P => "update_name",
+            KeysToTemporalAction::G => "wait_stage",
+            KeysToTemporalAction::O => "task_token",
+            KeysToTemporalAction::I => "activity_id",
+            KeysToTemporalAction::F => "outcome",
+            KeysToTemporalAction::J => "options",
+            KeysToTemporalAction::K => "signing key id",
+            KeysToTemporalAction::H => "signature",
+            KeysToTemporalAction::C => "ciphertext",
+        }
+    }
+
+    pub fn get_value(
+        &self,
+        encoder_map: &mut HashMap<Self, String>,
+    ) -> Result<String, DecodeError> {
+        encoder_map
+            .remove(self)
+            .ok_or(DecodeError::MissingKey(*self))
+    }
+}
+
+/// replace delimiters with their `ESCAPED_CHARS` form
+fn escape_value(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for char in value.chars() {
+        match ESCAPED_CHARS
+            .iter()
+            .find(|(escaped_char, _)| *escaped_char == char)
+        {
+            Some((_, escape_sequence)) => escaped.push_str(escape_sequence),
+            None => escaped.push(char),
+        }
+    }
+    escaped
+}
+
+/// reverse of `escape_value`. Unknown escape sequences are kept as-is,
+/// so strings encoded before values were escaped still decode.
+fn unescape_value(escaped: &str) -> String {
+    let mut value = String::with_capacity(escaped.len());
+    let mut rest = escaped;
+    while let Some(escape_index) = rest.find(ESCAPE_CHAR) {
+        value.push_str(&rest[..escape_index]);
+        rest = &rest[escape_index..];
+
+        match ESCAPED_CHARS
+            .iter()
+            .find(|(_, escape_sequence)| rest.starts_with(escape_sequence))
+        {
+            Some((char, escape_sequence)) => {
+                value.push(*char);
+                rest = &rest[escape_sequence.len()..];
+            }
+            None => {
+                value.push(ESCAPE_CHAR);
+                rest = &rest[ESCAPE_CHAR.len_utf8()..];
+            }
+        }
+    }
+    value.push_str(rest);
+    value
+}
+
+/// seconds since the unix epoch
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+fn unix_now() -> Result<u64> {
+    Ok(std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .context("system clock is before the unix epoch")?
+        .as_secs())
+}
+
+/// seconds since the unix epoch, from the JS host since `SystemTime::now` panics without an OS
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+fn unix_now() -> Result<u64> {
+    Ok((js_sys::Date::now() / 1000.0) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+    use serde_json::json;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    use super::*;
+    use crate::{SignalTemporal, TemporalInteraction};
+
+    fn build_mock_signal() -> TemporalInteraction {
+        TemporalInteraction::Signal(build_mock_signal_struct())
+    }
+
+    fn build_mock_signal_struct() -> SignalTemporal {
+        SignalTemporal {
+            namespace: "test-namespace".into(),
+            task_queue: "test-task-queue-rs".into(),
+            workflow_id: Some("some-super-long-uuid-string".into()),
+            run_id: Some("some-equally-long-uuid-string".into()),
+            signal_name: "signal_name_thats_defined_in_workflow".into(),
+            input: None,
+            ..Default::default()
+        }
+    }
+
+    fn build_mock_signal_with_uuids() -> TemporalInteraction {
+        TemporalInteraction::Signal(SignalTemporal {
+            workflow_id: Some("3f2b8c1e-9d4a-4e6b-8f0c-2a7d5e9b1c34".into()),
+            run_id: Some("b7e4a2d9-1c6f-4a8e-9b3d-5f0e7c2a8d16".into()),
+            ..build_mock_signal_struct()
+        })
+    }
+
+    fn build_test_signing_keys() -> SigningKeys {
+        SigningKeys::default()
+            .with_key("1", "test-secret-that-is-not-used-anywhere-else")
+            .unwrap()
+    }
+
+    fn build_mock_wf_exec() -> TemporalInteraction {
+        TemporalInteraction::Execute(build_mock_wf_exec_struct())
+    }
+
+    fn build_mock_wf_exec_struct() -> ExecuteTemporalWorkflow {
+        ExecuteTemporalWorkflow {
+            namespace: "test-namespace".into(),
+            task_queue: "test-task-queue-rs".into(),
+            workflow_id: "some-super-long-uuid-string".into(),
+            workflow_type: "some-wf-function-name".into(),
+            args: Some(vec![json!({
+                    "arg1" : "value1"
+            })]),
+            options: None,
+            expires_at: None,
+        }
+    }
+
+    fn build_mock_query() -> TemporalInteraction {
+        TemporalInteraction::Query(QueryTemporal {
+            namespace: "test-namespace".into(),
+            task_queue: "test-task-queue-rs".into(),
+            workflow_id: Some("some-super-long-uuid-string".into()),
+            run_id: Some("some-equally-long-uuid-string".into()),
+            query_type: "query_type_thats_defined_in_workflow".into(),
+            query_args: Some(vec![json!({"arg1": "value1"}), json!(["a", "b"]), json!(2)]),
+            expires_at: None,
+        })
+    }
+
+    fn build_mock_signal_with_start() -> TemporalInteraction {
+        TemporalInteraction::SignalWithStart(build_mock_signal_with_start_struct())
+    }
+
+    fn build_mock_signal_with_start_struct() -> SignalWithStartTemporal {
+        SignalWithStartTemporal {
+            namespace: "test-namespace".into(),
+            task_queue: "test-task-queue-rs".into(),
+            workflow_id: "user-U024BE7LH".into(),
+            workflow_type: "some-wf-function-name".into(),
+            args: Some(vec![json!({"user": "U024BE7LH"})]),
+            signal_name: "signal_name_thats_defined_in_workflow".into(),
+            ..Default::default()
+        }
+    }
+
+    fn build_mock_cancel() -> TemporalInteraction {
+        TemporalInteraction::Cancel(CancelTemporal {
+            namespace: "test-namespace".into(),
+            task_queue: "test-task-queue-rs".into(),
+            workflow_id: "some-super-long-uuid-string".into(),
+            run_id: Some("some-equally-long-uuid-string".into()),
+            reason: Some("aborted from slack".into()),
+            ..Default::default()
+        })
+    }
+
+    fn build_mock_terminate_struct() -> TerminateTemporal {
+        TerminateTemporal {
+            namespace: "test-namespace".into(),
+            task_queue: "test-task-queue-rs".into(),
+            workflow_id: "some-super-long-uuid-string".into(),
+            run_id: None,
+            reason: Some("stuck, see INC-1234".into()),
+            details: Some(vec![json!({"approved_by": "U024BE7LH"})]),
+            ..Default::default()
+        }
+    }
+
+    fn build_mock_update_struct() -> UpdateTemporal {
+        UpdateTemporal {
+            namespace: "test-namespace".into(),
+            task_queue: "test-task-queue-rs".into(),
+            workflow_id: "some-super-long-uuid-string".into(),
+            run_id: None,
+            update_name: "update_name_thats_defined_in_workflow".into(),
+            args: Some(vec![json!({"form": "access-request"})]),
+            wait_stage: Some(UpdateWaitStage::Completed),
+            ..Default::default()
+        }
+    }
+
+    fn build_mock_complete_activity_struct() -> CompleteActivityTemporal {
+        CompleteActivityTemporal {
+            namespace: "test-namespace".into(),
+            task_queue: "test-task-queue-rs".into(),
+            workflow_id: Some("some-super-long-uuid-string".into()),
+            activity_id: Some("7".into()),
+            outcome: Some(ActivityOutcome::Failed),
+            reason: Some("rejected in slack".into()),
+            ..Default::default()
+        }
+    }
+
+    fn unix_now() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+    }
+
+    #[test]
+    fn test_encode_slack_callback_id() {
+        let temporal_interaction = build_mock_signal();
+        let encoder = Encoder::A;
+        let callback_id = encoder.encode(temporal_interaction).unwrap();
+
+        dbg!(&callback_id);
+        dbg!(&callback_id.len()); // 143 as ordered csv, 153 as kv_pairs
+    }
+
+    #[test]
+    fn test_decode_slack_callback_id() {
+        let temporal_interaction = build_mock_signal();
+
+        let encoder = Encoder::A;
+        let callback_id = encoder.encode(temporal_interaction).unwrap();
+
+        let parsed = Encoder::decode(&callback_id).unwrap();
+
+        assert_eq!(build_mock_signal(), parsed)
+    }
+
+    #[test]
+    fn test_encode_decode_all_encoder_versions() {
+        let signing_keys = build_test_signing_keys();
+
+        for encoder_version in Encoder::iter() {
+            for temporal_event in [
+                build_mock_signal(),
+                build_mock_wf_exec(),
+                build_mock_query(),
+                build_mock_signal_with_start(),
+                build_mock_cancel(),
+                TemporalInteraction::Terminate(build_mock_terminate_struct()),
+                TemporalInteraction::Update(build_mock_update_struct()),
+                TemporalInteraction::CompleteActivity(build_mock_complete_activity_struct()),
+            ] {
+                // every event type round trips, including execute args
+                let expected_output = temporal_event.to_owned();
+
+                // as struct
+                let callback_id = encoder_version
+                    .encode_with_keys(temporal_event.clone(), &signing_keys)
+                    .unwrap();
+                let parsed = Encoder::decode_with_keys(&callback_id, &signing_keys)
+                    .unwrap_or_else(|err| {
+                        panic!("failed to decode. version {encoder_version} for string {callback_id}: {err}")
+                    });
+                assert_eq!(expected_output, parsed);
+
+                // as json string
+                let as_string = serde_json::to_string(&temporal_event).unwrap();
+                let callback_id = Encoder::encode_default_from_json_string(&as_string).unwrap();
+                let parsed = Encoder::decode(&callback_id).unwrap_or_else(|err| {
+                    panic!("failed to decode. version {encoder_version} for string {callback_id}: {err}")
+                });
+                assert_eq!(expected_output, parsed)
+            }
+        }
+    }
+
+    #[test]
+    fn test_signed_encoder_rejects_tampered_string() {
+        let signing_keys = build_test_signing_keys();
+        let callback_id = Encoder::S
+            .encode_with_keys(build_mock_signal(), &signing_keys)
+            .unwrap();
+
+        let tampered = callback_id.replace("W:some-super-long-uuid-string", "W:another-workflow");
+        assert!(Encoder::decode_with_keys(&tampered, &signing_keys).is_err());
+
+        let unsigned = callback_id.split(",H:").next().unwrap();
+        assert!(Encoder::decode_with_keys(unsigned, &signing_keys).is_err());
+
+        let wrong_key = SigningKeys::default()
+            .with_key("1", "a-different-secret")
+            .unwrap();
+        assert!(Encoder::decode_with_keys(&callback_id, &wrong_key).is_err());
+    }
+
+    #[test]
+    fn test_signed_encoder_user_data_is_not_signed() {
+        let signing_keys = build_test_signing_keys();
+        let callback_id = Encoder::S
+            .encode_with_keys(build_mock_signal(), &signing_keys)
+            .unwrap();
+
+        let with_user_data = format!("{callback_id}{ENCODER_SECTION_DELIMITER}approve-button");
+        let parsed = Encoder::decode_with_keys(&with_user_data, &signing_keys).unwrap();
+
+        assert_eq!(build_mock_signal(), parsed);
+        assert!(with_user_data.len() <= 255, "{}", with_user_data.len());
+    }
+
+    #[test]
+    fn test_signed_encoder_key_rotation() {
+        let old_keys = build_test_signing_keys();
+        let signed_with_old_key = Encoder::S
+            .encode_with_keys(build_mock_signal(), &old_keys)
+            .unwrap();
+
+        // new key signs, the old key still verifies strings already sent out
+        let rotated_keys = SigningKeys::from_key_list(
+            "2:a-brand-new-secret,1:test-secret-that-is-not-used-anywhere-else",
+        )
+        .unwrap();
+        assert_eq!(rotated_keys.active_key_id(), Some("2"));
+
+        let signed_with_new_key = Encoder::S
+            .encode_with_keys(build_mock_signal(), &rotated_keys)
+            .unwrap();
+        assert!(signed_with_new_key.contains(",K:2,"));
+
+        for callback_id in [&signed_with_old_key, &signed_with_new_key] {
+            assert_eq!(
+                build_mock_signal(),
+                Encoder::decode_with_keys(callback_id, &rotated_keys).unwrap()
+            );
+        }
+
+        // once the old key is removed, its strings no longer decode
+        let new_key_only = SigningKeys::from_key_list("2:a-brand-new-secret").unwrap();
+        assert!(Encoder::decode_with_keys(&signed_with_old_key, &new_key_only).is_err());
+    }
+
+    #[test]
+    fn test_require_signature() {
+        let signing_keys = build_test_signing_keys().with_require_signature(true);
+
+        let unsigned = Encoder::A.encode(build_mock_signal()).unwrap();
+        assert!(Encoder::decode_with_keys(&unsigned, &signing_keys).is_err());
+
+        let signed = Encoder::S
+            .encode_with_keys(build_mock_signal(), &signing_keys)
+            .unwrap();
+        assert!(Encoder::decode_with_keys(&signed, &signing_keys).is_ok());
+    }
+
+    #[test]
+    fn test_signed_encoder_without_keys() {
+        assert!(Encoder::S
+            .encode_with_keys(build_mock_signal(), &SigningKeys::default())
+            .is_err());
+    }
+
+    #[test]
+    fn test_encrypted_encoder_hides_temporal_info() {
+        let signing_keys = build_test_signing_keys();
+
+        for temporal_event in [build_mock_signal(), build_mock_wf_exec()] {
+            let callback_id = Encoder::X
+                .encode_with_keys(temporal_event.clone(), &signing_keys)
+                .unwrap();
+
+            assert!(callback_id.starts_with("X~K:1,C:"));
+            for plain_text in [
+                &temporal_event.namespace(),
+                &temporal_event.task_queue(),
+                &temporal_event.workflow_id(),
+            ] {
+                assert!(!callback_id.contains(plain_text.as_str()));
+            }
+            assert!(callback_id.len() <= 255, "{}", callback_id.len());
+
+            // a random nonce makes every encoding unique
+            let encoded_again = Encoder::X
+                .encode_with_keys(temporal_event, &signing_keys)
+                .unwrap();
+            assert_ne!(callback_id, encoded_again);
+        }
+    }
+
+    #[test]
+    fn test_encrypted_encoder_rejects_tampering() {
+        let signing_keys = build_test_signing_keys();
+        let callback_id = Encoder::X
+            .encode_with_keys(build_mock_signal(), &signing_keys)
+            .unwrap();
+
+        // flip one character of the ciphertext
+        let (header, ciphertext) = callback_id.split_once(",C:").unwrap();
+        let flipped = if ciphertext.starts_with('A') {
+            "B"
+        } else {
+            "A"
+        };
+        let tampered = format!("{header},C:{flipped}{}", &ciphertext[1..]);
+        assert!(Encoder::decode_with_keys(&tampered, &signing_keys).is_err());
+
+        // the key id is authenticated too
+        let rotated_keys = build_test_signing_keys()
+            .with_key("2", "test-secret-that-is-not-used-anywhere-else")
+            .unwrap();
+        let swapped_key_id = callback_id.replace("K:1", "K:2");
+        assert!(Encoder::decode_with_keys(&swapped_key_id, &rotated_keys).is_err());
+
+        let wrong_key = SigningKeys::default()
+            .with_key("1", "a-different-secret")
+            .unwrap();
+        assert!(Encoder::decode_with_keys(&callback_id, &wrong_key).is_err());
+
+        // user data still rides along after the encrypted section
+        let with_user_data = format!("{callback_id}{ENCODER_SECTION_DELIMITER}approve-button");
+        assert_eq!(
+            build_mock_signal(),
+            Encoder::decode_with_keys(&with_user_data, &signing_keys).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_expiring_callback_id() {
+        let signing_keys = build_test_signing_keys();
+        let expires_at = unix_now() + 60 * 60;
+
+        for encoder_version in Encoder::iter() {
+            let temporal_event = TemporalInteraction::Signal(SignalTemporal {
+                expires_at: Some(expires_at),
+                ..build_mock_signal_struct()
+            });
+
+            let callback_id = encoder_version
+                .encode_with_keys(temporal_event.clone(), &signing_keys)
+                .unwrap();
+            if encoder_version == Encoder::A {
+                assert!(callback_id.starts_with(&format!("A~E:Signal,D:{expires_at},")));
+            }
+
+            let parsed = Encoder::decode_with_keys(&callback_id, &signing_keys).unwrap();
+            assert_eq!(temporal_event, parsed);
+        }
+    }
+
+    #[test]
+    fn test_expired_callback_id() {
+        let signing_keys = build_test_signing_keys();
+        let expired_at = unix_now() - 1;
+
+        for encoder_version in Encoder::iter() {
+            let callback_id = encoder_version
+                .encode_with_keys(
+                    TemporalInteraction::Signal(SignalTemporal {
+                        expires_at: Some(expired_at),
+                        ..build_mock_signal_struct()
+                    }),
+                    &signing_keys,
+                )
+                .unwrap();
+
+            let err = Encoder::decode_with_keys(&callback_id, &signing_keys).unwrap_err();
+            assert!(
+                matches!(err, DecodeError::Expired { expired_at: at } if at == expired_at),
+                "version {encoder_version}: {err}"
+            );
+            assert_eq!(err.code(), "EXPIRED");
+        }
+    }
+
+    #[test]
+    fn test_signed_encoder_rejects_extended_deadline() {
+        let signing_keys = build_test_signing_keys();
+        let expired_at = unix_now() - 1;
+        let callback_id = Encoder::S
+            .encode_with_keys(
+                TemporalInteraction::Signal(SignalTemporal {
+                    expires_at: Some(expired_at),
+                    ..build_mock_signal_struct()
+                }),
+                &signing_keys,
+            )
+            .unwrap();
+
+        let extended = callback_id.replace(
+            &format!("D:{expired_at}"),
+            &format!("D:{}", expired_at + 60 * 60),
+        );
+        let err = Encoder::decode_with_keys(&extended, &signing_keys).unwrap_err();
+        assert!(matches!(err, DecodeError::BadSignature(_)), "{err}");
+    }
+
+    #[test]
+    fn test_compact_encoder_size() {
+        for temporal_event in [build_mock_signal(), build_mock_signal_with_uuids()] {
+            let plain = Encoder::A.encode(temporal_event.clone()).unwrap();
+            let compact = Encoder::B.encode(temporal_event.clone()).unwrap();
+
+            // 150 vs 146 chars with text ids, 166 vs 129 with UUIDs
+            assert!(compact.len() <= plain.len());
+            assert_eq!(temporal_event, Encoder::decode(&compact).unwrap());
+        }
+
+        // each UUID shrinks from 36 chars to ~22 chars
+        let plain = Encoder::A.encode(build_mock_signal_with_uuids()).unwrap();
+        let compact = Encoder::B.encode(build_mock_signal_with_uuids()).unwrap();
+        assert!(
+            compact.len() + 30 <= plain.len(),
+            "{} vs {}",
+            compact.len(),
+            plain.len()
+        );
+    }
+
+    #[test]
+    fn test_compact_encoder_non_uuid_ids() {
+        for workflow_id in [
+            "3F2B8C1E-9D4A-4E6B-8F0C-2A7D5E9B1C34",
+            "3f2b8c1e9d4a4e6b8f0c2a7d5e9b1c34",
+            "3f2b8c1e-9d4a-4e6b-8f0c-2a7d5e9b1c3z",
+            "orders.v2/ünïcode",
+        ] {
+            let temporal_event = TemporalInteraction::Signal(SignalTemporal {
+                workflow_id: Some(workflow_id.into()),
+                ..build_mock_signal_struct()
+            });
+            let compact = Encoder::B.encode(temporal_event.clone()).unwrap();
+
+            assert_eq!(temporal_event, Encoder::decode(&compact).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_compact_encoder_rejects_truncated_string() {
+        let compact = Encoder::B.encode(build_mock_signal_with_uuids()).unwrap();
+
+        assert!(Encoder::decode(&compact[..compact.len() - 4]).is_err());
+        assert!(Encoder::decode("B~not base64").is_err());
+    }
+
+    #[test]
+    fn test_delimiters_in_values() {
+        let temporal_event = TemporalInteraction::Signal(SignalTemporal {
+            workflow_id: Some("deploy:prod,eu".into()),
+            run_id: Some("100%2C~done".into()),
+            ..build_mock_signal_struct()
+        });
+
+        let callback_id = Encoder::A.encode(temporal_event.clone()).unwrap();
+        assert!(callback_id.contains(",W:deploy%3Aprod%2Ceu,"));
+        assert!(callback_id.contains(",R:100%252C%7Edone,"));
+
+        let with_user_data = format!("{callback_id}{ENCODER_SECTION_DELIMITER}user:data,~");
+        assert_eq!(temporal_event, Encoder::decode(&with_user_data).unwrap());
+    }
+
+    #[test]
+    fn test_unescaped_legacy_values() {
+        // strings encoded before escaping existed may hold a bare `%`
+        let callback_id =
+            "A~E:Signal,W:50%-off,N:test-namespace,T:test-task-queue-rs,R:run%,S:signal";
+        let parsed = Encoder::decode(callback_id).unwrap();
+
+        assert_eq!(parsed.workflow_id(), "50%-off");
+        assert!(matches!(parsed, TemporalInteraction::Signal(signal) if signal.run_id() == "run%"));
+    }
+
+    proptest! {
+        #[test]
+        fn test_encode_decode_arbitrary_strings(
+            namespace in any::<String>(),
+            task_queue in any::<String>(),
+            workflow_id in any::<String>(),
+            run_id in any::<String>(),
+            name in any::<String>(),
+        ) {
+            let signing_keys = build_test_signing_keys();
+            let signal = TemporalInteraction::Signal(SignalTemporal {
+                namespace: namespace.clone(),
+                task_queue: task_queue.clone(),
+                workflow_id: Some(workflow_id.clone()),
+                run_id: Some(run_id),
+                signal_name: name.clone(),
+                ..Default::default()
+            });
+            let exec = TemporalInteraction::Execute(ExecuteTemporalWorkflow {
+                namespace: namespace.clone(),
+                task_queue: task_queue.clone(),
+                workflow_id: workflow_id.clone(),
+                workflow_type: name.clone(),
+                ..Default::default()
+            });
+            let query = TemporalInteraction::Query(QueryTemporal {
+                namespace,
+                task_queue,
+                workflow_id: Some(workflow_id),
+                run_id: None,
+                query_type: name.clone(),
+                query_args: Some(vec![json!(name)]),
+                expires_at: None,
+            });
+
+            for encoder_version in Encoder::iter() {
+                for temporal_event in [&signal, &exec, &query] {
+                    let callback_id = encoder_version
+                        .encode_with_keys(temporal_event.clone(), &signing_keys)
+                        .unwrap();
+                    let with_user_data =
+                        format!("{callback_id}{ENCODER_SECTION_DELIMITER}user:data,~");
+
+                    for encoded in [&callback_id, &with_user_data] {
+                        let parsed = Encoder::decode_with_keys(encoded, &signing_keys);
+                        prop_assert!(parsed.is_ok(), "version {}: {:?}", encoder_version, parsed);
+                        prop_assert_eq!(temporal_event, &parsed.unwrap());
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_query_optional_fields() {
+        let query = TemporalInteraction::Query(QueryTemporal {
+            namespace: "test-namespace".into(),
+            task_queue: "test-task-queue-rs".into(),
+            workflow_id: Some("some-super-long-uuid-string".into()),
+            run_id: None,
+            query_type: "query_type_thats_defined_in_workflow".into(),
+            query_args: None,
+            expires_at: None,
+        });
+
+        let callback_id = Encoder::A.encode(query.clone()).unwrap();
+        assert_eq!(
+            callback_id,
+            "A~E:Query,W:some-super-long-uuid-string,N:test-namespace,T:test-task-queue-rs,Q:query_type_thats_defined_in_workflow"
+        );
+        assert_eq!(query, Encoder::decode(&callback_id).unwrap());
+    }
+
+    #[test]
+    fn test_execute_start_options() {
+        let exec_with_options: TemporalInteraction = serde_json::from_value(json!({
+            "type": "Execute",
+            "namespace": "test-namespace",
+            "task_queue": "test-task-queue-rs",
+            "workflow_id": "some-super-long-uuid-string",
+            "workflow_type": "some-wf-function-name",
+            "options": {
+                "execution_timeout_secs": 86400,
+                "run_timeout_secs": 3600,
+                "id_reuse_policy": "RejectDuplicate",
+                "retry_policy": {"backoff_coefficient": 1.5, "maximum_attempts": 3},
+                "cron_schedule": "0 9 * * MON",
+                "memo": {"requested_by": "U024BE7LH"},
+                "search_attributes": {"CustomKeywordField": "access-request"}
+            }
+        }))
+        .unwrap();
+        let options = match &exec_with_options {
+            TemporalInteraction::Execute(execute) => execute.options.as_ref().unwrap(),
+            _ => panic!("not an execute: {:?}", exec_with_options),
+        };
+        assert_eq!(options.run_timeout_secs, Some(3600));
+        assert_eq!(
+            options.id_reuse_policy,
+            Some(WorkflowIdReusePolicy::RejectDuplicate)
+        );
+
+        // every option is too much for a callback_id, but a few fit since unset fields aren't encoded
+        let err = Encoder::A.encode(exec_with_options).unwrap_err();
+        assert!(err.to_string().contains("over the limit"), "{}", err);
+
+        let exec_with_options = TemporalInteraction::Execute(ExecuteTemporalWorkflow {
+            options: Some(WorkflowStartOptions {
+                run_timeout_secs: Some(3600),
+                id_reuse_policy: Some(WorkflowIdReusePolicy::RejectDuplicate),
+                ..Default::default()
+            }),
+            ..build_mock_wf_exec_struct()
+        });
+        let signing_keys = build_test_signing_keys();
+        for encoder_version in Encoder::iter() {
+            let callback_id = encoder_version
+                .encode_with_keys(exec_with_options.clone(), &signing_keys)
+                .unwrap();
+            assert_eq!(
+                exec_with_options,
+                Encoder::decode_with_keys(&callback_id, &signing_keys).unwrap()
+            );
+        }
+
+        let callback_id = Encoder::A.encode(build_mock_wf_exec()).unwrap();
+        let err = Encoder::decode(&format!("{},J:bm90IGRlZmxhdGVk", callback_id)).unwrap_err();
+        assert!(matches!(
+            err,
+            DecodeError::MalformedValue {
+                key: KeysToTemporalAction::J,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_execute_args_length_budget() {
+        let small_args = build_mock_wf_exec();
+        let callback_id = Encoder::A.encode(small_args.clone()).unwrap();
+        assert!(callback_id.len() <= 255, "{}", callback_id.len());
+        assert_eq!(small_args, Encoder::decode(&callback_id).unwrap());
+
+        // repetitive args compress well below their json length
+        let repetitive_args = TemporalInteraction::Execute(ExecuteTemporalWorkflow {
+            args: Some(vec![json!({ "approvers": vec!["team-a"; 20] })]),
+            ..build_mock_wf_exec_struct()
+        });
+        let callback_id = Encoder::A.encode(repetitive_args.clone()).unwrap();
+        assert_eq!(repetitive_args, Encoder::decode(&callback_id).unwrap());
+
+        // random text doesn't compress
+        let random_text = (0..8)
+            .map(|message| {
+                build_test_signing_keys()
+                    .sign("1", &message.to_string())
+                    .unwrap()
+            })
+            .collect::<String>();
+        let random_args = TemporalInteraction::Execute(ExecuteTemporalWorkflow {
+            args: Some(vec![json!(random_text)]),
+            ..build_mock_wf_exec_struct()
+        });
+        let err = Encoder::A.encode(random_args).unwrap_err();
+        assert!(err.to_string().contains("over the limit"), "{}", err);
+    }
+
+    #[test]
+    fn test_signal_with_start_keys() {
+        let signal_with_start = TemporalInteraction::SignalWithStart(SignalWithStartTemporal {
+            args: None,
+            ..build_mock_signal_with_start_struct()
+        });
+
+        let callback_id = Encoder::A.encode(signal_with_start.clone()).unwrap();
+        assert_eq!(
+            callback_id,
+            "A~E:SignalWithStart,W:user-U024BE7LH,N:test-namespace,T:test-task-queue-rs,Y:some-wf-function-name,S:signal_name_thats_defined_in_workflow"
+        );
+        assert_eq!(signal_with_start, Encoder::decode(&callback_id).unwrap());
+
+        // the workflow type is needed in case it has to be started
+        let err =
+            Encoder::decode(&callback_id.replace(",Y:some-wf-function-name", "")).unwrap_err();
+        assert!(matches!(
+            err,
+            DecodeError::MissingKey(KeysToTemporalAction::Y)
+        ));
+    }
+
+    #[test]
+    fn test_cancel_and_terminate_keys() {
+        let callback_id = Encoder::A.encode(build_mock_cancel()).unwrap();
+        assert_eq!(
+            callback_id,
+            "A~E:Cancel,W:some-super-long-uuid-string,N:test-namespace,T:test-task-queue-rs,R:some-equally-long-uuid-string,M:aborted from slack"
+        );
+
+        // the reason is optional, and escaped like any other value
+        let terminate = TemporalInteraction::Terminate(TerminateTemporal {
+            reason: Some("stuck, retry later".into()),
+            details: None,
+            ..build_mock_terminate_struct()
+        });
+        let callback_id = Encoder::A.encode(terminate.clone()).unwrap();
+        assert_eq!(
+            callback_id,
+            "A~E:Terminate,W:some-super-long-uuid-string,N:test-namespace,T:test-task-queue-rs,M:stuck%2C retry later"
+        );
+        assert_eq!(terminate, Encoder::decode(&callback_id).unwrap());
+
+        let err = Encoder::decode(&format!("{},V:not-json", callback_id)).unwrap_err();
+        assert!(matches!(
+            err,
+            DecodeError::MalformedValue {
+                key: KeysToTemporalAction::V,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_update_keys() {
+        let update = TemporalInteraction::Update(UpdateTemporal {
+            args: None,
+            wait_stage: None,
+            ..build_mock_update_struct()
+        });
+        let callback_id = Encoder::A.encode(update.clone()).unwrap();
+        assert_eq!(
+            callback_id,
+            "A~E:Update,W:some-super-long-uuid-string,N:test-namespace,T:test-task-queue-rs,P:update_name_thats_defined_in_workflow"
+        );
+        assert_eq!(update, Encoder::decode(&callback_id).unwrap());
+
+        let accepted = format!("{},G:Accepted", callback_id);
+        assert_eq!(
+            Encoder::decode(&accepted).unwrap(),
+            TemporalInteraction::Update(UpdateTemporal {
+                args: None,
+                wait_stage: Some(UpdateWaitStage::Accepted),
+                ..build_mock_update_struct()
+            })
+        );
+
+        let err = Encoder::decode(&format!("{},G:Admitted", callback_id)).unwrap_err();
+        assert!(matches!(
+            err,
+            DecodeError::MalformedValue {
+                key: KeysToTemporalAction::G,
+                ..
+            }
+        ));
+
+        let err =
+            Encoder::decode(&callback_id.replace(",P:update_name_thats_defined_in_workflow", ""))
+                .unwrap_err();
+        assert!(matches!(
+            err,
+            DecodeError::MissingKey(KeysToTemporalAction::P)
+        ));
+    }
+
+    #[test]
+    fn test_complete_activity_keys() {
+        let by_id = TemporalInteraction::CompleteActivity(build_mock_complete_activity_struct());
+        let callback_id = Encoder::A.encode(by_id.clone()).unwrap();
+        assert_eq!(
+            callback_id,
+            "A~E:CompleteActivity,W:some-super-long-uuid-string,N:test-namespace,T:test-task-queue-rs,M:rejected in slack,I:7,F:Failed"
+        );
+        assert_eq!(by_id, Encoder::decode(&callback_id).unwrap());
+
+        // a short token is encoded as-is
+        let short_token = "CiQ2NDFmNjQ4Yy1hZDRhLTQ1YTMtYjJiYi1iZWQ3ZjVmZTBmMjE";
+        let by_token = TemporalInteraction::CompleteActivity(CompleteActivityTemporal {
+            task_token: Some(short_token.into()),
+            workflow_id: None,
+            activity_id: None,
+            outcome: None,
+            reason: None,
+            ..build_mock_complete_activity_struct()
+        });
+        let callback_id = Encoder::A.encode(by_token.clone()).unwrap();
+        assert_eq!(
+            callback_id,
+            format!("A~E:CompleteActivity,N:test-namespace,T:test-task-queue-rs,O:{short_token}")
+        );
+        assert_eq!(by_token, Encoder::decode(&callback_id).unwrap());
+        if let TemporalInteraction::CompleteActivity(complete) = by_token {
+            let task_token = complete.task_token_bytes().unwrap().unwrap();
+            assert!(task_token.ends_with(b"641f648c-ad4a-45a3-b2bb-bed7f5fe0f21"));
+        }
+
+        // a long token is left out when the activity can be completed by id
+        let long_token = short_token.repeat(3);
+        let with_long_token = TemporalInteraction::CompleteActivity(CompleteActivityTemporal {
+            task_token: Some(long_token.clone()),
+            ..build_mock_complete_activity_struct()
+        });
+        assert_eq!(
+            Encoder::decode(&Encoder::A.encode(with_long_token).unwrap()).unwrap(),
+            by_id
+        );
+        let only_long_token = TemporalInteraction::CompleteActivity(CompleteActivityTemporal {
+            task_token: Some(long_token.clone()),
+            activity_id: None,
+            ..build_mock_complete_activity_struct()
+        });
+        assert!(Encoder::A
+            .encode(only_long_token)
+            .unwrap()
+            .contains(&long_token));
+
+        let neither = TemporalInteraction::CompleteActivity(CompleteActivityTemporal {
+            activity_id: None,
+            ..build_mock_complete_activity_struct()
+        });
+        assert!(Encoder::A.encode(neither).is_err());
+        let err = Encoder::decode("A~E:CompleteActivity,N:test-namespace,T:test-task-queue-rs,I:7")
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            DecodeError::MissingKey(KeysToTemporalAction::O)
+        ));
+    }
+
+    #[test]
+    fn test_add_data_args_appends() {
+        let with_event = build_mock_wf_exec().add_data_args(Some(vec![json!("slack event")]));
+        assert_eq!(
+            with_event,
+            TemporalInteraction::Execute(ExecuteTemporalWorkflow {
+                args: Some(vec![json!({ "arg1": "value1" }), json!("slack event")]),
+                ..build_mock_wf_exec_struct()
+            })
+        );
+
+        let with_event = build_mock_signal().add_data_args(Some(vec![json!("slack event")]));
+        assert_eq!(
+            with_event,
+            TemporalInteraction::Signal(SignalTemporal {
+                input: Some(vec![json!("slack event")]),
+                ..build_mock_signal_struct()
+            })
+        );
+
+        // the event is the signal's input, not another arg to start the workflow with
+        let with_event =
+            build_mock_signal_with_start().add_data_args(Some(vec![json!("slack event")]));
+        assert_eq!(
+            with_event,
+            TemporalInteraction::SignalWithStart(SignalWithStartTemporal {
+                input: Some(vec![json!("slack event")]),
+                ..build_mock_signal_with_start_struct()
+            })
+        );
+
+        let with_event = TemporalInteraction::Terminate(build_mock_terminate_struct())
+            .add_data_args(Some(vec![json!("slack event")]));
+        assert_eq!(
+            with_event,
+            TemporalInteraction::Terminate(TerminateTemporal {
+                details: Some(vec![
+                    json!({"approved_by": "U024BE7LH"}),
+                    json!("slack event")
+                ]),
+                ..build_mock_terminate_struct()
+            })
+        );
+        assert_eq!(
+            build_mock_cancel().add_data_args(Some(vec![json!("slack event")])),
+            build_mock_cancel()
+        );
+
+        let with_event = TemporalInteraction::Update(build_mock_update_struct())
+            .add_data_args(Some(vec![json!("slack event")]));
+        assert_eq!(
+            with_event,
+            TemporalInteraction::Update(UpdateTemporal {
+                args: Some(vec![
+                    json!({"form": "access-request"}),
+                    json!("slack event")
+                ]),
+                ..build_mock_update_struct()
+            })
+        );
+
+        let with_event =
+            TemporalInteraction::CompleteActivity(build_mock_complete_activity_struct())
+                .add_data_args(Some(vec![json!("slack event")]));
+        assert_eq!(
+            with_event,
+            TemporalInteraction::CompleteActivity(CompleteActivityTemporal {
+                result: Some(vec![json!("slack event")]),
+                ..build_mock_complete_activity_struct()
+            })
+        );
+    }
+
+    #[test]
+    fn test_user_data_round_trip() {
+        let signing_keys = build_test_signing_keys();
+
+        for encoder_version in Encoder::iter() {
+            let callback_id = encoder_version
+                .encode_with_user_data_and_keys(build_mock_signal(), "yes~no", &signing_keys)
+                .unwrap();
+            assert!(callback_id.ends_with("~yes~no"));
+
+            assert_eq!(
+                Encoder::decode_with_user_data_and_keys(&callback_id, &signing_keys).unwrap(),
+                (build_mock_signal(), Some("yes~no".to_string()))
+            );
+        }
+
+        let without_user_data = Encoder::A.encode(build_mock_signal()).unwrap();
+        assert_eq!(
+            Encoder::decode_with_user_data(&without_user_data).unwrap(),
+            (build_mock_signal(), None)
+        );
+    }
+
+    #[test]
+    fn test_user_data_length_budget() {
+        let encoded_length = Encoder::A.encode(build_mock_signal()).unwrap().len();
+        let budget = ENCODED_MAX_LENGTH - encoded_length - 1;
+
+        let fits = "ü".repeat(budget);
+        let callback_id = Encoder::A
+            .encode_with_user_data(build_mock_signal(), &fits)
+            .unwrap();
+        assert_eq!(callback_id.chars().count(), ENCODED_MAX_LENGTH);
+
+        let err = Encoder::A
+            .encode_with_user_data(build_mock_signal(), &format!("{fits}!"))
+            .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains(&format!("leaving {budget} for user data")),
+            "{}",
+            err
+        );
+
+        // the temporal section is counted in chars too, not bytes
+        let mut non_ascii_signal = build_mock_signal();
+        if let TemporalInteraction::Signal(signal) = &mut non_ascii_signal {
+            signal.signal_name = "approuvé_par_l'équipe".into();
+        }
+        let encoded = Encoder::A.encode(non_ascii_signal.clone()).unwrap();
+        assert!(encoded.len() > encoded.chars().count());
+        let budget = ENCODED_MAX_LENGTH - encoded.chars().count() - 1;
+
+        let err = Encoder::A
+            .encode_with_user_data(non_ascii_signal, &"!".repeat(budget + 1))
+            .unwrap_err();
+        assert!(
+            err.to_string().contains(&format!(
+                "takes {} chars, leaving {budget} for user data",
+                encoded.chars().count()
+            )),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_encode_checked_profiles() {
+        let encoded = Encoder::A.encode(build_mock_signal()).unwrap();
+
+        assert_eq!(
+            Encoder::A
+                .encode_checked(build_mock_signal(), Profile::SlackActionId)
+                .unwrap(),
+            encoded
+        );
+
+        let err = Encoder::A
+            .encode_checked(build_mock_signal(), Profile::DiscordCustomId)
+            .unwrap_err();
+        let length_err = err.downcast_ref::<EncodedLengthError>().unwrap();
+        assert_eq!(
+            length_err,
+            &EncodedLengthError {
+                profile: Profile::DiscordCustomId,
+                length: encoded.len(),
+                largest_key: KeysToTemporalAction::S,
+                largest_value_length: "signal_name_thats_defined_in_workflow".len(),
+            }
+        );
+        assert_eq!(length_err.over_by(), encoded.len() - 100);
+        assert!(
+            err.to_string()
+                .contains("The largest field is `S` (signal_name)"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_encode_checked_counts_bytes_for_telegram() {
+        let signal = TemporalInteraction::Signal(SignalTemporal {
+            namespace: "ns".into(),
+            task_queue: "tq".into(),
+            workflow_id: Some("wf".into()),
+            signal_name: "ü".repeat(20),
+            ..Default::default()
+        });
+        let encoded = Encoder::A.encode(signal.clone()).unwrap();
+        assert!(encoded.chars().count() <= 64);
+
+        let err = Encoder::A
+            .encode_checked(signal, Profile::TelegramCallbackData)
+            .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<EncodedLengthError>().unwrap().length,
+            encoded.len()
+        );
+    }
+
+    #[test]
+    fn test_profile_from_str() {
+        assert_eq!(
+            Profile::from_str("slack_action_id").unwrap(),
+            Profile::SlackActionId
+        );
+        assert_eq!(Profile::DiscordCustomId.to_string(), "discord_custom_id");
+    }
+
+    /// `A` pairs in reverse, for testing codecs registered outside the crate
+    struct ReversedCodec;
+
+    impl InteractionCodec for ReversedCodec {
+        fn version(&self) -> &str {
+            "REV1"
+        }
+
+        fn is_signed(&self) -> bool {
+            false
+        }
+
+        fn encode(
+            &self,
+            temporal_interaction: TemporalInteraction,
+            keys: &SigningKeys,
+        ) -> Result<String> {
+            Ok(
+                InteractionCodec::encode(&Encoder::A, temporal_interaction, keys)?
+                    .chars()
+                    .rev()
+                    .collect(),
+            )
+        }
+
+        fn decode(
+            &self,
+            temporal_section: &str,
+            keys: &SigningKeys,
+        ) -> Result<TemporalInteraction, DecodeError> {
+            InteractionCodec::decode(
+                &Encoder::A,
+                &temporal_section.chars().rev().collect::<String>(),
+                keys,
+            )
+        }
+    }
+
+    #[test]
+    fn test_registered_codec_round_trip() {
+        // a local registry, so the process-wide versions other tests see don't change
+        let mut registry = codec::CodecRegistry::default();
+        registry
+            .register(std::sync::Arc::new(ReversedCodec))
+            .unwrap();
+        assert!(registry.versions().contains(&"REV1".to_string()));
+
+        let signing_keys = build_test_signing_keys();
+        let encoded =
+            codec::encode_with_codec(&ReversedCodec, build_mock_signal(), &signing_keys).unwrap();
+        let (version, temporal_section) = encoded.split_once(ENCODER_SECTION_DELIMITER).unwrap();
+        assert_eq!(version, "REV1");
+
+        let codec = registry.get(version).unwrap();
+        assert_eq!(
+            codec.decode(temporal_section, &signing_keys).unwrap(),
+            build_mock_signal()
+        );
+    }
+
+    #[test]
+    fn test_unknown_encoder_version() {
+        match Encoder::decode("Z9~E:Signal").unwrap_err() {
+            DecodeError::UnknownVersion {
+                version,
+                accepted_versions,
+            } => {
+                assert_eq!(version, "Z9");
+                assert_eq!(accepted_versions, vec!["A", "B", "S", "X"]);
+            }
+            err => panic!("expected an unknown version error, got {err:?}"),
+        }
+    }
+
+    #[test]
+    fn test_codec_registry() {
+        let mut registry = codec::CodecRegistry::default();
+        assert_eq!(registry.versions(), vec!["A", "B", "S", "X"]);
+
+        assert!(registry.register(std::sync::Arc::new(Encoder::A)).is_err());
+        registry
+            .register(std::sync::Arc::new(ReversedCodec))
+            .unwrap();
+
+        assert!(registry.retain_versions(&["S", "Z9"]).is_err());
+        registry.retain_versions(&["S", "REV1"]).unwrap();
+        assert_eq!(registry.versions(), vec!["REV1", "S"]);
+        assert!(registry.get("A").is_none());
+    }
+
+    #[test]
+    fn test_decode_error_variants() {
+        let cases = [
+            ("no version", "MALFORMED_VERSION"),
+            ("Z9~E:Signal", "UNKNOWN_VERSION"),
+            ("A~E:Signal,W", "MALFORMED_PAIR"),
+            ("A~E:Signal,Z:zzz", "UNKNOWN_KEY"),
+            ("A~E:Signal,N:ns,T:tq", "MISSING_KEY"),
+            ("A~E:Ping,N:ns,T:tq", "UNKNOWN_EVENT_TYPE"),
+            ("A~E:Signal,D:soon,N:ns,T:tq,S:s", "MALFORMED_VALUE"),
+            ("B~not base64", "MALFORMED_SECTION"),
+            (
+                "S~E:Signal,N:ns,T:tq,S:s,K:1,H:AAAAAAAAAAAAAAAA",
+                "BAD_SIGNATURE",
+            ),
+        ];
+
+        for (encoded, code) in cases {
+            let err = Encoder::decode_with_keys(encoded, &build_test_signing_keys()).unwrap_err();
+            assert_eq!(err.code(), code, "{encoded}: {err}");
+        }
+
+        let err = Encoder::decode("A~E:Signal,N:ns,T:tq").unwrap_err();
+        assert!(matches!(
+            err,
+            DecodeError::MissingKey(KeysToTemporalAction::S)
+        ));
+    }
+
+    #[cfg(feature = "json-schema")]
+    #[test]
+    fn test_json_schema_type_tags() {
+        let schema = serde_json::to_value(schema::temporal_interaction_schema()).unwrap();
+        let type_tags: Vec<&serde_json::Value> = schema["oneOf"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|variant| &variant["properties"]["type"]["enum"][0])
+            .collect();
+        assert_eq!(
+            type_tags,
+            [
+                &json!("Execute"),
+                &json!("Signal"),
+                &json!("Query"),
+                &json!("SignalWithStart"),
+                &json!("Cancel"),
+                &json!("Terminate"),
+                &json!("Update"),
+                &json!("CompleteActivity")
+            ]
+        );
+
+        let schema = serde_json::to_value(schema::temporal_interaction_response_schema()).unwrap();
+        let execute = &schema["oneOf"][0];
+        assert_eq!(execute["properties"]["type"]["enum"][0], "ExecuteWorkflow");
+        assert_eq!(execute["required"], json!(["run_id", "type"]));
+        let response = serde_json::to_value(TemporalInteractionResponse::ExecuteWorkflow(
+            TemporalExecuteWorkflowResponse {
+                run_id: "some-run-id".to_string(),
+            },
+        ))
+        .unwrap();
+        assert_eq!(
+            response,
+            json!({"type": "ExecuteWorkflow", "run_id": "some-run-id"})
+        );
     }
 }
@@ -0,0 +1,96 @@
+//! Length limits of the fields platforms let us hide an encoded string in, see `Encoder::encode_checked`.
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use strum::{Display, EnumIter, EnumString};
+
+use crate::KeysToTemporalAction;
+
+/// A platform field an encoded string is sent in.
+///
+/// There's no Teams profile on purpose: Teams card actions carry their data as a JSON object
+/// in the card, with no limit of its own, only the size limit of the whole message.
+/// Any encoded string fits well within that, so there's nothing to check it against
+#[derive(EnumIter, EnumString, Display, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+// napi enums derive Clone & Copy themselves
+#[cfg_attr(feature = "js", napi_derive::napi)]
+#[cfg_attr(not(feature = "js"), derive(Clone, Copy))]
+#[cfg_attr(feature = "python", pyo3::pyclass(module = "temporal_json"))]
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
+pub enum Profile {
+    /// Slack `callback_id` of modals, shortcuts & attachments, 255 chars
+    SlackCallbackId,
+    /// Slack `action_id` of a block element, 255 chars
+    SlackActionId,
+    /// Slack `block_id` of a block, 255 chars
+    SlackBlockId,
+    /// Discord `custom_id` of a message component or modal, 100 chars
+    DiscordCustomId,
+    /// Telegram `callback_data` of an inline keyboard button, 64 bytes
+    TelegramCallbackData,
+}
+
+impl Profile {
+    /// longest encoded string the platform accepts, measured with `length_of`
+    pub fn max_length(&self) -> usize {
+        match self {
+            Profile::SlackCallbackId | Profile::SlackActionId | Profile::SlackBlockId => 255,
+            Profile::DiscordCustomId => 100,
+            Profile::TelegramCallbackData => 64,
+        }
+    }
+
+    /// length of `value` the way the platform counts it, chars or UTF-8 bytes
+    pub fn length_of(&self, value: &str) -> usize {
+        match self {
+            Profile::TelegramCallbackData => value.len(),
+            _ => value.chars().count(),
+        }
+    }
+
+    fn unit(&self) -> &'static str {
+        match self {
+            Profile::TelegramCallbackData => "bytes",
+            _ => "chars",
+        }
+    }
+}
+
+/// Returned by `Encoder::encode_checked` when the encoded string doesn't fit the profile.
+/// Check for it with `err.is::<EncodedLengthError>()`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct EncodedLengthError {
+    pub profile: Profile,
+    /// length of the encoded string, measured with `Profile::length_of`
+    pub length: usize,
+    /// key of the longest escaped value in the temporal section, the first thing to shorten
+    pub largest_key: KeysToTemporalAction,
+    pub largest_value_length: usize,
+}
+
+impl EncodedLengthError {
+    /// how much has to be cut for the encoded string to fit
+    pub fn over_by(&self) -> usize {
+        self.length.saturating_sub(self.profile.max_length())
+    }
+}
+
+impl fmt::Display for EncodedLengthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "encoded string is {} {unit}, {} over the {} limit of {}. The largest field is `{}` ({}) at {} {unit}",
+            self.length,
+            self.over_by(),
+            self.profile,
+            self.profile.max_length(),
+            self.largest_key,
+            self.largest_key.field_name(),
+            self.largest_value_length,
+            unit = self.profile.unit(),
+        )
+    }
+}
+
+impl std::error::Error for EncodedLengthError {}
@@ -0,0 +1,14 @@
+//! JSON Schemas of the models, for clients that build them in other languages.
+use schemars::{schema::RootSchema, schema_for};
+
+use crate::{TemporalInteraction, TemporalInteractionResponse};
+
+/// `TemporalInteraction`, a union of objects tagged by their `type`
+pub fn temporal_interaction_schema() -> RootSchema {
+    schema_for!(TemporalInteraction)
+}
+
+/// `TemporalInteractionResponse`, a union of objects tagged by their `type`
+pub fn temporal_interaction_response_schema() -> RootSchema {
+    schema_for!(TemporalInteractionResponse)
+}
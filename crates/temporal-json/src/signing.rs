@@ -0,0 +1,228 @@
+//! Keys for signing & verifying encoded strings, see `Encoder::S`.
+//! The same keys are used to derive encryption keys for `Encoder::X`.
+use anyhow::{anyhow, Context, Result};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chacha20poly1305::{
+    aead::{Aead, AeadCore, KeyInit, OsRng, Payload},
+    ChaCha20Poly1305, Nonce,
+};
+use hmac::{Hmac, Mac};
+use once_cell::sync::OnceCell;
+use sha2::Sha256;
+use std::{collections::HashMap, fmt};
+
+use crate::{ENCODER_SECTION_DELIMITER, SLACK_INFO_DELIMITER, TEMPORAL_KEY_DELIMITER};
+
+/// comma separated list of `key_id:secret` pairs. The first key is used for signing.
+pub const SIGNING_KEYS_ENV_VAR: &str = "TEMPORAL_JSON_SIGNING_KEYS";
+/// when `true`, `Encoder::decode` refuses strings that aren't signed
+pub const REQUIRE_SIGNATURE_ENV_VAR: &str = "TEMPORAL_JSON_REQUIRE_SIGNATURE";
+/// bytes of the HMAC-SHA256 kept in the encoded string, 16 chars as base64url
+pub const SIGNATURE_LENGTH_BYTES: usize = 12;
+/// ChaCha20-Poly1305 nonce, prepended to the ciphertext
+pub const NONCE_LENGTH_BYTES: usize = 12;
+/// domain separation, so the encryption key never equals the HMAC key
+const ENCRYPTION_KEY_CONTEXT: &[u8] = b"temporal-json encryption key";
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// the error is kept so every encode & decode fails the same way, rather than panicking in the caller
+static SIGNING_KEYS: OnceCell<Result<SigningKeys>> = OnceCell::new();
+
+/// Set the keys used by `Encoder::encode` and `Encoder::decode`. Can only be set once, before first use.
+pub fn set_signing_keys(keys: SigningKeys) -> Result<()> {
+    SIGNING_KEYS
+        .set(Ok(keys))
+        .map_err(|_| anyhow!("signing keys were already set or used"))
+}
+
+/// The keys used by `Encoder::encode` and `Encoder::decode`.
+/// Loaded from env vars on first use if `set_signing_keys` wasn't called, and fails if they're invalid.
+pub fn signing_keys() -> Result<&'static SigningKeys> {
+    SIGNING_KEYS
+        .get_or_init(|| {
+            SigningKeys::from_env().with_context(|| {
+                format!(
+                    "invalid {} or {}",
+                    SIGNING_KEYS_ENV_VAR, REQUIRE_SIGNATURE_ENV_VAR
+                )
+            })
+        })
+        .as_ref()
+        .map_err(|err| anyhow!("{:#}", err))
+}
+
+/// HMAC secrets by key id, so keys can be rotated without breaking strings signed by an older key.
+#[derive(Default, Clone)]
+pub struct SigningKeys {
+    /// id of the key new strings are signed with
+    active_key_id: Option<String>,
+    keys: HashMap<String, Vec<u8>>,
+    /// refuse to decode strings without a signature
+    pub require_signature: bool,
+}
+
+impl SigningKeys {
+    /// Add a key. The first key added becomes the active signing key.
+    pub fn with_key(mut self, key_id: &str, secret: impl Into<Vec<u8>>) -> Result<Self> {
+        if key_id.is_empty()
+            || [
+                ENCODER_SECTION_DELIMITER,
+                SLACK_INFO_DELIMITER,
+                TEMPORAL_KEY_DELIMITER,
+            ]
+            .iter()
+            .any(|delimiter| key_id.contains(delimiter))
+        {
+            return Err(anyhow!(
+                "signing key id `{}` must be non-empty and not contain encoder delimiters",
+                key_id
+            ));
+        }
+
+        let secret = secret.into();
+        if secret.is_empty() {
+            return Err(anyhow!("signing key `{}` has an empty secret", key_id));
+        }
+
+        self.active_key_id.get_or_insert_with(|| key_id.to_string());
+        self.keys.insert(key_id.to_string(), secret);
+        Ok(self)
+    }
+
+    /// Sign new strings with a key that was already added, e.g. to finish a rotation.
+    pub fn with_active_key(mut self, key_id: &str) -> Result<Self> {
+        if !self.keys.contains_key(key_id) {
+            return Err(anyhow!("unknown signing key id `{}`", key_id));
+        }
+        self.active_key_id = Some(key_id.to_string());
+        Ok(self)
+    }
+
+    pub fn with_require_signature(mut self, require_signature: bool) -> Self {
+        self.require_signature = require_signature;
+        self
+    }
+
+    /// Parse a comma separated list of `key_id:secret` pairs. The first key is used for signing.
+    pub fn from_key_list(key_list: &str) -> Result<Self> {
+        key_list
+            .split(SLACK_INFO_DELIMITER)
+            .map(str::trim)
+            .filter(|pair| !pair.is_empty())
+            .try_fold(Self::default(), |keys, pair| {
+                let (key_id, secret) = pair
+                    .split_once(TEMPORAL_KEY_DELIMITER)
+                    .ok_or_else(|| anyhow!("signing keys must be formatted as `key_id:secret`"))?;
+                keys.with_key(key_id, secret)
+            })
+    }
+
+    /// Load keys from `TEMPORAL_JSON_SIGNING_KEYS` and `TEMPORAL_JSON_REQUIRE_SIGNATURE`.
+    pub fn from_env() -> Result<Self> {
+        let keys = match std::env::var(SIGNING_KEYS_ENV_VAR) {
+            Ok(key_list) => Self::from_key_list(&key_list)?,
+            Err(_) => Self::default(),
+        };
+
+        let require_signature = match std::env::var(REQUIRE_SIGNATURE_ENV_VAR) {
+            Ok(value) => value
+                .parse()
+                .with_context(|| format!("{} must be true or false", REQUIRE_SIGNATURE_ENV_VAR))?,
+            Err(_) => false,
+        };
+
+        Ok(keys.with_require_signature(require_signature))
+    }
+
+    pub fn active_key_id(&self) -> Option<&str> {
+        self.active_key_id.as_deref()
+    }
+
+    /// base64url encoded truncated HMAC of `message` with the given key
+    pub fn sign(&self, key_id: &str, message: &str) -> Result<String> {
+        let tag = self.build_mac(key_id, message)?.finalize().into_bytes();
+
+        Ok(URL_SAFE_NO_PAD.encode(&tag[..SIGNATURE_LENGTH_BYTES]))
+    }
+
+    pub fn verify(&self, key_id: &str, message: &str, signature: &str) -> Result<()> {
+        let tag = URL_SAFE_NO_PAD
+            .decode(signature)
+            .context("signature is not base64url")?;
+        if tag.len() != SIGNATURE_LENGTH_BYTES {
+            return Err(anyhow!("signature has the wrong length"));
+        }
+
+        self.build_mac(key_id, message)?
+            .verify_truncated_left(&tag)
+            .map_err(|_| anyhow!("signature does not match encoded string"))
+    }
+
+    /// Encrypt & authenticate `plaintext`, and authenticate `associated_data` which is sent in the clear.
+    /// Returns the random nonce followed by the ciphertext.
+    pub fn seal(&self, key_id: &str, associated_data: &[u8], plaintext: &[u8]) -> Result<Vec<u8>> {
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .build_cipher(key_id)?
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: plaintext,
+                    aad: associated_data,
+                },
+            )
+            .map_err(|_| anyhow!("failed to encrypt encoded string"))?;
+
+        Ok([nonce.as_slice(), &ciphertext].concat())
+    }
+
+    /// Reverse of `seal`, fails if the ciphertext or associated data were altered.
+    pub fn open(&self, key_id: &str, associated_data: &[u8], sealed: &[u8]) -> Result<Vec<u8>> {
+        if sealed.len() < NONCE_LENGTH_BYTES {
+            return Err(anyhow!("ciphertext is too short"));
+        }
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LENGTH_BYTES);
+
+        self.build_cipher(key_id)?
+            .decrypt(
+                Nonce::from_slice(nonce),
+                Payload {
+                    msg: ciphertext,
+                    aad: associated_data,
+                },
+            )
+            .map_err(|_| anyhow!("failed to decrypt encoded string"))
+    }
+
+    fn build_mac(&self, key_id: &str, message: &str) -> Result<HmacSha256> {
+        let secret = self
+            .keys
+            .get(key_id)
+            .ok_or_else(|| anyhow!("unknown signing key id `{}`", key_id))?;
+
+        let mut mac =
+            <HmacSha256 as Mac>::new_from_slice(secret).expect("HMAC can take a key of any size");
+        mac.update(message.as_bytes());
+        Ok(mac)
+    }
+
+    fn build_cipher(&self, key_id: &str) -> Result<ChaCha20Poly1305> {
+        let mut key_mac = self.build_mac(key_id, "")?;
+        key_mac.update(ENCRYPTION_KEY_CONTEXT);
+        let key = key_mac.finalize().into_bytes();
+
+        Ok(ChaCha20Poly1305::new(&key))
+    }
+}
+
+// never print secrets
+impl fmt::Debug for SigningKeys {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SigningKeys")
+            .field("active_key_id", &self.active_key_id)
+            .field("key_ids", &self.keys.keys().collect::<Vec<_>>())
+            .field("require_signature", &self.require_signature)
+            .finish()
+    }
+}
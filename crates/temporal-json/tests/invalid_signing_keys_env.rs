@@ -0,0 +1,24 @@
+//! Signing keys are loaded from the environment once per process, so this runs in its own test binary.
+use temporal_json::{signing::SIGNING_KEYS_ENV_VAR, Encoder, TemporalInteraction};
+
+#[test]
+fn test_invalid_signing_keys_env_var() {
+    std::env::set_var(SIGNING_KEYS_ENV_VAR, "k1-without-a-secret");
+
+    let interaction: TemporalInteraction = serde_json::from_str(
+        r#"{"type":"Signal","namespace":"my-namespace","task_queue":"my-taskqueue","workflow_id":"my-workflow","signal_name":"my_signal"}"#,
+    )
+    .unwrap();
+
+    // unsigned `A` strings fail too, the keys aren't silently ignored
+    let err = Encoder::A.encode(interaction).unwrap_err();
+    assert!(
+        format!("{:#}", err).contains("invalid TEMPORAL_JSON_SIGNING_KEYS"),
+        "{:#}",
+        err
+    );
+
+    let err = Encoder::decode("A~E:Signal,W:my-workflow,N:my-namespace,T:my-taskqueue,S:my_signal")
+        .unwrap_err();
+    assert!(err.to_string().contains("`key_id:secret`"), "{}", err);
+}
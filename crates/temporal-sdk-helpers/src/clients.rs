@@ -0,0 +1,667 @@
+use crate::TemporalSDKClient;
+use anyhow::{anyhow, Context, Result};
+use parking_lot::{Mutex, RwLock};
+use std::{
+    collections::HashMap,
+    fmt,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+use temporal_client::{ClientTlsConfig, TlsConfig};
+use tokio::sync::OnceCell;
+use tonic::Code;
+
+/// A Temporal frontend the gateway sends interactions to
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct TemporalTarget {
+    pub host: String,
+    pub port: String,
+    /// connect with TLS, plaintext when unset
+    pub tls: Option<TemporalTls>,
+    /// sent as a bearer token with every call, e.g. for Temporal Cloud
+    pub api_key: Option<String>,
+}
+
+impl TemporalTarget {
+    pub fn new(host: impl Into<String>, port: impl Into<String>) -> Self {
+        TemporalTarget {
+            host: host.into(),
+            port: port.into(),
+            tls: None,
+            api_key: None,
+        }
+    }
+
+    pub fn with_tls(self, tls: TemporalTls) -> Self {
+        TemporalTarget {
+            tls: Some(tls),
+            ..self
+        }
+    }
+
+    pub fn with_api_key(self, api_key: impl Into<String>) -> Self {
+        TemporalTarget {
+            api_key: Some(api_key.into()),
+            ..self
+        }
+    }
+
+    pub fn url(&self) -> Result<url::Url> {
+        let scheme = if self.tls.is_some() { "https" } else { "http" };
+
+        url::Url::parse(&format!("{}://{}:{}", scheme, self.host, self.port))
+            .with_context(|| format!("{} is not a valid Temporal url", self))
+    }
+
+    async fn connect(&self) -> Result<TemporalSDKClient> {
+        let temporal_url = self.url()?;
+
+        let mut client_options = temporal_client::ClientOptionsBuilder::default();
+        client_options
+            .identity("custom_rust_apig".into())
+            .client_name("")
+            .client_version("")
+            .target_url(temporal_url.clone());
+        if let Some(tls) = &self.tls {
+            client_options.tls_cfg(tls.tls_config()?);
+        }
+
+        let headers = self.api_key.as_ref().map(|api_key| {
+            Arc::new(RwLock::new(HashMap::from([(
+                "authorization".to_string(),
+                format!("Bearer {}", api_key),
+            )])))
+        });
+
+        client_options
+            .build()
+            .unwrap()
+            .connect_no_namespace(None, headers)
+            .await
+            .with_context(|| format!("Failed to create Temporal Client at url {temporal_url}"))
+    }
+}
+
+// the api key stays out of logs
+impl fmt::Debug for TemporalTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TemporalTarget")
+            .field("host", &self.host)
+            .field("port", &self.port)
+            .field("tls", &self.tls)
+            .field("api_key", &self.api_key.as_ref().map(|_| "<redacted>"))
+            .finish()
+    }
+}
+
+impl fmt::Display for TemporalTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.host, self.port)
+    }
+}
+
+/// TLS to a Temporal frontend, trusting the system's root certificates unless a CA bundle is given.
+///
+/// Files are read on each connection, so rotated certificates are picked up when the client reconnects
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct TemporalTls {
+    /// PEM bundle of the CAs the server's certificate is checked against
+    pub ca_cert_path: Option<PathBuf>,
+    /// PEM certificate presented to the server for mTLS, with `client_key_path`
+    pub client_cert_path: Option<PathBuf>,
+    /// PEM private key of `client_cert_path`
+    pub client_key_path: Option<PathBuf>,
+    /// name the server's certificate is checked against, the host when unset
+    pub server_name: Option<String>,
+}
+
+impl TemporalTls {
+    pub fn tls_config(&self) -> Result<TlsConfig> {
+        let client_tls_config = match (&self.client_cert_path, &self.client_key_path) {
+            (Some(client_cert_path), Some(client_key_path)) => Some(ClientTlsConfig {
+                client_cert: read_pem(client_cert_path, "client certificate")?,
+                client_private_key: read_pem(client_key_path, "client key")?,
+            }),
+            (None, None) => None,
+            _ => {
+                return Err(anyhow!(
+                    "mTLS needs both a client certificate and a client key"
+                ))
+            }
+        };
+
+        Ok(TlsConfig {
+            server_root_ca_cert: self
+                .ca_cert_path
+                .as_ref()
+                .map(|ca_cert_path| read_pem(ca_cert_path, "CA bundle"))
+                .transpose()?,
+            domain: self.server_name.clone(),
+            client_tls_config,
+        })
+    }
+}
+
+fn read_pem(path: &Path, what: &str) -> Result<Vec<u8>> {
+    std::fs::read(path)
+        .with_context(|| format!("unable to read the {} at {}", what, path.display()))
+}
+
+/// how long a connection attempt can take before the call fails, so requests don't hang on an unreachable Temporal
+pub const TEMPORAL_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// a target's client once it's connected. Concurrent calls wait on the same connection attempt
+type ClientSlot = Arc<OnceCell<TemporalSDKClient>>;
+
+/// Connected Temporal clients, shared by every request instead of a connection each.
+///
+/// Nothing connects until the first call to a target, and a client whose connection
+/// fails is dropped so the next call reconnects. Clones share the same clients.
+#[derive(Clone)]
+pub struct TemporalClients {
+    default_target: TemporalTarget,
+    clients: Arc<Mutex<HashMap<TemporalTarget, ClientSlot>>>,
+}
+
+impl TemporalClients {
+    /// clients for `default_target` & any other target, none are connected yet
+    pub fn new(default_target: TemporalTarget) -> Self {
+        TemporalClients {
+            default_target,
+            clients: Arc::default(),
+        }
+    }
+
+    pub fn default_target(&self) -> &TemporalTarget {
+        &self.default_target
+    }
+
+    /// the default target's client, connecting on first use
+    pub async fn client(&self) -> Result<TemporalSDKClient> {
+        self.client_for(&self.default_target).await
+    }
+
+    /// the target's client, connecting on first use
+    pub async fn client_for(&self, target: &TemporalTarget) -> Result<TemporalSDKClient> {
+        Ok(self.connect(target).await?.0)
+    }
+
+    /// The target's client, connecting on first use, and the connection it's from to pass to `response`.
+    ///
+    /// A burst of calls opens one connection rather than one each, and connecting to one
+    /// target doesn't hold up calls to the others
+    pub async fn connect(
+        &self,
+        target: &TemporalTarget,
+    ) -> Result<(TemporalSDKClient, TemporalConnection)> {
+        // only held to find the slot, never while connecting
+        let slot = self
+            .clients
+            .lock()
+            .entry(target.clone())
+            .or_default()
+            .clone();
+
+        let client = slot
+            .get_or_try_init(|| async {
+                tokio::time::timeout(TEMPORAL_CONNECT_TIMEOUT, target.connect())
+                    .await
+                    .map_err(|_| {
+                        anyhow!(
+                            "timed out connecting to Temporal at {} after {:?}",
+                            target,
+                            TEMPORAL_CONNECT_TIMEOUT
+                        )
+                    })?
+            })
+            .await?
+            .clone();
+
+        Ok((
+            client,
+            TemporalConnection {
+                target: target.clone(),
+                slot,
+            },
+        ))
+    }
+
+    /// whether the target has a connected client
+    pub fn is_connected(&self, target: &TemporalTarget) -> bool {
+        self.clients
+            .lock()
+            .get(target)
+            .map_or(false, |slot| slot.initialized())
+    }
+
+    /// drop the target's client, the next call to it reconnects
+    pub fn disconnect(&self, target: &TemporalTarget) {
+        self.clients.lock().remove(target);
+    }
+
+    /// The message of a call made with the connection's client.
+    /// The client is dropped when Temporal couldn't be reached, so the next call reconnects
+    pub fn response<T>(
+        &self,
+        connection: &TemporalConnection,
+        response: Result<tonic::Response<T>, tonic::Status>,
+    ) -> Result<T> {
+        match response {
+            Ok(response) => Ok(response.into_inner()),
+            Err(status) => {
+                if is_connection_failure(&status) {
+                    self.drop_connection(connection);
+                }
+                Err(status.into())
+            }
+        }
+    }
+
+    /// Drop the connection unless the target already reconnected, so a call that
+    /// fails on an old connection doesn't throw away the new one
+    fn drop_connection(&self, connection: &TemporalConnection) {
+        let mut clients = self.clients.lock();
+        if clients
+            .get(&connection.target)
+            .map_or(false, |slot| Arc::ptr_eq(slot, &connection.slot))
+        {
+            clients.remove(&connection.target);
+        }
+    }
+}
+
+/// Which of a target's connections a client came from, see `TemporalClients::connect`
+#[derive(Clone)]
+pub struct TemporalConnection {
+    target: TemporalTarget,
+    slot: ClientSlot,
+}
+
+impl TemporalConnection {
+    pub fn target(&self) -> &TemporalTarget {
+        &self.target
+    }
+}
+
+impl fmt::Debug for TemporalConnection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TemporalConnection")
+            .field("target", &self.target)
+            .finish_non_exhaustive()
+    }
+}
+
+impl fmt::Debug for TemporalClients {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TemporalClients")
+            .field("default_target", &self.default_target)
+            .finish_non_exhaustive()
+    }
+}
+
+/// the connection is broken, rather than Temporal refusing the request
+pub fn is_connection_failure(status: &tonic::Status) -> bool {
+    if status.code() == Code::Unavailable {
+        return true;
+    }
+
+    // transport errors tonic doesn't give a code, like a connection reset mid-call
+    let mut source = std::error::Error::source(status);
+    while let Some(err) = source {
+        if err.is::<std::io::Error>() || err.is::<tonic::transport::Error>() {
+            return true;
+        }
+        source = err.source();
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signal_temporal;
+    use hyper::{
+        body::Bytes, header::HeaderValue, server::conn::Http, service::service_fn, Body, HeaderMap,
+        Request, Response,
+    };
+    use std::{convert::Infallible, fs::File, io::BufReader};
+    use temporal_json::SignalTemporal;
+    use tokio::{net::TcpListener, task::JoinHandle};
+    use tokio_rustls::{rustls, TlsAcceptor};
+
+    /// the path & `authorization` header of each call the stub received
+    type ReceivedCalls = Arc<Mutex<Vec<(String, Option<String>)>>>;
+
+    /// certificates for `temporal.test` & a client, signed by `ca.pem`. See testdata/README.md
+    fn testdata(file: &str) -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("testdata")
+            .join(file)
+    }
+
+    fn read_certs(file: &str) -> Vec<rustls::Certificate> {
+        rustls_pemfile::certs(&mut BufReader::new(File::open(testdata(file)).unwrap()))
+            .unwrap()
+            .into_iter()
+            .map(rustls::Certificate)
+            .collect()
+    }
+
+    /// the stub's accept loop & connections, aborting them stops the stub
+    type StubTasks = Arc<Mutex<Vec<JoinHandle<()>>>>;
+
+    /// A local TLS gRPC server for `temporal.test`, which answers every call with an empty message.
+    /// That decodes as the default of any response, so it stands in for Temporal's frontend
+    async fn start_tls_stub(require_client_cert: bool) -> (TemporalTarget, ReceivedCalls) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let received_calls = ReceivedCalls::default();
+        serve_tls_stub(listener, require_client_cert, received_calls.clone());
+
+        (
+            TemporalTarget::new("127.0.0.1", port.to_string()),
+            received_calls,
+        )
+    }
+
+    fn serve_tls_stub(
+        listener: TcpListener,
+        require_client_cert: bool,
+        calls: ReceivedCalls,
+    ) -> StubTasks {
+        let key = rustls_pemfile::pkcs8_private_keys(&mut BufReader::new(
+            File::open(testdata("server.key")).unwrap(),
+        ))
+        .unwrap()
+        .remove(0);
+
+        let config = rustls::ServerConfig::builder().with_safe_defaults();
+        let config = if require_client_cert {
+            let mut client_roots = rustls::RootCertStore::empty();
+            for ca_cert in read_certs("ca.pem") {
+                client_roots.add(&ca_cert).unwrap();
+            }
+            config.with_client_cert_verifier(rustls::server::AllowAnyAuthenticatedClient::new(
+                client_roots,
+            ))
+        } else {
+            config.with_no_client_auth()
+        };
+        let mut config = config
+            .with_single_cert(read_certs("server.pem"), rustls::PrivateKey(key))
+            .unwrap();
+        config.alpn_protocols = vec![b"h2".to_vec()];
+        let acceptor = TlsAcceptor::from(Arc::new(config));
+
+        let tasks = StubTasks::default();
+        let connections = tasks.clone();
+        let accept_loop = tokio::spawn(async move {
+            while let Ok((tcp, _)) = listener.accept().await {
+                let (acceptor, calls) = (acceptor.clone(), calls.clone());
+                connections.lock().push(tokio::spawn(async move {
+                    // a client without a trusted certificate fails the handshake here
+                    if let Ok(tls) = acceptor.accept(tcp).await {
+                        let service = service_fn(move |request| grpc_ok(request, calls.clone()));
+                        let _ = Http::new()
+                            .http2_only(true)
+                            .serve_connection(tls, service)
+                            .await;
+                    }
+                }));
+            }
+        });
+        tasks.lock().push(accept_loop);
+
+        tasks
+    }
+
+    /// closes the listener & every open connection
+    async fn stop_tls_stub(tasks: &StubTasks) {
+        let tasks: Vec<_> = tasks.lock().drain(..).collect();
+        for task in tasks {
+            task.abort();
+            let _ = task.await;
+        }
+    }
+
+    async fn grpc_ok(
+        request: Request<Body>,
+        calls: ReceivedCalls,
+    ) -> Result<Response<Body>, Infallible> {
+        let path = request.uri().path().to_string();
+        let authorization = request
+            .headers()
+            .get("authorization")
+            .and_then(|authorization| authorization.to_str().ok())
+            .map(String::from);
+        let _ = hyper::body::to_bytes(request.into_body()).await;
+        calls.lock().push((path, authorization));
+
+        let (mut sender, body) = Body::channel();
+        tokio::spawn(async move {
+            // not compressed, 0 bytes long
+            let _ = sender.send_data(Bytes::from_static(&[0, 0, 0, 0, 0])).await;
+            let mut trailers = HeaderMap::new();
+            trailers.insert("grpc-status", HeaderValue::from_static("0"));
+            let _ = sender.send_trailers(trailers).await;
+        });
+
+        Ok(Response::builder()
+            .header("content-type", "application/grpc")
+            .body(body)
+            .unwrap())
+    }
+
+    fn build_signal() -> SignalTemporal {
+        SignalTemporal {
+            namespace: "test-namespace".into(),
+            task_queue: "test-task-queue-rs".into(),
+            workflow_id: Some("some-super-long-uuid-string".into()),
+            signal_name: "signal_name_thats_defined_in_workflow".into(),
+            ..Default::default()
+        }
+    }
+
+    /// trusts the stub, which is reached by ip rather than by its certificate's name
+    fn build_stub_tls() -> TemporalTls {
+        TemporalTls {
+            ca_cert_path: Some(testdata("ca.pem")),
+            server_name: Some("temporal.test".into()),
+            ..Default::default()
+        }
+    }
+
+    fn signalled(calls: &ReceivedCalls) -> bool {
+        calls
+            .lock()
+            .iter()
+            .any(|(path, _)| path.ends_with("/SignalWorkflowExecution"))
+    }
+
+    #[tokio::test]
+    async fn test_tls_with_ca_bundle() {
+        let (target, calls) = start_tls_stub(false).await;
+        assert_eq!(
+            target
+                .clone()
+                .with_tls(build_stub_tls())
+                .url()
+                .unwrap()
+                .scheme(),
+            "https"
+        );
+
+        let clients = TemporalClients::new(target.with_tls(build_stub_tls()));
+        signal_temporal(&clients, build_signal()).await.unwrap();
+        assert!(signalled(&calls));
+    }
+
+    #[tokio::test]
+    async fn test_tls_checks_the_server_name() {
+        let (target, calls) = start_tls_stub(false).await;
+
+        // the stub's certificate is for temporal.test, not 127.0.0.1
+        let clients = TemporalClients::new(target.with_tls(TemporalTls {
+            server_name: None,
+            ..build_stub_tls()
+        }));
+        assert!(signal_temporal(&clients, build_signal()).await.is_err());
+        assert!(!signalled(&calls));
+    }
+
+    #[tokio::test]
+    async fn test_mtls() {
+        let (target, calls) = start_tls_stub(true).await;
+
+        let without_client_cert = TemporalClients::new(target.clone().with_tls(build_stub_tls()));
+        assert!(signal_temporal(&without_client_cert, build_signal())
+            .await
+            .is_err());
+        assert!(!signalled(&calls));
+
+        let with_client_cert = TemporalClients::new(target.with_tls(TemporalTls {
+            client_cert_path: Some(testdata("client.pem")),
+            client_key_path: Some(testdata("client.key")),
+            ..build_stub_tls()
+        }));
+        signal_temporal(&with_client_cert, build_signal())
+            .await
+            .unwrap();
+        assert!(signalled(&calls));
+    }
+
+    #[tokio::test]
+    async fn test_api_key_is_sent_as_a_bearer_token() {
+        let (target, calls) = start_tls_stub(false).await;
+
+        let clients = TemporalClients::new(
+            target
+                .with_tls(build_stub_tls())
+                .with_api_key("test-api-key-that-is-not-used-anywhere-else"),
+        );
+        signal_temporal(&clients, build_signal()).await.unwrap();
+
+        let calls = calls.lock();
+        assert!(!calls.is_empty());
+        for (path, authorization) in calls.iter() {
+            assert_eq!(
+                authorization.as_deref(),
+                Some("Bearer test-api-key-that-is-not-used-anywhere-else"),
+                "{}",
+                path
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_clients_reconnect_after_connection_failure() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let calls = ReceivedCalls::default();
+        let stub = serve_tls_stub(listener, false, calls.clone());
+
+        let target = TemporalTarget::new("127.0.0.1", port.to_string()).with_tls(build_stub_tls());
+        let clients = TemporalClients::new(target.clone());
+        signal_temporal(&clients, build_signal()).await.unwrap();
+        assert!(clients.is_connected(&target));
+
+        // Temporal goes away, so the call fails & the client is dropped
+        stop_tls_stub(&stub).await;
+        let err = signal_temporal(&clients, build_signal()).await.unwrap_err();
+        let status = err.downcast_ref::<tonic::Status>().unwrap();
+        assert!(is_connection_failure(status), "{:?}", status);
+        assert!(!clients.is_connected(&target));
+
+        // back on the same port, the next call connects again
+        calls.lock().clear();
+        let listener = TcpListener::bind(("127.0.0.1", port)).await.unwrap();
+        serve_tls_stub(listener, false, calls.clone());
+        signal_temporal(&clients, build_signal()).await.unwrap();
+        assert!(clients.is_connected(&target));
+        assert!(signalled(&calls));
+    }
+
+    #[tokio::test]
+    async fn test_connection_failure_drops_only_that_target() {
+        let (default_target, _) = start_tls_stub(false).await;
+        let (other_target, _) = start_tls_stub(false).await;
+        let (default_target, other_target) = (
+            default_target.with_tls(build_stub_tls()),
+            other_target.with_tls(build_stub_tls()),
+        );
+
+        let clients = TemporalClients::new(default_target.clone());
+        let (_, default_connection) = clients.connect(&default_target).await.unwrap();
+        let (_, other_connection) = clients.connect(&other_target).await.unwrap();
+
+        let unavailable = Err::<tonic::Response<()>, _>(tonic::Status::unavailable("broken pipe"));
+        assert!(clients.response(&other_connection, unavailable).is_err());
+        assert!(!clients.is_connected(&other_target));
+        assert!(clients.is_connected(&default_target));
+
+        // Temporal refusing the call doesn't mean the connection is broken
+        let not_found = Err::<tonic::Response<()>, _>(tonic::Status::not_found("no workflow"));
+        assert!(clients.response(&default_connection, not_found).is_err());
+        assert!(clients.is_connected(&default_target));
+    }
+
+    #[tokio::test]
+    async fn test_late_failure_keeps_the_new_connection() {
+        let (target, _) = start_tls_stub(false).await;
+        let target = target.with_tls(build_stub_tls());
+        let clients = TemporalClients::new(target.clone());
+
+        // a call starts on the first connection, then another call's failure drops it & it reconnects
+        let (_, old_connection) = clients.connect(&target).await.unwrap();
+        let unavailable = || Err::<tonic::Response<()>, _>(tonic::Status::unavailable("reset"));
+        assert!(clients.response(&old_connection, unavailable()).is_err());
+        let (_, new_connection) = clients.connect(&target).await.unwrap();
+        assert!(clients.is_connected(&target));
+
+        // the first call fails late, which says nothing about the new connection
+        assert!(clients.response(&old_connection, unavailable()).is_err());
+        assert!(clients.is_connected(&target));
+
+        assert!(clients.response(&new_connection, unavailable()).is_err());
+        assert!(!clients.is_connected(&target));
+    }
+
+    #[test]
+    fn test_api_key_is_not_logged() {
+        let target = TemporalTarget::new("127.0.0.1", "7233")
+            .with_api_key("test-api-key-that-is-not-used-anywhere-else");
+
+        assert!(!format!("{:?}", target).contains("test-api-key"));
+    }
+
+    #[test]
+    fn test_tls_config_files() {
+        let tls_config = TemporalTls {
+            client_cert_path: Some(testdata("client.pem")),
+            client_key_path: Some(testdata("client.key")),
+            ..build_stub_tls()
+        }
+        .tls_config()
+        .unwrap();
+        assert_eq!(tls_config.domain.as_deref(), Some("temporal.test"));
+        assert!(tls_config.server_root_ca_cert.is_some());
+        assert!(tls_config.client_tls_config.is_some());
+
+        let err = TemporalTls {
+            client_cert_path: Some(testdata("client.pem")),
+            ..build_stub_tls()
+        }
+        .tls_config()
+        .unwrap_err();
+        assert!(err.to_string().contains("needs both"), "{}", err);
+
+        let err = TemporalTls {
+            ca_cert_path: Some(testdata("missing.pem")),
+            ..Default::default()
+        }
+        .tls_config()
+        .unwrap_err();
+        assert!(err.to_string().contains("missing.pem"), "{}", err);
+    }
+}
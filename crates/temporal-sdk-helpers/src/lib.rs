@@ -1,56 +1,61 @@
 use anyhow::{anyhow, Context, Result};
-use once_cell::sync::OnceCell;
-use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::{
+    collections::{BTreeMap, HashMap},
+    time::Duration,
+};
 use temporal_client::{self, ConfiguredClient, RetryClient, TemporalServiceClientWithMetrics};
-pub use temporal_json::{Encoder, TemporalInteraction};
-use temporal_json::{ExecuteTemporalWorkflow, QueryTemporal, SignalTemporal};
+pub use temporal_json::{
+    codec, schema, set_signing_keys, DecodeError, EncodedLengthError, Encoder, Profile,
+    SigningKeys, TemporalInteraction, TemporalInteractionResponse, TemporalUpdateResponse,
+    UpdateWaitStage,
+};
+use temporal_json::{
+    ActivityOutcome, CancelTemporal, CompleteActivityTemporal, ExecuteTemporalWorkflow,
+    QueryTemporal, SignalTemporal, SignalWithStartTemporal, TemporalCancelResponse,
+    TemporalCompleteActivityResponse, TemporalExecuteWorkflowResponse, TemporalQueryResponse,
+    TemporalSignalResponse, TemporalSignalWithStartResponse, TemporalTerminateResponse,
+    TerminateTemporal, UpdateTemporal, WorkflowRetryPolicy,
+};
 use temporal_sdk_core_protos::{
     coresdk::AsJsonPayloadExt,
     temporal::api::{
-        common::v1::{Payloads, WorkflowExecution, WorkflowType},
-        enums::v1::TaskQueueKind,
+        common::v1::{
+            Memo, Payload, Payloads, RetryPolicy, SearchAttributes, WorkflowExecution, WorkflowType,
+        },
+        enums::v1::{TaskQueueKind, UpdateWorkflowExecutionLifecycleStage, WorkflowIdReusePolicy},
+        failure::v1::{failure::FailureInfo, ApplicationFailureInfo, Failure},
         query::v1::WorkflowQuery,
         taskqueue::v1::TaskQueue,
+        update::v1::{outcome, Input, Meta, Request, WaitPolicy},
         workflowservice::v1::{
-            QueryWorkflowRequest, QueryWorkflowResponse, SignalWorkflowExecutionRequest,
+            QueryWorkflowRequest, QueryWorkflowResponse, RequestCancelWorkflowExecutionRequest,
+            RequestCancelWorkflowExecutionResponse, RespondActivityTaskCanceledByIdRequest,
+            RespondActivityTaskCanceledRequest, RespondActivityTaskCompletedByIdRequest,
+            RespondActivityTaskCompletedRequest, RespondActivityTaskFailedByIdRequest,
+            RespondActivityTaskFailedRequest, SignalWithStartWorkflowExecutionRequest,
+            SignalWithStartWorkflowExecutionResponse, SignalWorkflowExecutionRequest,
             SignalWorkflowExecutionResponse, StartWorkflowExecutionRequest,
-            StartWorkflowExecutionResponse,
+            StartWorkflowExecutionResponse, TerminateWorkflowExecutionRequest,
+            TerminateWorkflowExecutionResponse, UpdateWorkflowExecutionRequest,
+            UpdateWorkflowExecutionResponse,
         },
     },
 };
 use uuid::Uuid;
 
-pub const DEFAULT_NAMESPACE: &str = "test-namespace";
-pub type TemporalSDKClient = RetryClient<ConfiguredClient<TemporalServiceClientWithMetrics>>;
-
-pub static TEMPORAL_HOST_PORT_PAIR: OnceCell<(String, String)> = OnceCell::new();
-
-pub async fn build_temporal_client_without_namespace() -> Result<TemporalSDKClient> {
-    let (host, port) = TEMPORAL_HOST_PORT_PAIR
-        .get()
-        .ok_or_else(|| anyhow!("Temporal host and port not set!"))?;
+pub mod clients;
 
-    let temporal_url = url::Url::parse(&format!("http://{host}:{port}"))?;
-
-    let client_options = temporal_client::ClientOptionsBuilder::default()
-        .identity("custom_rust_apig".into())
-        .client_name("")
-        .client_version("")
-        .target_url(temporal_url.clone())
-        .build()
-        .unwrap();
+pub use clients::{TemporalClients, TemporalConnection, TemporalTarget, TemporalTls};
 
-    client_options
-        .connect_no_namespace(None, None)
-        .await
-        .with_context(|| format!("Failed to create Temporal Client at url {temporal_url}"))
-}
+pub const DEFAULT_NAMESPACE: &str = "test-namespace";
+pub type TemporalSDKClient = RetryClient<ConfiguredClient<TemporalServiceClientWithMetrics>>;
 
 pub async fn signal_temporal(
+    clients: &TemporalClients,
     signal_info: SignalTemporal,
 ) -> Result<SignalWorkflowExecutionResponse> {
-    let mut client = build_temporal_client_without_namespace().await?;
+    let (mut client, connection) = clients.connect(clients.default_target()).await?;
 
     let input = signal_info.input.map(|inputs| Payloads {
         payloads: inputs
@@ -85,32 +90,72 @@ pub async fn signal_temporal(
                 .unwrap_or_else(|| "placeholder_control".into()),
             header: None,
         })
-        .await?;
+        .await;
+
+    clients.response(&connection, signal_response)
+}
 
-    Ok(signal_response.into_inner())
+pub async fn signal_with_start_temporal(
+    clients: &TemporalClients,
+    signal_with_start_info: SignalWithStartTemporal,
+) -> Result<SignalWithStartWorkflowExecutionResponse> {
+    let (mut client, connection) = clients.connect(clients.default_target()).await?;
+
+    let signal_with_start_response = client
+        .get_client_mut()
+        .workflow_svc_mut()
+        .signal_with_start_workflow_execution(build_signal_with_start_request(
+            signal_with_start_info,
+        ))
+        .await;
+
+    clients.response(&connection, signal_with_start_response)
+}
+
+pub fn build_signal_with_start_request(
+    signal_with_start_info: SignalWithStartTemporal,
+) -> SignalWithStartWorkflowExecutionRequest {
+    SignalWithStartWorkflowExecutionRequest {
+        namespace: signal_with_start_info.namespace,
+        workflow_id: signal_with_start_info.workflow_id,
+        workflow_type: Some(WorkflowType {
+            name: signal_with_start_info.workflow_type,
+        }),
+        task_queue: Some(TaskQueue {
+            name: signal_with_start_info.task_queue,
+            kind: TaskQueueKind::Unspecified as i32,
+        }),
+        input: signal_with_start_info.args.map(to_json_payloads),
+        signal_name: signal_with_start_info.signal_name,
+        signal_input: signal_with_start_info.input.map(to_json_payloads),
+        identity: signal_with_start_info
+            .identity
+            .unwrap_or_else(|| "SecEngTemporalAPIG".into()),
+        request_id: signal_with_start_info
+            .request_id
+            .unwrap_or_else(|| Uuid::new_v4().to_string()),
+        control: signal_with_start_info
+            .control
+            .unwrap_or_else(|| "placeholder_control".into()),
+        ..Default::default()
+    }
 }
 
 pub async fn start_temporal_workflow(
+    clients: &TemporalClients,
     workflow_info: ExecuteTemporalWorkflow,
 ) -> Result<StartWorkflowExecutionResponse> {
-    let mut client = build_temporal_client_without_namespace().await?;
+    let (mut client, connection) = clients.connect(clients.default_target()).await?;
 
-    let workflow_execution_request = build_workflow_execution_request(
-        workflow_info.namespace,
-        workflow_info.args,
-        workflow_info.task_queue,
-        workflow_info.workflow_id,
-        workflow_info.workflow_type,
-        None,
-    );
+    let workflow_execution_request = build_workflow_execution_request(workflow_info);
 
     let execution_response = client
         .get_client_mut()
         .workflow_svc_mut()
         .start_workflow_execution(workflow_execution_request)
-        .await?;
+        .await;
 
-    Ok(execution_response.into_inner())
+    clients.response(&connection, execution_response)
 }
 
 pub fn to_json_payloads(args: Vec<serde_json::Value>) -> Payloads {
@@ -123,41 +168,91 @@ pub fn to_json_payloads(args: Vec<serde_json::Value>) -> Payloads {
 }
 
 pub fn build_workflow_execution_request(
-    namespace: String,
-    input: Option<Vec<serde_json::Value>>,
-    task_queue: String,
-    workflow_id: String,
-    workflow_type: String,
-    options: Option<temporal_client::WorkflowOptions>,
+    workflow_info: ExecuteTemporalWorkflow,
 ) -> StartWorkflowExecutionRequest {
-    let options = options.unwrap_or_default();
-
-    let input = input.map(to_json_payloads);
+    let options = workflow_info.options.unwrap_or_default();
 
     StartWorkflowExecutionRequest {
-        namespace,
-        input,
-        workflow_id,
+        namespace: workflow_info.namespace,
+        input: workflow_info.args.map(to_json_payloads),
+        workflow_id: workflow_info.workflow_id,
         workflow_type: Some(WorkflowType {
-            name: workflow_type,
+            name: workflow_info.workflow_type,
         }),
         task_queue: Some(TaskQueue {
-            name: task_queue,
+            name: workflow_info.task_queue,
             kind: TaskQueueKind::Unspecified as i32,
         }),
         request_id: Uuid::new_v4().to_string(),
-        workflow_id_reuse_policy: options.id_reuse_policy as i32,
-        workflow_execution_timeout: options.execution_timeout.and_then(|d| d.try_into().ok()),
-        workflow_run_timeout: options.execution_timeout.and_then(|d| d.try_into().ok()),
-        workflow_task_timeout: options.task_timeout.and_then(|d| d.try_into().ok()),
-        search_attributes: options.search_attributes.and_then(|d| d.try_into().ok()),
+        workflow_id_reuse_policy: options
+            .id_reuse_policy
+            .map_or(WorkflowIdReusePolicy::Unspecified, id_reuse_policy)
+            as i32,
+        workflow_execution_timeout: options.execution_timeout_secs.and_then(secs_to_duration),
+        workflow_run_timeout: options.run_timeout_secs.and_then(secs_to_duration),
+        workflow_task_timeout: options.task_timeout_secs.and_then(secs_to_duration),
+        retry_policy: options.retry_policy.map(retry_policy),
         cron_schedule: options.cron_schedule.unwrap_or_default(),
+        memo: options.memo.map(|memo| Memo {
+            fields: to_json_payload_map(memo),
+        }),
+        search_attributes: options
+            .search_attributes
+            .map(|search_attributes| SearchAttributes {
+                indexed_fields: to_json_payload_map(search_attributes),
+            }),
         ..Default::default()
     }
 }
 
-pub async fn query_temporal(query_info: QueryTemporal) -> Result<QueryWorkflowResponse> {
-    let mut client = build_temporal_client_without_namespace().await?;
+fn secs_to_duration<D: TryFrom<Duration>>(secs: u64) -> Option<D> {
+    Duration::from_secs(secs).try_into().ok()
+}
+
+fn to_json_payload_map(values: BTreeMap<String, Value>) -> HashMap<String, Payload> {
+    values
+        .into_iter()
+        .map(|(key, value)| (key, value.as_json_payload().unwrap()))
+        .collect()
+}
+
+fn id_reuse_policy(policy: temporal_json::WorkflowIdReusePolicy) -> WorkflowIdReusePolicy {
+    match policy {
+        temporal_json::WorkflowIdReusePolicy::AllowDuplicate => {
+            WorkflowIdReusePolicy::AllowDuplicate
+        }
+        temporal_json::WorkflowIdReusePolicy::AllowDuplicateFailedOnly => {
+            WorkflowIdReusePolicy::AllowDuplicateFailedOnly
+        }
+        temporal_json::WorkflowIdReusePolicy::RejectDuplicate => {
+            WorkflowIdReusePolicy::RejectDuplicate
+        }
+        temporal_json::WorkflowIdReusePolicy::TerminateIfRunning => {
+            WorkflowIdReusePolicy::TerminateIfRunning
+        }
+    }
+}
+
+fn retry_policy(policy: WorkflowRetryPolicy) -> RetryPolicy {
+    RetryPolicy {
+        initial_interval: policy.initial_interval_secs.and_then(secs_to_duration),
+        backoff_coefficient: policy
+            .backoff_coefficient
+            .and_then(|coefficient| coefficient.as_f64())
+            .unwrap_or_default(),
+        maximum_interval: policy.maximum_interval_secs.and_then(secs_to_duration),
+        maximum_attempts: policy
+            .maximum_attempts
+            .map_or(0, |attempts| attempts.try_into().unwrap_or(i32::MAX)),
+        non_retryable_error_types: policy.non_retryable_error_types.unwrap_or_default(),
+    }
+}
+
+pub async fn query_temporal(
+    clients: &TemporalClients,
+    query_info: QueryTemporal,
+) -> Result<QueryWorkflowResponse> {
+    let (mut client, connection) = clients.connect(clients.default_target()).await?;
 
     let input = query_info.query_args.map(|inputs| Payloads {
         payloads: inputs
@@ -184,9 +279,241 @@ pub async fn query_temporal(query_info: QueryTemporal) -> Result<QueryWorkflowRe
             }),
             ..Default::default() // query_reject_condition: todo!(),
         })
-        .await?;
+        .await;
+
+    clients.response(&connection, query_response)
+}
+
+pub async fn cancel_temporal(
+    clients: &TemporalClients,
+    cancel_info: CancelTemporal,
+) -> Result<RequestCancelWorkflowExecutionResponse> {
+    let (mut client, connection) = clients.connect(clients.default_target()).await?;
+
+    let cancel_response = client
+        .get_client_mut()
+        .workflow_svc_mut()
+        .request_cancel_workflow_execution(RequestCancelWorkflowExecutionRequest {
+            namespace: cancel_info.namespace,
+            workflow_execution: Some(WorkflowExecution {
+                workflow_id: cancel_info.workflow_id,
+                run_id: cancel_info.run_id.unwrap_or_default(),
+            }),
+            identity: cancel_info
+                .identity
+                .unwrap_or_else(|| "SecEngTemporalAPIG".into()),
+            request_id: cancel_info
+                .request_id
+                .unwrap_or_else(|| Uuid::new_v4().to_string()),
+            reason: cancel_info.reason.unwrap_or_default(),
+            ..Default::default()
+        })
+        .await;
+
+    clients.response(&connection, cancel_response)
+}
+
+pub async fn terminate_temporal(
+    clients: &TemporalClients,
+    terminate_info: TerminateTemporal,
+) -> Result<TerminateWorkflowExecutionResponse> {
+    let (mut client, connection) = clients.connect(clients.default_target()).await?;
+
+    let terminate_response = client
+        .get_client_mut()
+        .workflow_svc_mut()
+        .terminate_workflow_execution(TerminateWorkflowExecutionRequest {
+            namespace: terminate_info.namespace,
+            workflow_execution: Some(WorkflowExecution {
+                workflow_id: terminate_info.workflow_id,
+                run_id: terminate_info.run_id.unwrap_or_default(),
+            }),
+            reason: terminate_info.reason.unwrap_or_default(),
+            details: terminate_info.details.map(to_json_payloads),
+            identity: terminate_info
+                .identity
+                .unwrap_or_else(|| "SecEngTemporalAPIG".into()),
+            ..Default::default()
+        })
+        .await;
+
+    clients.response(&connection, terminate_response)
+}
+
+pub async fn update_temporal(
+    clients: &TemporalClients,
+    update_info: UpdateTemporal,
+) -> Result<UpdateWorkflowExecutionResponse> {
+    let (mut client, connection) = clients.connect(clients.default_target()).await?;
+
+    let update_response = client
+        .get_client_mut()
+        .workflow_svc_mut()
+        .update_workflow_execution(build_update_request(update_info))
+        .await;
+
+    clients.response(&connection, update_response)
+}
 
-    Ok(query_response.into_inner())
+pub fn build_update_request(update_info: UpdateTemporal) -> UpdateWorkflowExecutionRequest {
+    let lifecycle_stage = match update_info.wait_stage.unwrap_or_default() {
+        UpdateWaitStage::Accepted => UpdateWorkflowExecutionLifecycleStage::Accepted,
+        UpdateWaitStage::Completed => UpdateWorkflowExecutionLifecycleStage::Completed,
+    };
+
+    UpdateWorkflowExecutionRequest {
+        namespace: update_info.namespace,
+        workflow_execution: Some(WorkflowExecution {
+            workflow_id: update_info.workflow_id,
+            run_id: update_info.run_id.unwrap_or_default(),
+        }),
+        wait_policy: Some(WaitPolicy {
+            lifecycle_stage: lifecycle_stage as i32,
+        }),
+        request: Some(Request {
+            meta: Some(Meta {
+                update_id: update_info
+                    .update_id
+                    .unwrap_or_else(|| Uuid::new_v4().to_string()),
+                identity: update_info
+                    .identity
+                    .unwrap_or_else(|| "SecEngTemporalAPIG".into()),
+            }),
+            input: Some(Input {
+                header: None,
+                name: update_info.update_name,
+                args: update_info.args.map(to_json_payloads),
+            }),
+        }),
+        ..Default::default()
+    }
+}
+
+/// Complete, fail or cancel an activity that returned `complete_async`.
+/// Uses the task token when there is one, and the workflow & activity ids otherwise.
+pub async fn complete_activity_temporal(
+    clients: &TemporalClients,
+    complete_info: CompleteActivityTemporal,
+) -> Result<()> {
+    let (mut client, connection) = clients.connect(clients.default_target()).await?;
+    let workflow_svc = client.get_client_mut().workflow_svc_mut();
+
+    let task_token = complete_info.task_token_bytes()?;
+    let identity = complete_info
+        .identity
+        .unwrap_or_else(|| "SecEngTemporalAPIG".into());
+    let payloads = complete_info.result.map(to_json_payloads);
+    let outcome = complete_info.outcome.unwrap_or_default();
+
+    if let Some(task_token) = task_token {
+        match outcome {
+            ActivityOutcome::Completed => {
+                let response = workflow_svc
+                    .respond_activity_task_completed(RespondActivityTaskCompletedRequest {
+                        task_token,
+                        result: payloads,
+                        identity,
+                        namespace: complete_info.namespace,
+                        ..Default::default()
+                    })
+                    .await;
+                clients.response(&connection, response)?;
+            }
+            ActivityOutcome::Failed => {
+                let response = workflow_svc
+                    .respond_activity_task_failed(RespondActivityTaskFailedRequest {
+                        task_token,
+                        failure: Some(activity_failure(complete_info.reason, payloads)),
+                        identity,
+                        namespace: complete_info.namespace,
+                        ..Default::default()
+                    })
+                    .await;
+                clients.response(&connection, response)?;
+            }
+            ActivityOutcome::Canceled => {
+                let response = workflow_svc
+                    .respond_activity_task_canceled(RespondActivityTaskCanceledRequest {
+                        task_token,
+                        details: payloads,
+                        identity,
+                        namespace: complete_info.namespace,
+                        ..Default::default()
+                    })
+                    .await;
+                clients.response(&connection, response)?;
+            }
+        }
+        return Ok(());
+    }
+
+    let (workflow_id, activity_id) = complete_info
+        .workflow_id
+        .zip(complete_info.activity_id)
+        .ok_or_else(|| {
+            anyhow!("CompleteActivity needs a task_token, or a workflow_id & activity_id")
+        })?;
+    let run_id = complete_info.run_id.unwrap_or_default();
+
+    match outcome {
+        ActivityOutcome::Completed => {
+            let response = workflow_svc
+                .respond_activity_task_completed_by_id(RespondActivityTaskCompletedByIdRequest {
+                    namespace: complete_info.namespace,
+                    workflow_id,
+                    run_id,
+                    activity_id,
+                    result: payloads,
+                    identity,
+                })
+                .await;
+            clients.response(&connection, response)?;
+        }
+        ActivityOutcome::Failed => {
+            let response = workflow_svc
+                .respond_activity_task_failed_by_id(RespondActivityTaskFailedByIdRequest {
+                    namespace: complete_info.namespace,
+                    workflow_id,
+                    run_id,
+                    activity_id,
+                    failure: Some(activity_failure(complete_info.reason, payloads)),
+                    identity,
+                    ..Default::default()
+                })
+                .await;
+            clients.response(&connection, response)?;
+        }
+        ActivityOutcome::Canceled => {
+            let response = workflow_svc
+                .respond_activity_task_canceled_by_id(RespondActivityTaskCanceledByIdRequest {
+                    namespace: complete_info.namespace,
+                    workflow_id,
+                    run_id,
+                    activity_id,
+                    details: payloads,
+                    identity,
+                })
+                .await;
+            clients.response(&connection, response)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// an application failure, so the activity's retry policy decides whether it runs again
+fn activity_failure(reason: Option<String>, details: Option<Payloads>) -> Failure {
+    Failure {
+        message: reason.unwrap_or_else(|| "failed by the Temporal API Gateway".into()),
+        source: "SecEngTemporalAPIG".into(),
+        failure_info: Some(FailureInfo::ApplicationFailureInfo(
+            ApplicationFailureInfo {
+                details,
+                ..Default::default()
+            },
+        )),
+        ..Default::default()
+    }
 }
 
 /// Data Models ///////////////////////////////////////////////////
@@ -213,80 +540,453 @@ pub async fn query_temporal(query_info: QueryTemporal) -> Result<QueryWorkflowRe
 //   }
 
 pub async fn execute_interaction(
+    clients: &TemporalClients,
     interaction: TemporalInteraction,
 ) -> Result<TemporalInteractionResponse> {
     Ok(match interaction {
         TemporalInteraction::Execute(wf_info) => {
-            TemporalInteractionResponse::from(start_temporal_workflow(wf_info).await?)
+            execute_response(start_temporal_workflow(clients, wf_info).await?)
         }
         TemporalInteraction::Signal(signal_info) => {
-            TemporalInteractionResponse::from(signal_temporal(signal_info).await?)
+            signal_response(signal_temporal(clients, signal_info).await?)
         }
         TemporalInteraction::Query(query_info) => {
-            // we need `try_from` here because queries can return arbitrary data from the workflow,
+            // queries can return arbitrary data from the workflow,
             // which requires a fallible attempt at JSON conversion via serde
-            TemporalInteractionResponse::try_from(query_temporal(query_info).await?)?
+            query_response(query_temporal(clients, query_info).await?)?
+        }
+        TemporalInteraction::SignalWithStart(signal_with_start_info) => signal_with_start_response(
+            signal_with_start_temporal(clients, signal_with_start_info).await?,
+        ),
+        TemporalInteraction::Cancel(cancel_info) => {
+            cancel_temporal(clients, cancel_info).await?;
+            TemporalInteractionResponse::Cancel(TemporalCancelResponse {})
+        }
+        TemporalInteraction::Terminate(terminate_info) => {
+            terminate_temporal(clients, terminate_info).await?;
+            TemporalInteractionResponse::Terminate(TemporalTerminateResponse {})
+        }
+        TemporalInteraction::Update(update_info) => {
+            // a failed update is still a response, the caller decides what to tell the user
+            update_response(update_temporal(clients, update_info).await?)?
+        }
+        TemporalInteraction::CompleteActivity(complete_info) => {
+            complete_activity_temporal(clients, complete_info).await?;
+            TemporalInteractionResponse::CompleteActivity(TemporalCompleteActivityResponse {})
         }
     })
 }
 
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
-#[serde(tag = "type")]
-pub enum TemporalInteractionResponse {
-    ExecuteWorkflow(TemporalExecuteWorkflowResponse),
-    Signal(TemporalSignalResponse),
-    Query(TemporalQueryResponse),
+// the response models live in temporal_json so their JSON Schema can be published,
+// so these can't be `From` impls
+
+fn execute_response(exec_response: StartWorkflowExecutionResponse) -> TemporalInteractionResponse {
+    TemporalInteractionResponse::ExecuteWorkflow(TemporalExecuteWorkflowResponse {
+        run_id: exec_response.run_id,
+    })
 }
 
-impl From<StartWorkflowExecutionResponse> for TemporalInteractionResponse {
-    fn from(exec_response: StartWorkflowExecutionResponse) -> Self {
-        Self::ExecuteWorkflow(TemporalExecuteWorkflowResponse {
-            run_id: exec_response.run_id,
-        })
-    }
+fn signal_response(
+    _signal_response: SignalWorkflowExecutionResponse,
+) -> TemporalInteractionResponse {
+    TemporalInteractionResponse::Signal(TemporalSignalResponse {})
 }
 
-impl From<SignalWorkflowExecutionResponse> for TemporalInteractionResponse {
-    fn from(_signal_response: SignalWorkflowExecutionResponse) -> Self {
-        Self::Signal(TemporalSignalResponse {})
-    }
+fn signal_with_start_response(
+    signal_with_start_response: SignalWithStartWorkflowExecutionResponse,
+) -> TemporalInteractionResponse {
+    TemporalInteractionResponse::SignalWithStart(TemporalSignalWithStartResponse {
+        run_id: signal_with_start_response.run_id,
+    })
 }
 
-impl TryFrom<QueryWorkflowResponse> for TemporalInteractionResponse {
-    type Error = serde_json::Error;
+fn query_response(query_response: QueryWorkflowResponse) -> Result<TemporalInteractionResponse> {
+    // if we have results (payloads), convert them to JSON for HTTP transmission
+    let query_result = match query_response.query_result {
+        Some(payload_container) => Some(
+            payload_container
+                .payloads
+                .iter()
+                .map(|payload| serde_json::from_slice(&payload.data))
+                .collect::<Result<Vec<Value>, _>>()
+                .context("query result is not JSON")?,
+        ),
+        None => None,
+    };
+
+    Ok(TemporalInteractionResponse::Query(TemporalQueryResponse {
+        query_result,
+        query_rejected: query_response
+            .query_rejected
+            .map(|rejected| rejected.status),
+    }))
+}
 
-    fn try_from(query_response: QueryWorkflowResponse) -> Result<Self, Self::Error> {
-        // if we have results (payloads), convert them to JSON for HTTP transmission
-        let query_result = match query_response.query_result {
-            Some(payload_container) => Some(
-                payload_container
+fn update_response(
+    update_response: UpdateWorkflowExecutionResponse,
+) -> Result<TemporalInteractionResponse> {
+    let stage = if update_response.stage == UpdateWorkflowExecutionLifecycleStage::Completed as i32
+    {
+        UpdateWaitStage::Completed
+    } else {
+        UpdateWaitStage::Accepted
+    };
+
+    let (result, failure) = match update_response.outcome.and_then(|outcome| outcome.value) {
+        Some(outcome::Value::Success(payloads)) => (
+            Some(
+                payloads
                     .payloads
                     .iter()
-                    .map(|payload| serde_json::to_value(&payload.data))
-                    .collect::<Result<Vec<Value>, _>>()?,
+                    .map(|payload| serde_json::from_slice(&payload.data))
+                    .collect::<Result<Vec<Value>, _>>()
+                    .context("update result is not JSON")?,
             ),
-            None => None,
-        };
+            None,
+        ),
+        Some(outcome::Value::Failure(failure)) => (None, Some(failure.message)),
+        // the update was accepted but hasn't completed yet
+        None => (None, None),
+    };
+
+    Ok(TemporalInteractionResponse::Update(
+        TemporalUpdateResponse {
+            update_id: update_response
+                .update_ref
+                .map(|update_ref| update_ref.update_id)
+                .unwrap_or_default(),
+            stage,
+            result,
+            failure,
+        },
+    ))
+}
 
-        Ok(Self::Query(TemporalQueryResponse {
-            query_result,
-            query_rejected: query_response
-                .query_rejected
-                .map(|rejected| rejected.status),
-        }))
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use temporal_json::WorkflowStartOptions;
+    use temporal_sdk_core_protos::temporal::api::update::v1::{Outcome, UpdateRef};
+
+    fn build_execute(options: Option<WorkflowStartOptions>) -> ExecuteTemporalWorkflow {
+        ExecuteTemporalWorkflow {
+            namespace: "test-namespace".into(),
+            task_queue: "test-task-queue-rs".into(),
+            workflow_id: "some-super-long-uuid-string".into(),
+            workflow_type: "some-wf-function-name".into(),
+            args: Some(vec![json!({ "user": "U024BE7LH" })]),
+            options,
+            expires_at: None,
+        }
     }
-}
 
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
-pub struct TemporalExecuteWorkflowResponse {
-    run_id: String,
-}
+    #[test]
+    fn test_target_url() {
+        let target = TemporalTarget::new("temporal.internal", "7233");
+
+        assert_eq!(
+            target.url().unwrap().as_str(),
+            "http://temporal.internal:7233/"
+        );
+        assert!(TemporalTarget::new("temporal.internal", "not-a-port")
+            .url()
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_clients_connect_lazily() {
+        // nothing listens on port 1
+        let target = TemporalTarget::new("127.0.0.1", "1");
+        let clients = TemporalClients::new(target.clone());
+        assert!(!clients.is_connected(&target));
+
+        // a failed connection isn't kept, so the next call tries again
+        assert!(clients.client().await.is_err());
+        assert!(!clients.is_connected(&target));
+        assert!(clients.clone().client().await.is_err());
+    }
+
+    #[test]
+    fn test_only_connection_failures_reconnect() {
+        assert!(clients::is_connection_failure(&tonic::Status::unavailable(
+            "connection refused"
+        )));
+        assert!(clients::is_connection_failure(&tonic::Status::from_error(
+            Box::new(std::io::Error::from(std::io::ErrorKind::ConnectionReset))
+        )));
+        assert!(!clients::is_connection_failure(&tonic::Status::not_found(
+            "workflow not found"
+        )));
+    }
+
+    #[test]
+    fn test_signal_with_start_request() {
+        let request = build_signal_with_start_request(SignalWithStartTemporal {
+            namespace: "test-namespace".into(),
+            task_queue: "test-task-queue-rs".into(),
+            workflow_id: "some-super-long-uuid-string".into(),
+            workflow_type: "some-wf-function-name".into(),
+            args: Some(vec![json!({ "user": "U024BE7LH" })]),
+            signal_name: "approval".into(),
+            input: Some(vec![json!("approved"), json!(2)]),
+            request_id: Some("some-request-id".into()),
+            ..Default::default()
+        });
+
+        assert_eq!(request.namespace, "test-namespace");
+        assert_eq!(request.workflow_id, "some-super-long-uuid-string");
+        assert_eq!(request.workflow_type.unwrap().name, "some-wf-function-name");
+        assert_eq!(request.task_queue.unwrap().name, "test-task-queue-rs");
+        // the start args & the signal's args aren't mixed up
+        assert_eq!(
+            request.input.unwrap(),
+            to_json_payloads(vec![json!({ "user": "U024BE7LH" })])
+        );
+        assert_eq!(request.signal_name, "approval");
+        assert_eq!(
+            request.signal_input.unwrap(),
+            to_json_payloads(vec![json!("approved"), json!(2)])
+        );
+        assert_eq!(request.request_id, "some-request-id");
+        assert_eq!(request.identity, "SecEngTemporalAPIG");
+
+        let request = build_signal_with_start_request(SignalWithStartTemporal::default());
+        assert_eq!(request.input, None);
+        assert_eq!(request.signal_input, None);
+        assert!(Uuid::parse_str(&request.request_id).is_ok());
+    }
+
+    fn build_update(wait_stage: Option<UpdateWaitStage>) -> UpdateTemporal {
+        UpdateTemporal {
+            namespace: "test-namespace".into(),
+            task_queue: "test-task-queue-rs".into(),
+            workflow_id: "some-super-long-uuid-string".into(),
+            update_name: "validate_ticket".into(),
+            args: Some(vec![json!({ "ticket": "SEC-1234" })]),
+            wait_stage,
+            ..Default::default()
+        }
+    }
+
+    fn update_lifecycle_stage(request: &UpdateWorkflowExecutionRequest) -> i32 {
+        request.wait_policy.as_ref().unwrap().lifecycle_stage
+    }
+
+    #[test]
+    fn test_update_request() {
+        let request = build_update_request(build_update(None));
+
+        // waits for the handler's result unless told otherwise
+        assert_eq!(
+            update_lifecycle_stage(&request),
+            UpdateWorkflowExecutionLifecycleStage::Completed as i32
+        );
+        let update = request.request.unwrap();
+        let meta = update.meta.unwrap();
+        assert!(Uuid::parse_str(&meta.update_id).is_ok());
+        assert_eq!(meta.identity, "SecEngTemporalAPIG");
+        let input = update.input.unwrap();
+        assert_eq!(input.name, "validate_ticket");
+        assert_eq!(
+            input.args.unwrap(),
+            to_json_payloads(vec![json!({ "ticket": "SEC-1234" })])
+        );
+
+        let request = build_update_request(UpdateTemporal {
+            update_id: Some("some-update-id".into()),
+            ..build_update(Some(UpdateWaitStage::Accepted))
+        });
+        assert_eq!(
+            update_lifecycle_stage(&request),
+            UpdateWorkflowExecutionLifecycleStage::Accepted as i32
+        );
+        assert_eq!(
+            request.request.unwrap().meta.unwrap().update_id,
+            "some-update-id"
+        );
+    }
 
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
-pub struct TemporalSignalResponse {}
+    fn build_update_response(
+        stage: UpdateWorkflowExecutionLifecycleStage,
+        outcome: Option<outcome::Value>,
+    ) -> UpdateWorkflowExecutionResponse {
+        UpdateWorkflowExecutionResponse {
+            update_ref: Some(UpdateRef {
+                update_id: "some-update-id".into(),
+                ..Default::default()
+            }),
+            outcome: outcome.map(|value| Outcome { value: Some(value) }),
+            stage: stage as i32,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_update_response_outcomes() {
+        let success = update_response(build_update_response(
+            UpdateWorkflowExecutionLifecycleStage::Completed,
+            Some(outcome::Value::Success(to_json_payloads(vec![json!(
+                "approved"
+            )]))),
+        ))
+        .unwrap();
+        assert_eq!(
+            success,
+            TemporalInteractionResponse::Update(TemporalUpdateResponse {
+                update_id: "some-update-id".into(),
+                stage: UpdateWaitStage::Completed,
+                result: Some(vec![json!("approved")]),
+                failure: None,
+            })
+        );
+
+        let failure = update_response(build_update_response(
+            UpdateWorkflowExecutionLifecycleStage::Completed,
+            Some(outcome::Value::Failure(Failure {
+                message: "ticket is already closed".into(),
+                ..Default::default()
+            })),
+        ))
+        .unwrap();
+        assert_eq!(
+            failure,
+            TemporalInteractionResponse::Update(TemporalUpdateResponse {
+                update_id: "some-update-id".into(),
+                stage: UpdateWaitStage::Completed,
+                result: None,
+                failure: Some("ticket is already closed".into()),
+            })
+        );
+
+        // accepted, and the handler hasn't returned yet
+        let accepted = update_response(build_update_response(
+            UpdateWorkflowExecutionLifecycleStage::Accepted,
+            None,
+        ))
+        .unwrap();
+        assert_eq!(
+            accepted,
+            TemporalInteractionResponse::Update(TemporalUpdateResponse {
+                update_id: "some-update-id".into(),
+                stage: UpdateWaitStage::Accepted,
+                result: None,
+                failure: None,
+            })
+        );
+
+        let not_json = update_response(build_update_response(
+            UpdateWorkflowExecutionLifecycleStage::Completed,
+            Some(outcome::Value::Success(Payloads {
+                payloads: vec![Payload {
+                    data: b"\x00binary".to_vec(),
+                    ..Default::default()
+                }],
+            })),
+        ));
+        assert!(not_json.is_err());
+    }
 
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
-pub struct TemporalQueryResponse {
-    pub query_rejected: Option<i32>,
-    pub query_result: Option<Vec<Value>>,
+    #[test]
+    fn test_query_result_is_json() {
+        let response = query_response(QueryWorkflowResponse {
+            query_result: Some(to_json_payloads(vec![
+                json!({"status": "approved"}),
+                json!(3),
+            ])),
+            query_rejected: None,
+        })
+        .unwrap();
+
+        assert_eq!(
+            response,
+            TemporalInteractionResponse::Query(TemporalQueryResponse {
+                query_rejected: None,
+                query_result: Some(vec![json!({"status": "approved"}), json!(3)]),
+            })
+        );
+
+        let not_json = QueryWorkflowResponse {
+            query_result: Some(Payloads {
+                payloads: vec![Payload {
+                    data: b"\x00binary".to_vec(),
+                    ..Default::default()
+                }],
+            }),
+            query_rejected: None,
+        };
+        assert!(query_response(not_json).is_err());
+    }
+
+    #[test]
+    fn test_start_request_without_options() {
+        let request = build_workflow_execution_request(build_execute(None));
+
+        assert_eq!(request.namespace, "test-namespace");
+        assert_eq!(request.workflow_id, "some-super-long-uuid-string");
+        assert_eq!(request.input.unwrap().payloads.len(), 1);
+        assert_eq!(
+            request.workflow_id_reuse_policy,
+            WorkflowIdReusePolicy::Unspecified as i32
+        );
+        assert_eq!(request.workflow_execution_timeout, None);
+        assert_eq!(request.workflow_run_timeout, None);
+        assert_eq!(request.workflow_task_timeout, None);
+        assert_eq!(request.retry_policy, None);
+        assert_eq!(request.cron_schedule, "");
+        assert_eq!(request.memo, None);
+        assert_eq!(request.search_attributes, None);
+    }
+
+    #[test]
+    fn test_start_request_with_options() {
+        let request = build_workflow_execution_request(build_execute(Some(WorkflowStartOptions {
+            execution_timeout_secs: Some(86400),
+            run_timeout_secs: Some(3600),
+            task_timeout_secs: Some(10),
+            id_reuse_policy: Some(temporal_json::WorkflowIdReusePolicy::RejectDuplicate),
+            retry_policy: Some(WorkflowRetryPolicy {
+                initial_interval_secs: Some(1),
+                backoff_coefficient: serde_json::Number::from_f64(1.5),
+                maximum_interval_secs: Some(60),
+                maximum_attempts: Some(3),
+                non_retryable_error_types: Some(vec!["AccessDenied".into()]),
+            }),
+            cron_schedule: Some("0 9 * * MON".into()),
+            memo: Some(BTreeMap::from([(
+                "requested_by".into(),
+                json!("U024BE7LH"),
+            )])),
+            search_attributes: Some(BTreeMap::from([(
+                "CustomKeywordField".into(),
+                json!("access-request"),
+            )])),
+        })));
+
+        // the run timeout isn't the execution timeout
+        assert_eq!(request.workflow_execution_timeout.unwrap().seconds, 86400);
+        assert_eq!(request.workflow_run_timeout.unwrap().seconds, 3600);
+        assert_eq!(request.workflow_task_timeout.unwrap().seconds, 10);
+        assert_eq!(
+            request.workflow_id_reuse_policy,
+            WorkflowIdReusePolicy::RejectDuplicate as i32
+        );
+
+        let retry_policy = request.retry_policy.unwrap();
+        assert_eq!(retry_policy.initial_interval.unwrap().seconds, 1);
+        assert_eq!(retry_policy.backoff_coefficient, 1.5);
+        assert_eq!(retry_policy.maximum_interval.unwrap().seconds, 60);
+        assert_eq!(retry_policy.maximum_attempts, 3);
+        assert_eq!(retry_policy.non_retryable_error_types, ["AccessDenied"]);
+
+        assert_eq!(request.cron_schedule, "0 9 * * MON");
+        assert_eq!(
+            request.memo.unwrap().fields["requested_by"],
+            json!("U024BE7LH").as_json_payload().unwrap()
+        );
+        assert_eq!(
+            request.search_attributes.unwrap().indexed_fields["CustomKeywordField"],
+            json!("access-request").as_json_payload().unwrap()
+        );
+    }
 }
@@ -0,0 +1,103 @@
+//! `cargo xtask codegen`
+use anyhow::{anyhow, Context, Result};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use temporal_json::schema::{temporal_interaction_response_schema, temporal_interaction_schema};
+
+use crate::typescript;
+
+/// re-exports the generated types from the napi generated `index.d.ts`, which `napi build` overwrites
+const NODE_TYPES_EXPORT: &str = "export * from './temporal_interaction'\n";
+
+pub fn run(check: bool) -> Result<()> {
+    let crates_dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .context("xtask is not in the crates directory")?;
+    let schema_dir = crates_dir.join("temporal-json").join("schema");
+    let node_dir = crates_dir.join("temporal-json-node");
+    let ffi_dir = crates_dir.join("temporal-json-ffi");
+
+    let interaction_schema = serde_json::to_value(temporal_interaction_schema())?;
+    let response_schema = serde_json::to_value(temporal_interaction_response_schema())?;
+
+    let mut generated = vec![
+        (
+            schema_dir.join("temporal_interaction.schema.json"),
+            to_pretty_json(&interaction_schema)?,
+        ),
+        (
+            schema_dir.join("temporal_interaction_response.schema.json"),
+            to_pretty_json(&response_schema)?,
+        ),
+        (
+            node_dir.join("temporal_interaction.d.ts"),
+            typescript::from_json_schemas(&[&interaction_schema, &response_schema])?,
+        ),
+        (
+            ffi_dir.join("include").join("temporal_json.h"),
+            c_header(&ffi_dir)?,
+        ),
+    ];
+
+    let index_path = node_dir.join("index.d.ts");
+    let index = fs::read_to_string(&index_path)
+        .with_context(|| format!("unable to read {}", index_path.display()))?;
+    if !index.contains(NODE_TYPES_EXPORT) {
+        generated.push((index_path, format!("{}{}", index, NODE_TYPES_EXPORT)));
+    }
+
+    let mut out_of_date = Vec::new();
+    for (path, contents) in generated {
+        if fs::read_to_string(&path).ok().as_deref() == Some(contents.as_str()) {
+            continue;
+        }
+
+        if check {
+            out_of_date.push(path);
+        } else {
+            fs::create_dir_all(path.parent().expect("generated files are in a directory"))?;
+            fs::write(&path, contents)
+                .with_context(|| format!("unable to write {}", path.display()))?;
+            println!("wrote {}", path.display());
+        }
+    }
+
+    if !out_of_date.is_empty() {
+        return Err(anyhow!(
+            "generated files are out of date, run `cargo xtask codegen`:\n{}",
+            display_paths(&out_of_date)
+        ));
+    }
+
+    Ok(())
+}
+
+/// the FFI crate's C header, configured by its `cbindgen.toml`
+fn c_header(ffi_dir: &Path) -> Result<String> {
+    let config = cbindgen::Config::from_file(ffi_dir.join("cbindgen.toml"))
+        .map_err(|err| anyhow!("unable to read cbindgen.toml, {}", err))?;
+
+    let mut header = Vec::new();
+    cbindgen::Builder::new()
+        .with_config(config)
+        .with_src(ffi_dir.join("src").join("lib.rs"))
+        .generate()
+        .context("unable to generate the C header")?
+        .write(&mut header);
+
+    Ok(String::from_utf8(header)?)
+}
+
+fn to_pretty_json(schema: &serde_json::Value) -> Result<String> {
+    Ok(format!("{}\n", serde_json::to_string_pretty(schema)?))
+}
+
+fn display_paths(paths: &[PathBuf]) -> String {
+    paths
+        .iter()
+        .map(|path| path.display().to_string())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -1,16 +1,30 @@
-mod flags {
-    use std::path::PathBuf;
+mod codegen;
+mod typescript;
+mod wasm;
 
+mod flags {
     xflags::xflags! {
+        cmd xtask {
+            /// Write the JSON Schemas of the temporal-json models, the Node package's TypeScript types generated from them, and the FFI crate's C header.
+            cmd codegen {
+                /// Fail if a generated file is out of date, instead of writing it.
+                optional --check
+            }
 
-    cmd my-command {
-        required path: PathBuf
-        optional -v, --verbose
-    }
+            /// Build the size-optimized temporal-json-wasm package into `crates/temporal-json-wasm/pkg`.
+            cmd wasm {
+                /// wasm-bindgen output target: bundler (default), web, nodejs or deno.
+                optional --target target: String
+            }
+        }
     }
 }
 
-fn main() {
-    let flags = flags::MyCommand::from_env();
-    println!("{:#?}", flags);
+fn main() -> anyhow::Result<()> {
+    let flags = flags::Xtask::from_env_or_exit();
+
+    match flags.subcommand {
+        flags::XtaskCmd::Codegen(codegen) => codegen::run(codegen.check),
+        flags::XtaskCmd::Wasm(wasm) => wasm::run(wasm.target),
+    }
 }
@@ -0,0 +1,179 @@
+//! Just enough of a JSON Schema to TypeScript converter for the schemas schemars generates.
+use anyhow::{anyhow, Context, Result};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+
+const HEADER: &str =
+    "/* generated by `cargo xtask codegen` from the temporal-json JSON Schemas, do not edit */\n";
+const INDENT: &str = "  ";
+
+/// One `export type` per root schema, named after its `title`, and one per definition
+pub fn from_json_schemas(schemas: &[&Value]) -> Result<String> {
+    let mut types = Vec::new();
+    let mut definitions = BTreeMap::new();
+
+    for schema in schemas {
+        let title = schema
+            .get("title")
+            .and_then(Value::as_str)
+            .context("root schema has no title")?;
+        types.push(export_type(title, schema)?);
+
+        if let Some(defs) = schema.get("definitions").and_then(Value::as_object) {
+            definitions.extend(defs.iter());
+        }
+    }
+
+    for (name, schema) in definitions {
+        types.push(export_type(name, schema)?);
+    }
+
+    Ok(format!("{}\n{}", HEADER, types.join("\n")))
+}
+
+fn export_type(name: &str, schema: &Value) -> Result<String> {
+    let ts_type = type_of(schema, 0)?;
+    // unions start on the next line
+    let separator = if ts_type.starts_with('\n') { "" } else { " " };
+    Ok(format!(
+        "{}export type {} ={}{}\n",
+        doc_comment(schema, ""),
+        name,
+        separator,
+        ts_type
+    ))
+}
+
+fn type_of(schema: &Value, depth: usize) -> Result<String> {
+    let schema = match schema {
+        Value::Bool(true) => return Ok("unknown".to_string()),
+        Value::Bool(false) => return Ok("never".to_string()),
+        Value::Object(schema) => schema,
+        _ => return Err(anyhow!("not a schema: {}", schema)),
+    };
+
+    if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+        return reference
+            .strip_prefix("#/definitions/")
+            .map(str::to_string)
+            .ok_or_else(|| anyhow!("unsupported $ref `{}`", reference));
+    }
+
+    for key in ["oneOf", "anyOf"] {
+        if let Some(members) = schema.get(key).and_then(Value::as_array) {
+            return union_of(members, depth);
+        }
+    }
+
+    if let Some(all_of) = schema.get("allOf").and_then(Value::as_array) {
+        // schemars wraps a $ref in allOf to attach a description to it
+        return match all_of.as_slice() {
+            [single] => type_of(single, depth),
+            _ => Err(anyhow!("unsupported allOf with {} schemas", all_of.len())),
+        };
+    }
+
+    if let Some(values) = schema.get("enum").and_then(Value::as_array) {
+        return Ok(values
+            .iter()
+            .map(Value::to_string)
+            .collect::<Vec<_>>()
+            .join(" | "));
+    }
+
+    let types = match schema.get("type") {
+        Some(Value::String(instance_type)) => vec![instance_type.as_str()],
+        Some(Value::Array(instance_types)) => {
+            instance_types.iter().filter_map(Value::as_str).collect()
+        }
+        _ => return Ok("unknown".to_string()),
+    };
+
+    Ok(types
+        .into_iter()
+        .map(|instance_type| instance_type_of(instance_type, schema, depth))
+        .collect::<Result<Vec<_>>>()?
+        .join(" | "))
+}
+
+fn union_of(members: &[Value], depth: usize) -> Result<String> {
+    let indent = INDENT.repeat(depth + 1);
+    members
+        .iter()
+        .map(|member| {
+            Ok(format!(
+                "\n{}{}| {}",
+                doc_comment(member, &indent),
+                indent,
+                type_of(member, depth + 1)?
+            ))
+        })
+        .collect()
+}
+
+fn instance_type_of(
+    instance_type: &str,
+    schema: &Map<String, Value>,
+    depth: usize,
+) -> Result<String> {
+    Ok(match instance_type {
+        "string" => "string".to_string(),
+        "integer" | "number" => "number".to_string(),
+        "boolean" => "boolean".to_string(),
+        "null" => "null".to_string(),
+        "array" => match schema.get("items") {
+            Some(items) => format!("Array<{}>", type_of(items, depth)?),
+            None => "Array<unknown>".to_string(),
+        },
+        "object" => object_of(schema, depth)?,
+        _ => return Err(anyhow!("unsupported type `{}`", instance_type)),
+    })
+}
+
+fn object_of(schema: &Map<String, Value>, depth: usize) -> Result<String> {
+    let properties = schema.get("properties").and_then(Value::as_object);
+    let properties = match properties {
+        Some(properties) if !properties.is_empty() => properties,
+        _ => {
+            return Ok(match schema.get("additionalProperties") {
+                Some(Value::Bool(false)) | None => "Record<string, never>".to_string(),
+                Some(values) => format!("Record<string, {}>", type_of(values, depth)?),
+            })
+        }
+    };
+
+    let required: Vec<&str> = schema
+        .get("required")
+        .and_then(Value::as_array)
+        .map(|required| required.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+
+    let indent = INDENT.repeat(depth + 1);
+    let mut object = String::from("{\n");
+    for (name, property) in properties {
+        object.push_str(&format!(
+            "{}{}{}{}: {}\n",
+            doc_comment(property, &indent),
+            indent,
+            name,
+            if required.contains(&name.as_str()) {
+                ""
+            } else {
+                "?"
+            },
+            type_of(property, depth + 1)?
+        ));
+    }
+    object.push_str(&INDENT.repeat(depth));
+    object.push('}');
+
+    Ok(object)
+}
+
+/// JSDoc from the schema's `description`, which schemars fills from doc comments
+fn doc_comment(schema: &Value, indent: &str) -> String {
+    match schema.get("description").and_then(Value::as_str) {
+        Some(description) => format!("{}/** {} */\n", indent, description),
+        None => String::new(),
+    }
+}
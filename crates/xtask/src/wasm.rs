@@ -0,0 +1,77 @@
+//! `cargo xtask wasm`
+use anyhow::{anyhow, Context, Result};
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+const WASM_TARGET: &str = "wasm32-unknown-unknown";
+/// size-optimized profile in the workspace Cargo.toml
+const WASM_PROFILE: &str = "wasm-release";
+
+pub fn run(target: Option<String>) -> Result<()> {
+    let workspace_dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .ancestors()
+        .nth(2)
+        .context("xtask is not in the crates directory")?;
+    let target_dir = env::var_os("CARGO_TARGET_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| workspace_dir.join("target"));
+    let out_dir = workspace_dir
+        .join("crates")
+        .join("temporal-json-wasm")
+        .join("pkg");
+
+    run_command(
+        Command::new(env::var("CARGO").unwrap_or_else(|_| "cargo".to_string()))
+            .current_dir(workspace_dir)
+            .args(["build", "--package", "temporal-json-wasm"])
+            .args(["--target", WASM_TARGET, "--profile", WASM_PROFILE]),
+    )?;
+
+    let wasm = target_dir
+        .join(WASM_TARGET)
+        .join(WASM_PROFILE)
+        .join("temporal_json_wasm.wasm");
+    run_command(
+        Command::new("wasm-bindgen")
+            .args(["--target", target.as_deref().unwrap_or("bundler")])
+            .arg("--out-dir")
+            .arg(&out_dir)
+            .arg(&wasm),
+    )
+    .context("install the wasm-bindgen CLI at the version in Cargo.lock")?;
+
+    let bindgen_wasm = out_dir.join("temporal_json_wasm_bg.wasm");
+    // wasm-opt is optional, it's in binaryen rather than on crates.io
+    match Command::new("wasm-opt")
+        .arg("-Oz")
+        .arg(&bindgen_wasm)
+        .arg("-o")
+        .arg(&bindgen_wasm)
+        .status()
+    {
+        Ok(status) if status.success() => {}
+        Ok(status) => return Err(anyhow!("wasm-opt failed with {}", status)),
+        Err(_) => println!("wasm-opt not found, skipping it"),
+    }
+
+    println!(
+        "wrote {} ({} bytes)",
+        bindgen_wasm.display(),
+        fs::metadata(&bindgen_wasm)?.len()
+    );
+    Ok(())
+}
+
+fn run_command(command: &mut Command) -> Result<()> {
+    let status = command
+        .status()
+        .with_context(|| format!("unable to run {:?}", command))?;
+    if !status.success() {
+        return Err(anyhow!("{:?} failed with {}", command, status));
+    }
+
+    Ok(())
+}